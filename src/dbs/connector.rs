@@ -1,13 +1,46 @@
 use super::error::DatabaseError;
 use super::models::{DbConfig, DbConnection};
-use crate::sys::env;
-use std::sync::Arc;
-use surrealdb::opt::auth::Namespace;
+use crate::sys::{config::ConfigReader, env};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use surrealdb::{
+    Surreal,
+    engine::any::Any,
+    opt::auth::{Namespace, Root},
+};
 
 /// Establishes a connection to the `SurrealDB` database.
 /// # Errors
 /// Returns `DatabaseError::ConnectionError` or `DatabaseError::AuthenticationError` on failure.
 pub async fn connect(config: &DbConfig) -> Result<DbConnection, DatabaseError> {
+    let db = open(config).await?;
+    db.signin(Namespace {
+        namespace: &config.namespace,
+        username: &config.username,
+        password: config.password.expose(),
+    })
+    .await
+    .map_err(|e| DatabaseError::AuthenticationError(e.to_string()))?;
+
+    Ok(Arc::new(db))
+}
+
+/// Establishes a connection signed in as a root user, which may define and
+/// use any namespace.
+/// # Errors
+/// Returns `DatabaseError::ConnectionError` or `DatabaseError::AuthenticationError` on failure.
+pub async fn connect_root(config: &DbConfig) -> Result<DbConnection, DatabaseError> {
+    let db = open(config).await?;
+    db.signin(Root {
+        username: &config.username,
+        password: config.password.expose(),
+    })
+    .await
+    .map_err(|e| DatabaseError::AuthenticationError(e.to_string()))?;
+
+    Ok(Arc::new(db))
+}
+
+async fn open(config: &DbConfig) -> Result<Surreal<Any>, DatabaseError> {
     let db = surrealdb::engine::any::connect(&config.endpoint)
         .await
         .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
@@ -17,37 +50,81 @@ pub async fn connect(config: &DbConfig) -> Result<DbConnection, DatabaseError> {
         .await
         .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
 
-    db.signin(Namespace {
-        namespace: &config.namespace,
-        username: &config.username,
-        password: &config.password,
-    })
-    .await
-    .map_err(|e| DatabaseError::AuthenticationError(e.to_string()))?;
-
-    Ok(Arc::new(db))
+    Ok(db)
 }
 
 impl DbConfig {
-    /// Creates a database configuration from environment variables.
-    /// # Errors
-    /// Returns `DatabaseError::ConfigError` if any required environment variable is not set.
-    pub fn from_env() -> Result<Self, DatabaseError> {
-        Ok(Self {
-            endpoint: env::get_required("DB_ENDPOINT")
-                .map_err(|e| DatabaseError::ConfigError(e.to_string()))?,
-
-            namespace: env::get_required("DB_NAMESPACE")
-                .map_err(|e| DatabaseError::ConfigError(e.to_string()))?,
-
-            database: env::get_required("DB_NAME")
-                .map_err(|e| DatabaseError::ConfigError(e.to_string()))?,
-
-            username: env::get_required("DB_USERNAME")
-                .map_err(|e| DatabaseError::ConfigError(e.to_string()))?,
-
-            password: env::get_required("DB_PASSWORD")
-                .map_err(|e| DatabaseError::ConfigError(e.to_string()))?,
-        })
+    /// Creates the primary database configuration from `DB_*` settings.
+    ///
+    /// Missing connection settings are recorded as configuration issues.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        Self::load(
+            config,
+            |field| format!("DB_{field}"),
+            Duration::from_secs(10),
+            Duration::from_secs(5),
+        )
+    }
+
+    /// Creates the configuration of a named database from its
+    /// `DB__<NAME>__*` settings, e.g. `DB__ANALYTICS__ENDPOINT`.
+    ///
+    /// Timeouts default to those of the `primary` database.
+    pub fn from_config_group(config: &mut ConfigReader, name: &str, primary: &Self) -> Self {
+        Self::load(
+            config,
+            |field| env::group_key("DB", name, field),
+            primary.connection_timeout,
+            primary.health_check_timeout,
+        )
+    }
+
+    /// Creates every named database configuration found in any layer.
+    pub fn named_from_config(config: &mut ConfigReader, primary: &Self) -> BTreeMap<String, Self> {
+        config
+            .groups("DB")
+            .into_iter()
+            .map(|name| {
+                let db = Self::from_config_group(config, &name, primary);
+                (name.to_lowercase(), db)
+            })
+            .collect()
+    }
+
+    fn load(
+        config: &mut ConfigReader,
+        key: impl Fn(&str) -> String,
+        default_connection_timeout: Duration,
+        default_health_check_timeout: Duration,
+    ) -> Self {
+        let connection_timeout =
+            config.duration(&key("CONNECTION_TIMEOUT"), default_connection_timeout);
+        let health_check_timeout =
+            config.duration(&key("HEALTH_CHECK_TIMEOUT"), default_health_check_timeout);
+
+        for (field, timeout) in [
+            ("CONNECTION_TIMEOUT", connection_timeout),
+            ("HEALTH_CHECK_TIMEOUT", health_check_timeout),
+        ] {
+            if timeout.is_zero() {
+                config.invalid(&key(field), "must be greater than zero");
+            }
+        }
+
+        let endpoint_key = key("ENDPOINT");
+        let endpoint = config.required(&endpoint_key);
+        if !endpoint.is_empty() && env::parse_url(&endpoint).is_none() {
+            config.invalid(&endpoint_key, format!("'{endpoint}' is not a valid URL"));
+        }
+
+        Self {
+            endpoint,
+            namespace: config.required(&key("NAMESPACE")),
+            database: config.required(&key("NAME")),
+            username: config.required(&key("USERNAME")),
+            password: config.secret(&key("PASSWORD")),
+            connection_timeout,
+            health_check_timeout,
+        }
     }
 }
@@ -12,6 +12,11 @@ pub enum DatabaseError {
     QueryError(String),
     AuthenticationError(String),
     NotFound(String),
+    /// A write conflicts with the current state, such as a record that
+    /// already exists.
+    Conflict(String),
+    /// A write was based on an outdated version of a record.
+    VersionMismatch(String),
     ConfigError(String),
 }
 
@@ -23,6 +28,8 @@ impl fmt::Display for DatabaseError {
             Self::AuthenticationError(msg) => write!(f, "Authentication error: {msg}"),
 
             Self::NotFound(msg) => write!(f, "Not found: {msg}"),
+            Self::Conflict(msg) => write!(f, "Conflict: {msg}"),
+            Self::VersionMismatch(msg) => write!(f, "Version mismatch: {msg}"),
             Self::ConfigError(msg) => write!(f, "Configuration error: {msg}"),
         }
     }
@@ -40,12 +47,33 @@ impl IntoResponse for DatabaseError {
 
             Self::AuthenticationError(msg) => (StatusCode::UNAUTHORIZED, msg),
             Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            Self::Conflict(msg) | Self::VersionMismatch(msg) => (StatusCode::CONFLICT, msg),
         };
         let body = Json(json!({"error":error_message}));
         (status, body).into_response()
     }
 }
 
+/// Returns the error of the statement that failed a transaction, rather
+/// than the errors of the statements that were cancelled with it.
+pub(crate) fn transaction_failure(response: &mut surrealdb::Response) -> Option<surrealdb::Error> {
+    let mut errors: Vec<_> = response.take_errors().into_iter().collect();
+    errors.sort_by_key(|(index, _)| *index);
+    let cause = errors
+        .iter()
+        .position(|(_, e)| {
+            !e.to_string()
+                .contains("not executed due to a failed transaction")
+        })
+        .unwrap_or(0);
+    errors.into_iter().nth(cause).map(|(_, e)| e)
+}
+
+/// Returns true if `error` reports that a created record already exists.
+pub(crate) fn is_duplicate(error: &surrealdb::Error) -> bool {
+    error.to_string().contains("already exists")
+}
+
 impl From<surrealdb::Error> for DatabaseError {
     fn from(err: surrealdb::Error) -> Self {
         Self::QueryError(err.to_string())
@@ -0,0 +1,154 @@
+use super::{
+    error::{DatabaseError, transaction_failure},
+    models::DbConnection,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// A SurrealQL script applied once to a database.
+#[derive(Debug, Clone, Serialize)]
+pub struct Migration {
+    /// The file name without `.surql`, such as `0001_create_notes`.
+    pub version: String,
+    /// SHA-256 of the script, to detect scripts changed after they ran.
+    pub checksum: String,
+    #[serde(skip)]
+    pub script: String,
+}
+
+/// A migration recorded in the `migration` table of a database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: String,
+    pub checksum: String,
+    pub applied_at: i64,
+}
+
+/// The migrations of a directory of `*.surql` files, applied in file name
+/// order.
+///
+/// Each migration runs in a transaction of its own together with its
+/// record in the `migration` table, so a failed migration leaves nothing
+/// behind and is attempted again on the next run. Scripts must not contain
+/// transaction statements, and must not be changed once applied.
+#[derive(Debug, Clone, Default)]
+pub struct Migrations {
+    migrations: Vec<Migration>,
+}
+
+impl Migrations {
+    /// Reads every `*.surql` file in `dir`. A missing directory has no
+    /// migrations.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::ConfigError` if the directory or a file
+    /// cannot be read.
+    pub fn load(dir: &Path) -> Result<Self, DatabaseError> {
+        if !dir.exists() {
+            warn!(dir = %dir.display(), "Migrations directory does not exist");
+            return Ok(Self::default());
+        }
+        let unreadable = |e: std::io::Error| {
+            DatabaseError::ConfigError(format!(
+                "Migrations in '{}' cannot be read: {e}",
+                dir.display()
+            ))
+        };
+
+        let mut migrations = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(unreadable)? {
+            let path = entry.map_err(unreadable)?.path();
+            if path.extension().is_none_or(|ext| ext != "surql") {
+                continue;
+            }
+            let Some(version) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let script = std::fs::read_to_string(&path).map_err(unreadable)?;
+            migrations.push(Migration {
+                version: version.to_string(),
+                checksum: hex::encode(Sha256::digest(script.as_bytes())),
+                script,
+            });
+        }
+        migrations.sort_by(|a, b| a.version.cmp(&b.version));
+        Ok(Self { migrations })
+    }
+
+    /// Returns the migrations in the order they are applied.
+    #[must_use]
+    pub fn all(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// Returns the migrations recorded in `db`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the query fails.
+    pub async fn applied(db: &DbConnection) -> Result<Vec<AppliedMigration>, DatabaseError> {
+        Ok(db
+            .query(
+                "SELECT meta::id(id) AS version, checksum, applied_at FROM migration \
+                 ORDER BY version",
+            )
+            .await?
+            .take(0)?)
+    }
+
+    /// Applies the migrations not yet recorded in `db`, in order. Returns
+    /// the versions applied.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::Conflict` if an applied migration's script has
+    ///   changed since
+    /// - `DatabaseError::QueryError` if a migration fails, in which case it
+    ///   and the migrations after it are not applied
+    pub async fn apply(&self, db: &DbConnection) -> Result<Vec<String>, DatabaseError> {
+        let applied = Self::applied(db).await?;
+        for migration in &self.migrations {
+            let recorded = applied.iter().find(|a| a.version == migration.version);
+            if let Some(recorded) = recorded
+                && recorded.checksum != migration.checksum
+            {
+                return Err(DatabaseError::Conflict(format!(
+                    "Migration '{}' was changed after it was applied",
+                    migration.version
+                )));
+            }
+        }
+
+        let mut versions = Vec::new();
+        for migration in &self.migrations {
+            if applied.iter().any(|a| a.version == migration.version) {
+                continue;
+            }
+            let query = format!(
+                "BEGIN TRANSACTION;\n{}\n;\n\
+                 CREATE type::thing('migration', $version) \
+                 CONTENT {{ checksum: $checksum, applied_at: $now }};\n\
+                 COMMIT TRANSACTION;",
+                migration.script.trim().trim_end_matches(';')
+            );
+            let mut response = db
+                .query(query)
+                .bind(("version", migration.version.clone()))
+                .bind(("checksum", migration.checksum.clone()))
+                .bind(("now", chrono::Utc::now().timestamp_millis()))
+                .await?;
+            if let Some(e) = transaction_failure(&mut response) {
+                return Err(DatabaseError::QueryError(format!(
+                    "Migration '{}' failed: {e}",
+                    migration.version
+                )));
+            }
+            info!(version = %migration.version, "Applied migration");
+            versions.push(migration.version.clone());
+        }
+        Ok(versions)
+    }
+}
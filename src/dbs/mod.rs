@@ -1,4 +1,6 @@
 pub mod connector;
 pub mod error;
 pub mod health;
+pub mod migrations;
 pub mod models;
+pub mod repository;
@@ -1,17 +1,79 @@
-use std::sync::Arc;
+use crate::sys::{audit::AuditAction, env::Secret, events::Event, middleware::conditional::ETag};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
 use surrealdb::Surreal;
 use surrealdb::engine::any::Any;
 
 pub type DbConnection = Arc<Surreal<Any>>;
 pub struct Database {
+    /// Component name reported by the health check.
+    pub name: String,
     pub db: DbConnection,
+    pub health_check_timeout: Duration,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct DbConfig {
     pub endpoint: String,
     pub namespace: String,
     pub database: String,
     pub username: String,
-    pub password: String,
+    pub password: Secret,
+    pub connection_timeout: Duration,
+    pub health_check_timeout: Duration,
+}
+
+impl DbConfig {
+    /// Returns true if connecting with `other` would reach a different
+    /// database or use different credentials.
+    #[must_use]
+    pub fn connection_differs(&self, other: &Self) -> bool {
+        self.endpoint != other.endpoint
+            || self.namespace != other.namespace
+            || self.database != other.database
+            || self.username != other.username
+            || self.password != other.password
+    }
+}
+
+/// A record of a [`Repository`](super::repository::Repository) with its key,
+/// version and deletion time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub id: String,
+    /// Starts at 1 and is incremented by every update.
+    pub version: u64,
+    /// When the record was soft-deleted, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// ETag of this version, to be matched by `If-Match` on updates.
+    #[must_use]
+    pub fn etag(&self) -> ETag {
+        ETag::from_version(self.version)
+    }
+}
+
+/// Published on the event bus after a write of a repository created with
+/// [`Repository::with_event_bus`](super::repository::Repository::with_event_bus).
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordChanged {
+    pub table: String,
+    /// The tenant whose data changed, for tenant repositories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub id: String,
+    pub action: AuditAction,
+    pub version: u64,
+    /// The record as written, or as it was before it was purged.
+    pub record: Arc<Value>,
+}
+
+impl Event for RecordChanged {
+    const NAME: &'static str = "record.changed";
 }
@@ -0,0 +1,601 @@
+use super::{
+    error::DatabaseError,
+    models::{DbConnection, RecordChanged, Versioned},
+};
+use crate::sys::{
+    audit::{AuditAction, AuditContext},
+    cache::Cache,
+    events::EventBus,
+    outbox::record_events,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{marker::PhantomData, sync::Arc};
+use tracing::warn;
+
+/// Fields selected for every record: its own fields plus the record key as `id`.
+const FIELDS: &str = "*, meta::id(id) AS id";
+
+/// The content written for a record: its fields plus its version.
+#[derive(Serialize)]
+struct Content<'a, T> {
+    #[serde(flatten)]
+    data: &'a T,
+    version: u64,
+}
+
+/// Typed access to the records of one table with optimistic concurrency.
+///
+/// Every record carries a `version` that starts at 1 and is incremented by
+/// each update. Updates and deletes name the version they were based on and
+/// fail with `DatabaseError::VersionMismatch` if the record changed in
+/// between, so concurrent writers cannot overwrite each other. `T` must not
+/// have fields named `id`, `version` or `deleted_at`.
+///
+/// With [`Repository::soft_delete`], deleting only sets `deleted_at` and
+/// the record is hidden from reads until restored. With
+/// [`Repository::audited`], every write records an event in `audit_log` in
+/// the same transaction, and with [`Repository::with_events`] it records an
+/// event in the outbox to be relayed. With [`Repository::with_event_bus`],
+/// every write publishes [`RecordChanged`] once it is committed. With
+/// [`Repository::cached`], reads go through the cache and writes invalidate
+/// them. [`Repository::for_tenant`] marks the data as a tenant's.
+pub struct Repository<T> {
+    db: DbConnection,
+    table: String,
+    tenant: Option<String>,
+    soft_delete: bool,
+    audited: bool,
+    events: bool,
+    bus: Option<EventBus>,
+    cache: Option<RecordCache>,
+    context: AuditContext,
+    record: PhantomData<fn() -> T>,
+}
+
+/// The cache reads of a repository go through, and the tag its entries are
+/// stored with.
+#[derive(Clone)]
+struct RecordCache {
+    cache: Cache,
+    tag: String,
+}
+
+impl<T> Clone for Repository<T> {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+            table: self.table.clone(),
+            tenant: self.tenant.clone(),
+            soft_delete: self.soft_delete,
+            audited: self.audited,
+            events: self.events,
+            bus: self.bus.clone(),
+            cache: self.cache.clone(),
+            context: self.context.clone(),
+            record: PhantomData,
+        }
+    }
+}
+
+/// A write whose result is the written record.
+struct Write {
+    action: AuditAction,
+    statement: String,
+    id: Option<String>,
+    expected_version: Option<u64>,
+    content: Option<serde_json::Value>,
+}
+
+impl<T> Repository<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync,
+{
+    #[must_use]
+    pub fn new(db: DbConnection, table: impl Into<String>) -> Self {
+        Self {
+            db,
+            table: table.into(),
+            tenant: None,
+            soft_delete: false,
+            audited: false,
+            events: false,
+            bus: None,
+            cache: None,
+            context: AuditContext::default(),
+            record: PhantomData,
+        }
+    }
+
+    /// Deletes by setting `deleted_at`, so records can be restored.
+    #[must_use]
+    pub fn soft_delete(mut self) -> Self {
+        self.soft_delete = true;
+        self
+    }
+
+    /// Records every write in `audit_log`.
+    #[must_use]
+    pub fn audited(mut self) -> Self {
+        self.audited = true;
+        self
+    }
+
+    /// Records an outbox event such as `<table>.created` for every write, in
+    /// the same transaction. The aggregate is the record, and the payload is
+    /// the record as written, or as it was before it was purged. The
+    /// repository must use the primary database, which holds the outbox, so
+    /// writes of tenant repositories fail with `DatabaseError::ConfigError`.
+    #[must_use]
+    pub fn with_events(mut self) -> Self {
+        self.events = true;
+        self
+    }
+
+    /// Publishes [`RecordChanged`] on `bus` after every write.
+    #[must_use]
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Serves [`Repository::get`] and [`Repository::list`] from `cache`,
+    /// storing entries with `tag` for the default TTL. Every write
+    /// invalidates the tag, so it must name this table in this database
+    /// only, such as `users`; tenant repositories prefix it with
+    /// `tenant:<id>:`. With the memory store, writes made by other
+    /// instances are only seen once entries expire.
+    #[must_use]
+    pub fn cached(mut self, cache: Cache, tag: impl Into<String>) -> Self {
+        self.cache = Some(RecordCache {
+            cache,
+            tag: tag.into(),
+        });
+        self
+    }
+
+    /// Marks the records as data of `tenant`, kept in its database.
+    /// [`RecordChanged`] names the tenant, and cache tags are scoped to it.
+    #[must_use]
+    pub fn for_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Returns a repository whose writes are audited as made by `context`.
+    #[must_use]
+    pub fn with_context(&self, context: AuditContext) -> Self {
+        Self {
+            context,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the table name.
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Creates a record with a generated id at version 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the record cannot be written.
+    pub async fn create(&self, data: &T) -> Result<Versioned<T>, DatabaseError> {
+        let created = self
+            .write(Write {
+                action: AuditAction::Create,
+                statement: "CREATE type::table($table) CONTENT $content RETURN AFTER".to_string(),
+                id: None,
+                expected_version: None,
+                content: Some(content(data, 1)?),
+            })
+            .await?;
+        created.ok_or_else(|| {
+            DatabaseError::QueryError(format!("Failed to create a {} record", self.table))
+        })
+    }
+
+    /// Creates a record with the given id at version 1.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::Conflict` if a record with this id exists, even if
+    ///   it is deleted
+    /// - `DatabaseError::QueryError` if the record cannot be written
+    pub async fn create_with_id(&self, id: &str, data: &T) -> Result<Versioned<T>, DatabaseError> {
+        let created = self
+            .write(Write {
+                action: AuditAction::Create,
+                statement: "CREATE type::thing($table, $id) CONTENT $content RETURN AFTER"
+                    .to_string(),
+                id: Some(id.to_string()),
+                expected_version: None,
+                content: Some(content(data, 1)?),
+            })
+            .await;
+        match created {
+            Ok(Some(created)) => Ok(created),
+            // Creating fails if the record exists
+            _ if self.get_including_deleted(id).await?.is_some() => Err(DatabaseError::Conflict(
+                format!("{} '{id}' already exists", self.table),
+            )),
+            Ok(None) => Err(DatabaseError::QueryError(format!(
+                "Failed to create {} '{id}'",
+                self.table
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the record with the given id, unless it is deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn get(&self, id: &str) -> Result<Option<Versioned<T>>, DatabaseError> {
+        let record = match &self.cache {
+            Some(cached) => {
+                let tag = self.cache_tag(cached);
+                let key = format!("{tag}:record:{id}");
+                cached
+                    .cache
+                    .get_or_load(&key, None, &[&tag], || self.get_including_deleted(id))
+                    .await?
+            }
+            None => self.get_including_deleted(id).await?,
+        };
+        Ok(record.filter(|record| record.deleted_at.is_none()))
+    }
+
+    /// Returns the record with the given id, even if it is deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn get_including_deleted(
+        &self,
+        id: &str,
+    ) -> Result<Option<Versioned<T>>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!("SELECT {FIELDS} FROM type::thing($table, $id)"))
+            .bind(("table", self.table.clone()))
+            .bind(("id", id.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Returns up to `limit` records that are not deleted, starting at
+    /// `start`, ordered by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn list(&self, start: u64, limit: u64) -> Result<Vec<Versioned<T>>, DatabaseError> {
+        let Some(cached) = &self.cache else {
+            return self.list_where("deleted_at IS NONE", start, limit).await;
+        };
+        let tag = self.cache_tag(cached);
+        let key = format!("{tag}:list:{start}:{limit}");
+        cached
+            .cache
+            .get_or_load(&key, None, &[&tag], || {
+                self.list_where("deleted_at IS NONE", start, limit)
+            })
+            .await
+    }
+
+    /// Returns up to `limit` deleted records, starting at `start`, ordered
+    /// by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn list_deleted(
+        &self,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Versioned<T>>, DatabaseError> {
+        self.list_where("deleted_at IS NOT NONE", start, limit)
+            .await
+    }
+
+    /// Replaces the fields of a record if it is still at `expected_version`
+    /// and increments its version.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::NotFound` if the record does not exist or is deleted
+    /// - `DatabaseError::VersionMismatch` if the record is at another version
+    /// - `DatabaseError::QueryError` if the record cannot be written
+    pub async fn update(
+        &self,
+        id: &str,
+        expected_version: u64,
+        data: &T,
+    ) -> Result<Versioned<T>, DatabaseError> {
+        let updated = self
+            .write(Write {
+                action: AuditAction::Update,
+                statement: "UPDATE type::thing($table, $id) CONTENT $content \
+                            WHERE version = $expected AND deleted_at IS NONE RETURN AFTER"
+                    .to_string(),
+                id: Some(id.to_string()),
+                expected_version: Some(expected_version),
+                content: Some(content(data, expected_version + 1)?),
+            })
+            .await?;
+        match updated {
+            Some(updated) => Ok(updated),
+            None => Err(self.write_failed(id, expected_version, false).await?),
+        }
+    }
+
+    /// Deletes a record if it is still at `expected_version`.
+    ///
+    /// With soft delete the record is kept with `deleted_at` set and its
+    /// version incremented; otherwise it is removed permanently.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::NotFound` if the record does not exist or is deleted
+    /// - `DatabaseError::VersionMismatch` if the record is at another version
+    /// - `DatabaseError::QueryError` if the record cannot be deleted
+    pub async fn delete(
+        &self,
+        id: &str,
+        expected_version: u64,
+    ) -> Result<Versioned<T>, DatabaseError> {
+        if !self.soft_delete {
+            return self.purge(id, expected_version).await;
+        }
+        let deleted = self
+            .write(Write {
+                action: AuditAction::SoftDelete,
+                statement: "UPDATE type::thing($table, $id) \
+                            SET deleted_at = $now, version = $expected + 1 \
+                            WHERE version = $expected AND deleted_at IS NONE RETURN AFTER"
+                    .to_string(),
+                id: Some(id.to_string()),
+                expected_version: Some(expected_version),
+                content: None,
+            })
+            .await?;
+        match deleted {
+            Some(deleted) => Ok(deleted),
+            None => Err(self.write_failed(id, expected_version, false).await?),
+        }
+    }
+
+    /// Restores a soft-deleted record if it is still at `expected_version`
+    /// and increments its version.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::NotFound` if the record does not exist
+    /// - `DatabaseError::VersionMismatch` if the record is at another version
+    /// - `DatabaseError::Conflict` if the record is not deleted
+    /// - `DatabaseError::QueryError` if the record cannot be written
+    pub async fn restore(
+        &self,
+        id: &str,
+        expected_version: u64,
+    ) -> Result<Versioned<T>, DatabaseError> {
+        let restored = self
+            .write(Write {
+                action: AuditAction::Restore,
+                statement: "UPDATE type::thing($table, $id) \
+                            SET deleted_at = NONE, version = $expected + 1 \
+                            WHERE version = $expected AND deleted_at IS NOT NONE RETURN AFTER"
+                    .to_string(),
+                id: Some(id.to_string()),
+                expected_version: Some(expected_version),
+                content: None,
+            })
+            .await?;
+        match restored {
+            Some(restored) => Ok(restored),
+            None => Err(self.write_failed(id, expected_version, true).await?),
+        }
+    }
+
+    /// Permanently removes a record, deleted or not, if it is still at
+    /// `expected_version`. Returns the removed record.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::NotFound` if the record does not exist
+    /// - `DatabaseError::VersionMismatch` if the record is at another version
+    /// - `DatabaseError::QueryError` if the record cannot be deleted
+    pub async fn purge(
+        &self,
+        id: &str,
+        expected_version: u64,
+    ) -> Result<Versioned<T>, DatabaseError> {
+        let purged = self
+            .write(Write {
+                action: AuditAction::Delete,
+                statement: "DELETE type::thing($table, $id) WHERE version = $expected \
+                            RETURN BEFORE"
+                    .to_string(),
+                id: Some(id.to_string()),
+                expected_version: Some(expected_version),
+                content: None,
+            })
+            .await?;
+        match purged {
+            Some(purged) => Ok(purged),
+            None => Err(self.write_failed(id, expected_version, true).await?),
+        }
+    }
+
+    async fn list_where(
+        &self,
+        condition: &str,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Versioned<T>>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM type::table($table) WHERE {condition} \
+                 ORDER BY id LIMIT $limit START $start"
+            ))
+            .bind(("table", self.table.clone()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+
+    /// Returns the tag cached entries are stored with, scoped to the tenant.
+    fn cache_tag(&self, cached: &RecordCache) -> String {
+        match &self.tenant {
+            Some(tenant) => format!("tenant:{tenant}:{}", cached.tag),
+            None => cached.tag.clone(),
+        }
+    }
+
+    /// Runs a write and, for audited repositories or ones with events,
+    /// records it in `audit_log` or the outbox in the same transaction.
+    /// Returns `None` if the write matched nothing.
+    async fn write(&self, write: Write) -> Result<Option<Versioned<T>>, DatabaseError> {
+        if self.events && self.tenant.is_some() {
+            // The relay only reads the outbox of the primary database
+            return Err(DatabaseError::ConfigError(format!(
+                "Outbox events cannot be recorded for {} in a tenant database",
+                self.table
+            )));
+        }
+        let written = format!("SELECT {FIELDS} FROM ({})", write.statement);
+        let query = if self.audited || self.events {
+            let before = if self.audited && write.id.is_some() {
+                format!("(SELECT {FIELDS} FROM type::thing($table, $id))[0]")
+            } else {
+                "NONE".to_string()
+            };
+            let after = if write.action == AuditAction::Delete {
+                "NONE"
+            } else {
+                "$written[0]"
+            };
+            let mut recorded = Vec::new();
+            if self.audited {
+                recorded.push(format!(
+                    "CREATE audit_log CONTENT {{
+                         record_table: $table,
+                         record_id: $written[0].id,
+                         action: $action,
+                         actor: $actor,
+                         request_id: $request_id,
+                         timestamp: $now,
+                         before: $before,
+                         after: {after}
+                     }} RETURN NONE"
+                ));
+            }
+            if self.events {
+                recorded.push(record_events(
+                    "[{
+                         aggregate_type: $table,
+                         aggregate_id: $written[0].id,
+                         event: $event,
+                         payload: $written[0],
+                         created_at: $now
+                     }]",
+                ));
+            }
+            format!(
+                "BEGIN TRANSACTION;
+                 LET $before = {before};
+                 LET $written = ({written});
+                 IF array::len($written) > 0 {{
+                     {};
+                 }};
+                 RETURN $written;
+                 COMMIT TRANSACTION;",
+                recorded.join(";\n")
+            )
+        } else {
+            written
+        };
+
+        let written: Option<Versioned<T>> = self
+            .db
+            .query(query)
+            .bind(("table", self.table.clone()))
+            .bind(("id", write.id))
+            .bind(("expected", write.expected_version))
+            .bind(("content", write.content))
+            .bind(("now", chrono::Utc::now().timestamp_millis()))
+            .bind(("action", write.action.as_str()))
+            .bind((
+                "event",
+                format!("{}.{}", self.table, event_name(write.action)),
+            ))
+            .bind(("actor", self.context.actor.clone()))
+            .bind(("request_id", self.context.request_id.clone()))
+            .await?
+            .take(0)?;
+
+        if let (Some(cached), Some(_)) = (&self.cache, &written) {
+            // Entries left behind expire with the default TTL
+            let tag = self.cache_tag(cached);
+            if let Err(e) = cached.cache.invalidate_tag(&tag).await {
+                warn!(tag = %tag, error = %e, "Failed to invalidate cached records");
+            }
+        }
+        if let (Some(bus), Some(written)) = (&self.bus, &written) {
+            let record = serde_json::to_value(written).unwrap_or_default();
+            bus.publish(RecordChanged {
+                table: self.table.clone(),
+                tenant: self.tenant.clone(),
+                id: written.id.clone(),
+                action: write.action,
+                version: written.version,
+                record: Arc::new(record),
+            });
+        }
+        Ok(written)
+    }
+
+    /// Explains why a write conditioned on `expected_version` matched nothing.
+    async fn write_failed(
+        &self,
+        id: &str,
+        expected_version: u64,
+        include_deleted: bool,
+    ) -> Result<DatabaseError, DatabaseError> {
+        let current = self
+            .get_including_deleted(id)
+            .await?
+            .filter(|record| include_deleted || record.deleted_at.is_none());
+        Ok(match current {
+            None => DatabaseError::NotFound(format!("{} '{id}' does not exist", self.table)),
+            Some(current) if current.version != expected_version => {
+                DatabaseError::VersionMismatch(format!(
+                    "{} '{id}' is at version {}, not {expected_version}",
+                    self.table, current.version
+                ))
+            }
+            Some(_) => DatabaseError::Conflict(format!("{} '{id}' is not deleted", self.table)),
+        })
+    }
+}
+
+/// Names the outbox event raised by a write, after the table name.
+const fn event_name(action: AuditAction) -> &'static str {
+    match action {
+        AuditAction::Create => "created",
+        AuditAction::Update => "updated",
+        AuditAction::SoftDelete | AuditAction::Delete => "deleted",
+        AuditAction::Restore => "restored",
+    }
+}
+
+/// Converts a record's fields and version to the content written to the table.
+fn content<T: Serialize>(data: &T, version: u64) -> Result<serde_json::Value, DatabaseError> {
+    serde_json::to_value(Content { data, version })
+        .map_err(|e| DatabaseError::QueryError(format!("Record cannot be stored: {e}")))
+}
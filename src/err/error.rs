@@ -1,5 +1,5 @@
 use crate::dbs::error::DatabaseError;
-use crate::sys::env::EnvironmentError;
+use crate::sys::{config::ConfigError, env::EnvironmentError};
 use axum::{
     Json,
     http::StatusCode,
@@ -16,10 +16,34 @@ pub enum AppError {
 
     // Server/IO Errors
     ServerError(String),
+    /// A request handler panicked.
+    Panic {
+        request_id: Option<String>,
+    },
     BindError(String),
+    TlsError(String),
+
+    // Request Errors
+    BadRequest(String),
+    Conflict(String),
+    UnprocessableEntity(String),
+    PreconditionFailed(String),
+    PreconditionRequired(String),
+    RequestTimeout(String),
+    GatewayTimeout(String),
+    PayloadTooLarge(String),
+    TooManyRequests(String),
+    ServiceUnavailable(String),
+
+    // Authentication Errors
+    Unauthorized(String),
+    Forbidden(String),
 
     // Environment Errors
     Environment(EnvironmentError),
+
+    // Configuration Errors
+    Config(ConfigError),
 }
 
 impl fmt::Display for AppError {
@@ -27,8 +51,27 @@ impl fmt::Display for AppError {
         match self {
             Self::Database(e) => write!(f, "Database error: {e}"),
             Self::Environment(e) => write!(f, "Environment error: {e}"),
+            Self::Config(e) => write!(f, "Configuration error: {e}"),
             Self::ServerError(msg) => write!(f, "Server error: {msg}"),
+            Self::Panic { request_id } => write!(
+                f,
+                "Request handler panicked (request id: {})",
+                request_id.as_deref().unwrap_or("unknown")
+            ),
             Self::BindError(msg) => write!(f, "Bind error: {msg}"),
+            Self::TlsError(msg) => write!(f, "TLS error: {msg}"),
+            Self::BadRequest(msg) => write!(f, "Bad request: {msg}"),
+            Self::Conflict(msg) => write!(f, "Conflict: {msg}"),
+            Self::UnprocessableEntity(msg) => write!(f, "Unprocessable entity: {msg}"),
+            Self::PreconditionFailed(msg) => write!(f, "Precondition failed: {msg}"),
+            Self::PreconditionRequired(msg) => write!(f, "Precondition required: {msg}"),
+            Self::RequestTimeout(msg) => write!(f, "Request timeout: {msg}"),
+            Self::GatewayTimeout(msg) => write!(f, "Gateway timeout: {msg}"),
+            Self::PayloadTooLarge(msg) => write!(f, "Payload too large: {msg}"),
+            Self::TooManyRequests(msg) => write!(f, "Too many requests: {msg}"),
+            Self::ServiceUnavailable(msg) => write!(f, "Service unavailable: {msg}"),
+            Self::Unauthorized(msg) => write!(f, "Unauthorized: {msg}"),
+            Self::Forbidden(msg) => write!(f, "Forbidden: {msg}"),
         }
     }
 }
@@ -51,6 +94,15 @@ impl IntoResponse for AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
             }
 
+            Self::Config(config_err) => {
+                error!(error = %config_err, "Invalid application configuration");
+                let body = Json(json!({
+                    "error": "configuration_error",
+                    "message": "Application misconfiguration detected. Check server logs."
+                }));
+                (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+
             // Handle Server errors
             Self::ServerError(msg) => {
                 let body = Json(json!({
@@ -60,6 +112,15 @@ impl IntoResponse for AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
             }
 
+            Self::Panic { request_id } => {
+                let body = Json(json!({
+                    "error": "internal_error",
+                    "message": "An unexpected error occurred. Check server logs.",
+                    "request_id": request_id
+                }));
+                (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+
             Self::BindError(msg) => {
                 let body = Json(json!({
                     "error": "bind_error",
@@ -67,6 +128,110 @@ impl IntoResponse for AppError {
                 }));
                 (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
             }
+
+            Self::TlsError(msg) => {
+                let body = Json(json!({
+                    "error": "tls_error",
+                    "message": msg
+                }));
+                (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+            }
+
+            Self::BadRequest(msg) => {
+                let body = Json(json!({
+                    "error": "bad_request",
+                    "message": msg
+                }));
+                (StatusCode::BAD_REQUEST, body).into_response()
+            }
+
+            Self::Conflict(msg) => {
+                let body = Json(json!({
+                    "error": "conflict",
+                    "message": msg
+                }));
+                (StatusCode::CONFLICT, body).into_response()
+            }
+
+            Self::UnprocessableEntity(msg) => {
+                let body = Json(json!({
+                    "error": "unprocessable_entity",
+                    "message": msg
+                }));
+                (StatusCode::UNPROCESSABLE_ENTITY, body).into_response()
+            }
+
+            Self::PreconditionFailed(msg) => {
+                let body = Json(json!({
+                    "error": "precondition_failed",
+                    "message": msg
+                }));
+                (StatusCode::PRECONDITION_FAILED, body).into_response()
+            }
+
+            Self::PreconditionRequired(msg) => {
+                let body = Json(json!({
+                    "error": "precondition_required",
+                    "message": msg
+                }));
+                (StatusCode::PRECONDITION_REQUIRED, body).into_response()
+            }
+
+            Self::RequestTimeout(msg) => {
+                let body = Json(json!({
+                    "error": "request_timeout",
+                    "message": msg
+                }));
+                (StatusCode::REQUEST_TIMEOUT, body).into_response()
+            }
+
+            Self::GatewayTimeout(msg) => {
+                let body = Json(json!({
+                    "error": "gateway_timeout",
+                    "message": msg
+                }));
+                (StatusCode::GATEWAY_TIMEOUT, body).into_response()
+            }
+
+            Self::PayloadTooLarge(msg) => {
+                let body = Json(json!({
+                    "error": "payload_too_large",
+                    "message": msg
+                }));
+                (StatusCode::PAYLOAD_TOO_LARGE, body).into_response()
+            }
+
+            Self::TooManyRequests(msg) => {
+                let body = Json(json!({
+                    "error": "too_many_requests",
+                    "message": msg
+                }));
+                (StatusCode::TOO_MANY_REQUESTS, body).into_response()
+            }
+
+            Self::ServiceUnavailable(msg) => {
+                let body = Json(json!({
+                    "error": "service_unavailable",
+                    "message": msg
+                }));
+                (StatusCode::SERVICE_UNAVAILABLE, body).into_response()
+            }
+
+            Self::Unauthorized(msg) => {
+                let body = Json(json!({
+                    "error": "unauthorized",
+                    "message": msg
+                }));
+                (StatusCode::UNAUTHORIZED, body).into_response()
+            }
+
+            Self::Forbidden(msg) => {
+                let body = Json(json!({
+                    "error": "forbidden",
+                    "message": msg
+                }));
+                (StatusCode::FORBIDDEN, body).into_response()
+            }
         }
     }
 }
@@ -85,6 +250,13 @@ impl From<EnvironmentError> for AppError {
     }
 }
 
+// Automatically convert ConfigError -> AppError
+impl From<ConfigError> for AppError {
+    fn from(err: ConfigError) -> Self {
+        Self::Config(err)
+    }
+}
+
 // Automatically convert io::Error -> AppError
 impl From<std::io::Error> for AppError {
     fn from(err: std::io::Error) -> Self {
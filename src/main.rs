@@ -1,11 +1,15 @@
-use axum::routing::get;
+use axum::{Router, routing::get};
 use axum_backend::{
     AppError,
-    sys::{health::aggregate_health, init::initialize},
+    sys::{
+        admin::admin_routes,
+        health::aggregate_health,
+        init::{initialize, load_middleware},
+        metrics::get_metrics,
+        server::serve_all,
+    },
 };
 
-use tracing::error;
-
 /// Initializes and runs the application.
 ///
 /// # Errors
@@ -13,19 +17,32 @@ use tracing::error;
 /// Returns `AppError` if initialization or server execution fails.
 #[tokio::main]
 async fn main() -> Result<(), AppError> {
-    let (app, state, listener) = initialize().await?;
+    let (app, state, listeners) = initialize().await?;
+
+    // Add public routes to the router
+    let mut app = app.route("/", get(root));
+    let server = state.config.load().server.clone();
+    if server.admin_listener && server.public_health {
+        app = app.route("/health", get(aggregate_health));
+    }
 
-    // Add routes to the router
-    let app = app
-        .route("/", get(root))
+    // Health, metrics and admin routes, kept off the public listeners when
+    // the admin listener is enabled
+    let admin = Router::new()
         .route("/health", get(aggregate_health))
-        .with_state(state);
+        .route("/metrics", get(get_metrics))
+        .merge(admin_routes(&state));
+
+    // Wrap the routes with the middleware stack
+    let app = load_middleware(app, &state).with_state(state.clone());
+    let admin = load_middleware(admin, &state).with_state(state.clone());
+
+    // Start every listener, exposing peer addresses to the access log, until
+    // a shutdown signal arrives
+    serve_all(listeners, app, admin, &state.shutdown).await?;
 
-    // Start the server
-    axum::serve(listener, app).await.map_err(|e| {
-        error!(error = %e, "The server encountered an unrecoverable error");
-        AppError::ServerError(e.to_string())
-    })?;
+    // Let running jobs and scheduled tasks finish or cancel
+    tokio::join!(state.jobs.stopped(), state.scheduler.stopped());
 
     Ok(())
 }
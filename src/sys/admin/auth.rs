@@ -0,0 +1,49 @@
+use crate::{
+    AppError,
+    sys::{config::state::AppState, middleware::access_log::UserId},
+};
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Middleware that requires `Authorization: Bearer <ADMIN_TOKEN>`.
+///
+/// Authenticated requests are attributed to the `admin` user in the access log.
+pub async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let config = state.config.load();
+    let Some(expected) = config.admin.token.as_ref() else {
+        return AppError::Forbidden("The admin API is disabled".to_string()).into_response();
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.expose().as_bytes()) => {
+            request.extensions_mut().insert(UserId("admin".to_string()));
+            next.run(request).await
+        }
+        _ => {
+            warn!(path = %request.uri().path(), "Rejected unauthenticated admin request");
+            AppError::Unauthorized("A valid admin token is required".to_string()).into_response()
+        }
+    }
+}
+
+/// Compares two byte strings without short-circuiting on the first difference.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
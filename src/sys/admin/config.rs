@@ -0,0 +1,17 @@
+use crate::sys::{config::state::AppState, env};
+use axum::{Json, extract::State, response::IntoResponse};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Returns the effective configuration, the source of every setting and
+/// unknown settings that may be typos. Secrets are masked.
+pub async fn get_config(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let config = state.config.current();
+
+    Json(json!({
+        "file": config.file,
+        "env_prefix": env::prefix(),
+        "settings": config.report.settings,
+        "unknown": config.report.unknown,
+    }))
+}
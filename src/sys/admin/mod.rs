@@ -0,0 +1,7 @@
+pub mod auth;
+pub mod config;
+pub mod models;
+pub mod routes;
+
+pub use models::AdminConfig;
+pub use routes::admin_routes;
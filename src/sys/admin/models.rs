@@ -0,0 +1,20 @@
+use crate::sys::{config::ConfigReader, env::Secret};
+
+#[derive(Debug, Clone, Default)]
+pub struct AdminConfig {
+    /// Bearer token required by `/admin/*` routes. The admin API is disabled
+    /// when no token is configured.
+    pub token: Option<Secret>,
+}
+
+impl AdminConfig {
+    /// Creates an `AdminConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let token = config.optional_secret("ADMIN_TOKEN");
+        if token.as_ref().is_some_and(|t| t.expose().len() < 16) {
+            config.invalid("ADMIN_TOKEN", "must be at least 16 characters long");
+        }
+
+        Self { token }
+    }
+}
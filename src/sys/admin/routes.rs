@@ -0,0 +1,60 @@
+use super::{auth::require_admin, config::get_config};
+use crate::sys::{
+    audit::get_record_history,
+    config::state::AppState,
+    outbox::{list_dead_events, retry_dead_event},
+    scheduler::{get_scheduled_tasks, get_task_runs},
+    tenancy::{
+        get_tenant, get_tenant_health, list_migrations, list_tenants, migrate_tenant,
+        migrate_tenants, provision_tenant, resume_tenant, suspend_tenant,
+    },
+    webhooks::{
+        create_subscription, delete_subscription, get_delivery, list_deliveries,
+        list_subscriptions, replay_delivery,
+    },
+};
+use axum::{
+    Router,
+    middleware::from_fn_with_state,
+    routing::{delete, get, post},
+};
+use std::sync::Arc;
+
+/// Creates the `/admin/*` routes, all protected by the admin token.
+pub fn admin_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/config", get(get_config))
+        .route("/admin/audit/{table}/{id}", get(get_record_history))
+        .route("/admin/outbox/dead", get(list_dead_events))
+        .route("/admin/outbox/dead/{id}/retry", post(retry_dead_event))
+        .route("/admin/scheduler", get(get_scheduled_tasks))
+        .route("/admin/scheduler/{task}/runs", get(get_task_runs))
+        .route(
+            "/admin/webhooks/{tenant}/subscriptions",
+            get(list_subscriptions).post(create_subscription),
+        )
+        .route(
+            "/admin/webhooks/{tenant}/subscriptions/{id}",
+            delete(delete_subscription),
+        )
+        .route("/admin/webhooks/{tenant}/deliveries", get(list_deliveries))
+        .route(
+            "/admin/webhooks/{tenant}/deliveries/{id}",
+            get(get_delivery),
+        )
+        .route(
+            "/admin/webhooks/{tenant}/deliveries/{id}/replay",
+            post(replay_delivery),
+        )
+        .route("/admin/tenants", get(list_tenants).post(provision_tenant))
+        .route("/admin/tenants/{id}", get(get_tenant))
+        .route("/admin/tenants/{id}/migrate", post(migrate_tenant))
+        .route("/admin/tenants/{id}/suspend", post(suspend_tenant))
+        .route("/admin/tenants/{id}/resume", post(resume_tenant))
+        .route("/admin/tenants/{id}/health", get(get_tenant_health))
+        .route(
+            "/admin/migrations",
+            get(list_migrations).post(migrate_tenants),
+        )
+        .route_layer(from_fn_with_state(state.clone(), require_admin))
+}
@@ -0,0 +1,24 @@
+use super::models::AuditContext;
+use crate::sys::middleware::access_log::UserId;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::convert::Infallible;
+
+impl<S: Send + Sync> FromRequestParts<S> for AuditContext {
+    type Rejection = Infallible;
+
+    /// Takes the actor from the `UserId` set by authentication layers and
+    /// the request id from `x-request-id`.
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            actor: parts
+                .extensions
+                .get::<UserId>()
+                .map(|UserId(id)| id.clone()),
+            request_id: parts
+                .headers
+                .get("x-request-id")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+        })
+    }
+}
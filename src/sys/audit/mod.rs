@@ -0,0 +1,7 @@
+mod extract;
+mod models;
+mod routes;
+mod store;
+pub use models::{AuditAction, AuditContext, AuditEvent, FieldChange};
+pub use routes::{HistoryQuery, get_record_history};
+pub use store::AuditLog;
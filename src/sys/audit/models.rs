@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Who made a change and in which request, recorded with every audit event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditContext {
+    /// The authenticated user, or the name of a background task.
+    pub actor: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    /// Context for changes made by a background task rather than a request.
+    #[must_use]
+    pub fn system(name: impl Into<String>) -> Self {
+        Self {
+            actor: Some(name.into()),
+            request_id: None,
+        }
+    }
+}
+
+/// The kind of change recorded by an audit event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    /// Marked as deleted, can be restored.
+    SoftDelete,
+    Restore,
+    /// Removed permanently.
+    Delete,
+}
+
+impl AuditAction {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::SoftDelete => "soft_delete",
+            Self::Restore => "restore",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// One change to a record, as stored in the `audit_log` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: String,
+    pub record_table: String,
+    pub record_id: String,
+    pub action: AuditAction,
+    pub actor: Option<String>,
+    pub request_id: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    /// The record before the change, `None` for creates.
+    pub before: Option<Value>,
+    /// The record after the change, `None` for permanent deletes.
+    pub after: Option<Value>,
+}
+
+/// A changed field with its old and new values.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub before: Value,
+    pub after: Value,
+}
+
+impl AuditEvent {
+    /// Returns the fields that differ between `before` and `after`.
+    #[must_use]
+    pub fn changes(&self) -> BTreeMap<String, FieldChange> {
+        let empty = Map::new();
+        let before = self
+            .before
+            .as_ref()
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        let after = self
+            .after
+            .as_ref()
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+
+        before
+            .keys()
+            .chain(after.keys())
+            .filter(|field| *field != "id")
+            .filter_map(|field| {
+                let old = before.get(field).cloned().unwrap_or(Value::Null);
+                let new = after.get(field).cloned().unwrap_or(Value::Null);
+                (old != new).then(|| {
+                    (
+                        field.clone(),
+                        FieldChange {
+                            before: old,
+                            after: new,
+                        },
+                    )
+                })
+            })
+            .collect()
+    }
+}
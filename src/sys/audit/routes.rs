@@ -0,0 +1,73 @@
+use super::store::AuditLog;
+use crate::{AppError, dbs::error::DatabaseError, sys::config::state::AppState};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Most events returned by one history request.
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Named database holding the record, the primary one when unset.
+    pub database: Option<String>,
+    /// Tenant whose database holds the record, instead of `database`.
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub start: u64,
+    pub limit: Option<u64>,
+}
+
+/// Returns the audit history of one record, newest first, with the fields
+/// changed by each event.
+pub async fn get_record_history(
+    State(state): State<Arc<AppState>>,
+    Path((table, id)): Path<(String, String)>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let db = match (&query.tenant, &query.database) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::BadRequest(
+                "Only one of tenant and database can be given".to_string(),
+            ));
+        }
+        (Some(tenant), None) => state.tenants.connection(tenant).await?,
+        (None, Some(name)) => state
+            .database(name)
+            .ok_or_else(|| DatabaseError::NotFound(format!("Database '{name}' is not configured")))?
+            .clone(),
+        (None, None) => state.db_connection.clone(),
+    };
+    let limit = query.limit.unwrap_or(100).min(MAX_LIMIT);
+    let events = AuditLog::new(db)
+        .history(&table, &id, query.start, limit)
+        .await?;
+
+    let events: Vec<_> = events
+        .into_iter()
+        .map(|event| {
+            let changes = event.changes();
+            json!({
+                "id": event.id,
+                "action": event.action,
+                "actor": event.actor,
+                "request_id": event.request_id,
+                "timestamp": event.timestamp,
+                "changes": changes,
+                "before": event.before,
+                "after": event.after,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "table": table,
+        "id": id,
+        "events": events,
+    })))
+}
@@ -0,0 +1,42 @@
+use super::models::AuditEvent;
+use crate::dbs::{error::DatabaseError, models::DbConnection};
+
+/// Reads the `audit_log` table written by audited repositories.
+#[derive(Clone)]
+pub struct AuditLog {
+    db: DbConnection,
+}
+
+impl AuditLog {
+    #[must_use]
+    pub fn new(db: DbConnection) -> Self {
+        Self { db }
+    }
+
+    /// Returns the changes to one record, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn history(
+        &self,
+        table: &str,
+        id: &str,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<AuditEvent>, DatabaseError> {
+        Ok(self
+            .db
+            .query(
+                "SELECT *, meta::id(id) AS id FROM audit_log \
+                 WHERE record_table = $table AND record_id = $id \
+                 ORDER BY timestamp DESC LIMIT $limit START $start",
+            )
+            .bind(("table", table.to_string()))
+            .bind(("id", id.to_string()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+}
@@ -0,0 +1,307 @@
+use super::{
+    models::{CacheConfig, CacheEntry},
+    store::{CacheStore, millis, now_millis},
+};
+use crate::{
+    dbs::error::DatabaseError,
+    sys::{
+        health::models::{ComponentHealth, HealthCheck, HealthStatus},
+        metrics::{Counter, MetricsRegistry},
+    },
+};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+/// Key written and read back by the health check.
+const HEALTH_KEY: &str = "__health";
+
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Typed access to the configured [`CacheStore`].
+///
+/// Reads fail open: a value that cannot be read or decoded is reported as a
+/// miss and logged, so an unavailable cache slows requests down instead of
+/// failing them.
+#[derive(Clone)]
+pub struct Cache {
+    inner: Arc<CacheInner>,
+}
+
+struct CacheInner {
+    store: Arc<dyn CacheStore>,
+    config: CacheConfig,
+    /// Loads in progress by key, so concurrent misses load a value once.
+    loads: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    hits: Counter,
+    misses: Counter,
+    coalesced: Counter,
+    errors: Counter,
+}
+
+/// Removes the in-progress load of a key once no request waits for it.
+struct LoadGuard<'a> {
+    cache: &'a CacheInner,
+    key: &'a str,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl Drop for LoadGuard<'_> {
+    fn drop(&mut self) {
+        let mut loads = self
+            .cache
+            .loads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // One reference is held by the map and one by this guard
+        if Arc::strong_count(&self.lock) <= 2 {
+            loads.remove(self.key);
+        }
+    }
+}
+
+impl Cache {
+    #[must_use]
+    pub fn new(store: Arc<dyn CacheStore>, config: CacheConfig, metrics: &MetricsRegistry) -> Self {
+        let help = "Cache lookups by result";
+        Self {
+            inner: Arc::new(CacheInner {
+                store,
+                config,
+                loads: Mutex::new(HashMap::new()),
+                hits: metrics.counter("cache_requests_total", help, &[("result", "hit")]),
+                misses: metrics.counter("cache_requests_total", help, &[("result", "miss")]),
+                coalesced: metrics.counter(
+                    "cache_coalesced_loads_total",
+                    "Cache misses served by a load already in progress for the same key",
+                    &[],
+                ),
+                errors: metrics.counter("cache_errors_total", "Cache operations that failed", &[]),
+            }),
+        }
+    }
+
+    /// Returns true if caching is enabled.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.inner.config.enabled
+    }
+
+    /// Returns the value stored under `key`.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.lookup(key).await;
+        if value.is_some() {
+            self.inner.hits.inc();
+        } else {
+            self.inner.misses.inc();
+        }
+        value
+    }
+
+    /// Stores `value` under `key` for `ttl`, or the default TTL when `None`.
+    ///
+    /// `tags` group entries so they can be removed together with
+    /// [`Cache::invalidate_tag`].
+    pub async fn set<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+        tags: &[&str],
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let value = match serde_json::to_string(value) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!(key, error = %e, "Failed to serialize cache value");
+                self.inner.errors.inc();
+                return;
+            }
+        };
+        let ttl = ttl.unwrap_or(self.inner.config.default_ttl);
+        let entry = CacheEntry {
+            value,
+            tags: tags.iter().map(|tag| (*tag).to_string()).collect(),
+            expires_at: now_millis().saturating_add(millis(ttl)),
+        };
+        if let Err(e) = self.inner.store.set(key, entry).await {
+            warn!(key, error = %e, "Failed to store cache value");
+            self.inner.errors.inc();
+        }
+    }
+
+    /// Returns the value stored under `key`, or loads, stores and returns it
+    /// on a miss.
+    ///
+    /// Concurrent misses for the same key in this process wait for a single
+    /// load instead of all querying the database. Errors from `load` are
+    /// returned and not cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `load`.
+    pub async fn get_or_load<T, E, F, Fut>(
+        &self,
+        key: &str,
+        ttl: Option<Duration>,
+        tags: &[&str],
+        load: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if !self.is_enabled() {
+            return load().await;
+        }
+        if let Some(value) = self.get(key).await {
+            return Ok(value);
+        }
+
+        let lock = self
+            .inner
+            .loads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        let guard = LoadGuard {
+            cache: &self.inner,
+            key,
+            lock,
+        };
+        let _loading = match guard.lock.try_lock() {
+            Ok(loading) => loading,
+            Err(_) => {
+                let loading = guard.lock.lock().await;
+                // The request holding the lock has stored the value by now
+                if let Some(value) = self.lookup(key).await {
+                    self.inner.coalesced.inc();
+                    return Ok(value);
+                }
+                loading
+            }
+        };
+
+        debug!(key, "Loading value on cache miss");
+        let value = load().await?;
+        self.set(key, &value, ttl, tags).await;
+        Ok(value)
+    }
+
+    /// Removes the value stored under `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the store could not be written.
+    pub async fn delete(&self, key: &str) -> Result<(), DatabaseError> {
+        self.inner.store.delete(key).await.inspect_err(|_| {
+            self.inner.errors.inc();
+        })
+    }
+
+    /// Removes every value stored with `tag` and returns how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the store could not be written.
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<u64, DatabaseError> {
+        let removed = self
+            .inner
+            .store
+            .invalidate_tag(tag)
+            .await
+            .inspect_err(|_| {
+                self.inner.errors.inc();
+            })?;
+        debug!(tag, removed, "Invalidated cache tag");
+        Ok(removed)
+    }
+
+    async fn lookup<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let entry = match self.inner.store.get(key).await {
+            Ok(entry) => entry?,
+            Err(e) => {
+                warn!(key, error = %e, "Failed to read cache value");
+                self.inner.errors.inc();
+                return None;
+            }
+        };
+        match serde_json::from_str(&entry.value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                // Usually a value stored by an older version of the type
+                debug!(key, error = %e, "Discarding cache value that cannot be decoded");
+                None
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for Cache {
+    /// Writes and reads back a probe value. A failing cache only slows
+    /// requests down, so it is reported as degraded.
+    async fn check(&self) -> ComponentHealth {
+        let store = &self.inner.store;
+        let probe = async {
+            let entry = CacheEntry {
+                value: "true".to_string(),
+                tags: Vec::new(),
+                expires_at: now_millis().saturating_add(millis(HEALTH_CHECK_TIMEOUT)),
+            };
+            store.set(HEALTH_KEY, entry).await?;
+            store.get(HEALTH_KEY).await
+        };
+
+        let (status, message) = match timeout(HEALTH_CHECK_TIMEOUT, probe).await {
+            Ok(Ok(Some(_))) => {
+                let hits = self.inner.hits.get();
+                let lookups = hits + self.inner.misses.get();
+                let ratio = if lookups == 0 {
+                    0.0
+                } else {
+                    hits as f64 / lookups as f64
+                };
+                (
+                    HealthStatus::Healthy,
+                    format!("Hit ratio: {:.1}% of {lookups} lookups", ratio * 100.0),
+                )
+            }
+            Ok(Ok(None)) => (
+                HealthStatus::Degraded,
+                "Probe value was not read back".to_string(),
+            ),
+            Ok(Err(e)) => {
+                warn!(error = %e, "Cache health check failed");
+                (HealthStatus::Degraded, format!("Store error: {e}"))
+            }
+            Err(_) => (
+                HealthStatus::Degraded,
+                format!(
+                    "Health check timeout after {}ms",
+                    HEALTH_CHECK_TIMEOUT.as_millis()
+                ),
+            ),
+        };
+
+        ComponentHealth {
+            name: "Cache".to_string(),
+            status,
+            message: Some(message),
+        }
+    }
+}
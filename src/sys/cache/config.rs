@@ -0,0 +1,29 @@
+use super::models::{CacheConfig, CacheStoreKind};
+use crate::sys::config::ConfigReader;
+use std::time::Duration;
+
+impl CacheConfig {
+    /// Creates a `CacheConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let default_ttl = config.duration("CACHE_DEFAULT_TTL", Duration::from_secs(300));
+        if default_ttl.is_zero() {
+            config.invalid("CACHE_DEFAULT_TTL", "must be greater than zero");
+        }
+        let max_entries = config.parsed("CACHE_MAX_ENTRIES", 10_000);
+        if max_entries == 0 {
+            config.invalid("CACHE_MAX_ENTRIES", "must be greater than zero");
+        }
+
+        Self {
+            enabled: config.bool("CACHE_ENABLED", true),
+            store: config.enumeration("CACHE_STORE", CacheStoreKind::Memory),
+            default_ttl,
+            max_entries,
+            max_size: config.byte_size("CACHE_MAX_SIZE", 64 * 1024 * 1024),
+            database: config
+                .optional("CACHE_DATABASE")
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+}
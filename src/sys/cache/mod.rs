@@ -0,0 +1,9 @@
+mod client;
+mod config;
+mod models;
+mod store;
+mod surreal;
+pub use client::Cache;
+pub use models::{CacheConfig, CacheEntry, CacheStoreKind};
+pub use store::{CacheStore, MemoryCache, now_millis};
+pub use surreal::SurrealCache;
@@ -0,0 +1,55 @@
+use crate::sys::env::EnvEnum;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    pub store: CacheStoreKind,
+    /// Lifetime of entries stored without an explicit TTL.
+    pub default_ttl: Duration,
+    /// Entries kept by the in-memory store before the least recently used
+    /// are evicted.
+    pub max_entries: usize,
+    /// Total size of values kept by the in-memory store, in bytes.
+    pub max_size: u64,
+    /// Named database for the SurrealDB store, the primary one when `None`.
+    pub database: Option<String>,
+}
+
+/// Where cached values are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStoreKind {
+    /// Per-instance LRU cache, lost on restart.
+    Memory,
+    /// Shared cache in SurrealDB, for deployments with several instances.
+    SurrealDb,
+}
+
+impl EnvEnum for CacheStoreKind {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("memory", Self::Memory),
+        ("surrealdb", Self::SurrealDb),
+        ("database", Self::SurrealDb),
+    ];
+}
+
+/// A serialized value with its tags and expiry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// The value as JSON.
+    pub value: String,
+    /// Tags that invalidate the entry together with other entries.
+    pub tags: Vec<String>,
+    /// Expiry in milliseconds since the Unix epoch.
+    pub expires_at: i64,
+}
+
+impl CacheEntry {
+    /// Returns true if the entry has expired at `now` (milliseconds since the
+    /// Unix epoch).
+    #[must_use]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
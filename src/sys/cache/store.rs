@@ -0,0 +1,208 @@
+use super::models::CacheEntry;
+use crate::{
+    dbs::error::DatabaseError,
+    sys::metrics::{Counter, Gauge, MetricsRegistry},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Keeps serialized cache entries.
+#[async_trait::async_trait]
+pub trait CacheStore: Send + Sync {
+    /// Returns the entry stored under `key`, if it has not expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the store could not be read.
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, DatabaseError>;
+
+    /// Stores `entry` under `key`, replacing any previous entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the store could not be written.
+    async fn set(&self, key: &str, entry: CacheEntry) -> Result<(), DatabaseError>;
+
+    /// Removes the entry stored under `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the store could not be written.
+    async fn delete(&self, key: &str) -> Result<(), DatabaseError>;
+
+    /// Removes every entry carrying `tag` and returns how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the store could not be written.
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, DatabaseError>;
+}
+
+/// Current time in milliseconds since the Unix epoch.
+#[must_use]
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, millis)
+}
+
+pub(super) fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+/// LRU cache kept in this process, bounded by entry count and total size.
+pub struct MemoryCache {
+    state: Mutex<MemoryState>,
+    max_entries: usize,
+    max_size: u64,
+    evictions: Counter,
+    entries: Gauge,
+    size: Gauge,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    /// Keys by last use, least recently used first.
+    recency: BTreeMap<u64, String>,
+    /// Keys carrying each tag.
+    tags: HashMap<String, HashSet<String>>,
+    size: u64,
+    tick: u64,
+}
+
+struct MemoryEntry {
+    entry: CacheEntry,
+    last_used: u64,
+}
+
+impl MemoryState {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(item) = self.entries.get_mut(key) {
+            self.recency.remove(&item.last_used);
+            item.last_used = tick;
+            self.recency.insert(tick, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        let Some(item) = self.entries.remove(key) else {
+            return false;
+        };
+        self.recency.remove(&item.last_used);
+        self.size -= item.entry.value.len() as u64;
+        for tag in &item.entry.tags {
+            if let Some(keys) = self.tags.get_mut(tag) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.tags.remove(tag);
+                }
+            }
+        }
+        true
+    }
+}
+
+impl MemoryCache {
+    #[must_use]
+    pub fn new(max_entries: usize, max_size: u64, metrics: &MetricsRegistry) -> Self {
+        Self {
+            state: Mutex::new(MemoryState::default()),
+            max_entries,
+            max_size,
+            evictions: metrics.counter(
+                "cache_evictions_total",
+                "Entries evicted from the in-memory cache to stay within its bounds",
+                &[],
+            ),
+            entries: metrics.gauge("cache_entries", "Entries in the in-memory cache", &[]),
+            size: metrics.gauge(
+                "cache_size_bytes",
+                "Size of the values in the in-memory cache",
+                &[],
+            ),
+        }
+    }
+
+    fn update_gauges(&self, state: &MemoryState) {
+        self.entries
+            .set(i64::try_from(state.entries.len()).unwrap_or(i64::MAX));
+        self.size.set(i64::try_from(state.size).unwrap_or(i64::MAX));
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheStore for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, DatabaseError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let expired = match state.entries.get(key) {
+            None => return Ok(None),
+            Some(item) => item.entry.is_expired(now_millis()),
+        };
+        if expired {
+            state.remove(key);
+            self.update_gauges(&state);
+            return Ok(None);
+        }
+        state.touch(key);
+        Ok(state.entries.get(key).map(|item| item.entry.clone()))
+    }
+
+    async fn set(&self, key: &str, entry: CacheEntry) -> Result<(), DatabaseError> {
+        let size = entry.value.len() as u64;
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.remove(key);
+        // A value larger than the whole cache would evict everything else
+        if size > self.max_size {
+            self.update_gauges(&state);
+            return Ok(());
+        }
+
+        for tag in &entry.tags {
+            state
+                .tags
+                .entry(tag.clone())
+                .or_default()
+                .insert(key.to_string());
+        }
+        state.size += size;
+        state.entries.insert(
+            key.to_string(),
+            MemoryEntry {
+                entry,
+                last_used: 0,
+            },
+        );
+        state.touch(key);
+
+        while state.entries.len() > self.max_entries || state.size > self.max_size {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.remove(&oldest);
+            self.evictions.inc();
+        }
+        self.update_gauges(&state);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.remove(key);
+        self.update_gauges(&state);
+        Ok(())
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, DatabaseError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let keys = state.tags.remove(tag).unwrap_or_default();
+        let removed = keys.iter().filter(|key| state.remove(key)).count();
+        self.update_gauges(&state);
+        Ok(removed as u64)
+    }
+}
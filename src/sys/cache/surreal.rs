@@ -0,0 +1,88 @@
+use super::{
+    models::CacheEntry,
+    store::{CacheStore, now_millis},
+};
+use crate::{
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::server::Shutdown,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::warn;
+
+#[derive(Debug, Deserialize)]
+struct DeletedRow {}
+
+/// Cache shared by every instance through the `cache` table.
+pub struct SurrealCache {
+    db: DbConnection,
+}
+
+impl SurrealCache {
+    /// Creates the store and starts a task that deletes expired entries
+    /// until shutdown.
+    #[must_use]
+    pub fn new(db: DbConnection, sweep_interval: Duration, shutdown: Shutdown) -> Self {
+        let sweeper = db.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sweep_interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    () = shutdown.triggered() => break,
+                }
+                if let Err(e) = sweeper
+                    .query("DELETE cache WHERE expires_at <= $now")
+                    .bind(("now", now_millis()))
+                    .await
+                {
+                    warn!(error = %e, "Failed to delete expired cache entries");
+                }
+            }
+        });
+
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheStore for SurrealCache {
+    async fn get(&self, key: &str) -> Result<Option<CacheEntry>, DatabaseError> {
+        let entry: Option<CacheEntry> = self
+            .db
+            .query("SELECT * FROM type::thing('cache', $key)")
+            .bind(("key", key.to_string()))
+            .await?
+            .take(0)?;
+        Ok(entry.filter(|entry| !entry.is_expired(now_millis())))
+    }
+
+    async fn set(&self, key: &str, entry: CacheEntry) -> Result<(), DatabaseError> {
+        self.db
+            .query("UPSERT type::thing('cache', $key) CONTENT $entry RETURN NONE")
+            .bind(("key", key.to_string()))
+            .bind(("entry", entry))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), DatabaseError> {
+        self.db
+            .query("DELETE type::thing('cache', $key)")
+            .bind(("key", key.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, DatabaseError> {
+        let deleted: Vec<DeletedRow> = self
+            .db
+            .query("DELETE cache WHERE tags CONTAINS $tag RETURN id")
+            .bind(("tag", tag.to_string()))
+            .await?
+            .take(0)?;
+        Ok(deleted.len() as u64)
+    }
+}
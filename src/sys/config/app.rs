@@ -0,0 +1,118 @@
+use super::{
+    cli::CliArgs, error::ConfigError, features::FeatureFlags, reader::ConfigReader,
+    reload::ReloadConfig, report::ConfigReport, server::ServerConfig, source::ConfigSources,
+};
+use crate::{
+    dbs::models::DbConfig,
+    sys::{
+        admin::AdminConfig,
+        cache::CacheConfig,
+        events::EventsConfig,
+        health::HealthConfig,
+        jobs::JobsConfig,
+        log::LogConfig,
+        middleware::{
+            access_log::AccessLogConfig, conditional::ConditionalConfig, http::HttpConfig,
+            idempotency::IdempotencyConfig, load_shed::LoadShedConfig, rate_limit::RateLimitConfig,
+        },
+        outbox::OutboxConfig,
+        scheduler::SchedulerConfig,
+        tenancy::TenancyConfig,
+        tls::TlsConfig,
+        webhooks::{InboundWebhookConfig, WebhooksConfig},
+    },
+};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// The complete, validated application configuration.
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub tls: TlsConfig,
+    pub database: DbConfig,
+    /// Additional databases configured as `DB__<NAME>__*`, keyed by lowercase name.
+    pub databases: BTreeMap<String, DbConfig>,
+    pub log: LogConfig,
+    pub access_log: AccessLogConfig,
+    pub http: HttpConfig,
+    pub conditional: ConditionalConfig,
+    pub rate_limit: RateLimitConfig,
+    pub load_shed: LoadShedConfig,
+    pub idempotency: IdempotencyConfig,
+    pub cache: CacheConfig,
+    pub jobs: JobsConfig,
+    pub scheduler: SchedulerConfig,
+    pub webhooks: WebhooksConfig,
+    /// Senders of inbound webhooks configured as `INBOUND_WEBHOOK__<NAME>__*`,
+    /// keyed by lowercase name.
+    pub inbound_webhooks: BTreeMap<String, InboundWebhookConfig>,
+    pub outbox: OutboxConfig,
+    pub events: EventsConfig,
+    pub health: HealthConfig,
+    pub tenancy: TenancyConfig,
+    pub features: FeatureFlags,
+    pub admin: AdminConfig,
+    pub reload: ReloadConfig,
+    /// The configuration file values were read from, if any.
+    pub file: Option<PathBuf>,
+    /// Where every setting came from, with secrets masked.
+    pub report: ConfigReport,
+}
+
+impl AppConfig {
+    /// Loads configuration from defaults, the configuration file, environment
+    /// variables and the process command line, in increasing priority.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` listing every missing or invalid value.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(CliArgs::from_process())
+    }
+
+    /// Loads configuration using already parsed command line arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` listing every missing or invalid value.
+    pub fn load_from(cli: CliArgs) -> Result<Self, ConfigError> {
+        let mut issues = Vec::new();
+        let sources = ConfigSources::load(cli, &mut issues);
+        let file = sources.file_path.clone();
+        let mut reader = ConfigReader::new(sources, issues);
+
+        let database = DbConfig::from_config(&mut reader);
+        let databases = DbConfig::named_from_config(&mut reader, &database);
+
+        let config = Self {
+            server: ServerConfig::from_config(&mut reader),
+            tls: TlsConfig::from_config(&mut reader),
+            database,
+            databases,
+            log: LogConfig::from_config(&mut reader),
+            access_log: AccessLogConfig::from_config(&mut reader),
+            http: HttpConfig::from_config(&mut reader),
+            conditional: ConditionalConfig::from_config(&mut reader),
+            rate_limit: RateLimitConfig::from_config(&mut reader),
+            load_shed: LoadShedConfig::from_config(&mut reader),
+            idempotency: IdempotencyConfig::from_config(&mut reader),
+            cache: CacheConfig::from_config(&mut reader),
+            jobs: JobsConfig::from_config(&mut reader),
+            scheduler: SchedulerConfig::from_config(&mut reader),
+            webhooks: WebhooksConfig::from_config(&mut reader),
+            inbound_webhooks: InboundWebhookConfig::named_from_config(&mut reader),
+            outbox: OutboxConfig::from_config(&mut reader),
+            events: EventsConfig::from_config(&mut reader),
+            health: HealthConfig::from_config(&mut reader),
+            tenancy: TenancyConfig::from_config(&mut reader),
+            features: FeatureFlags::from_config(&mut reader),
+            admin: AdminConfig::from_config(&mut reader),
+            reload: ReloadConfig::from_config(&mut reader),
+            file,
+            report: ConfigReport::default(),
+        };
+
+        let report = reader.finish()?;
+        Ok(Self { report, ..config })
+    }
+}
@@ -0,0 +1,66 @@
+use super::error::ConfigIssue;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Configuration supplied on the command line.
+///
+/// `--config <path>` selects the configuration file. Every other flag maps to
+/// the environment key of the same name, so `--server-port 8080` and
+/// `--server-port=8080` both override `SERVER_PORT`. A flag without a value is
+/// treated as `true`.
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub config_file: Option<PathBuf>,
+    pub overrides: BTreeMap<String, String>,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl CliArgs {
+    /// Parses the arguments of the current process, skipping the binary name.
+    #[must_use]
+    pub fn from_process() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses command line arguments.
+    #[must_use]
+    pub fn parse<I>(args: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut cli = Self::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--").or_else(|| arg.strip_prefix('-')) else {
+                cli.issues.push(ConfigIssue {
+                    key: arg.clone(),
+                    message: "Unexpected positional argument".to_string(),
+                });
+                continue;
+            };
+
+            let (name, value) = match flag.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next_if(|next| !next.starts_with('-'))
+                        .unwrap_or_else(|| "true".to_string());
+                    (flag.to_string(), value)
+                }
+            };
+
+            if name == "config" || name == "c" {
+                cli.config_file = Some(PathBuf::from(value));
+            } else {
+                cli.overrides.insert(flag_to_key(&name), value);
+            }
+        }
+
+        cli
+    }
+}
+
+/// Converts a flag name such as `server-port` to its key `SERVER_PORT`.
+fn flag_to_key(name: &str) -> String {
+    name.replace('-', "_").to_uppercase()
+}
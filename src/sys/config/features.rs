@@ -0,0 +1,30 @@
+use super::reader::ConfigReader;
+use std::collections::BTreeSet;
+
+/// Named feature flags enabled through `FEATURE_FLAGS`.
+///
+/// Flags are reloadable, so check them per request rather than caching the
+/// result at startup.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags {
+    enabled: BTreeSet<String>,
+}
+
+impl FeatureFlags {
+    /// Creates `FeatureFlags` from a comma-separated list of flag names.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config
+            .list("FEATURE_FLAGS", "")
+            .into_iter()
+            .map(|flag| flag.to_lowercase())
+            .collect();
+
+        Self { enabled }
+    }
+
+    /// Returns true if the flag is enabled (case-insensitive).
+    #[must_use]
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.enabled.contains(&flag.to_lowercase())
+    }
+}
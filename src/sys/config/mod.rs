@@ -1,2 +1,15 @@
+pub mod app;
+pub mod cli;
+pub mod error;
+pub mod features;
+pub mod reader;
+pub mod reload;
+pub mod report;
 pub mod server;
+pub mod source;
 pub mod state;
+pub use app::AppConfig;
+pub use error::{ConfigError, ConfigIssue};
+pub use reader::ConfigReader;
+pub use reload::{ConfigHandle, ConfigReloaded, spawn_config_watcher, spawn_reload_events};
+pub use report::{ConfigReport, ConfigSource, ResolvedSetting, UnknownSetting};
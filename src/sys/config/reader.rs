@@ -0,0 +1,308 @@
+use super::{
+    error::{ConfigError, ConfigIssue},
+    report::{ConfigReport, ConfigSource, ReportBuilder, UnknownSetting, suggest},
+    source::ConfigSources,
+};
+use crate::sys::env::{self, EnvEnum, Secret};
+use std::time::Duration;
+use std::{
+    collections::{BTreeSet, HashSet},
+    str::FromStr,
+};
+use tracing::{debug, warn};
+use url::Url;
+
+/// Variables read before layered configuration is assembled, which are never
+/// reported as unknown.
+const META_KEYS: [&str; 4] = ["CONFIG_FILE", "ENV_PREFIX", "ENV_STRICT", "SECRETS_DIR"];
+
+/// Sections shared with the toolchain that do not indicate a typo (`RUST_LOG`
+/// is read, but `RUST_BACKTRACE` is not ours to report).
+const FOREIGN_SECTIONS: [&str; 2] = ["RUST", "CARGO"];
+
+/// Typed access to layered configuration values.
+///
+/// Invalid or missing values do not stop loading: they are recorded as issues
+/// and a default is returned, so `finish` can report every problem at once.
+/// Outside strict mode (see [`env::is_strict`]), a present-but-invalid value
+/// with a default is only logged.
+pub struct ConfigReader {
+    sources: ConfigSources,
+    strict: bool,
+    issues: Vec<ConfigIssue>,
+    used: HashSet<String>,
+    report: ReportBuilder,
+}
+
+impl ConfigReader {
+    #[must_use]
+    pub fn new(sources: ConfigSources, issues: Vec<ConfigIssue>) -> Self {
+        Self {
+            sources,
+            strict: env::is_strict(),
+            issues,
+            used: HashSet::new(),
+            report: ReportBuilder::default(),
+        }
+    }
+
+    /// Returns the value of `key` if any layer defines it.
+    pub fn optional(&mut self, key: &str) -> Option<String> {
+        self.used.insert(key.to_string());
+        match self.sources.lookup(key) {
+            Ok(Some((value, source))) => {
+                self.report.record(key, Some(value.clone()), source);
+                Some(value)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.invalid(key, e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Records that `key` fell back to its default value.
+    fn defaulted(&mut self, key: &str, default: &dyn std::fmt::Debug) {
+        debug!(key = %key, default = ?default, "Using default configuration value");
+        self.report
+            .record(key, Some(format!("{default:?}")), ConfigSource::Default);
+    }
+
+    /// Returns the value of `key`, or `default` when no layer defines it.
+    pub fn string(&mut self, key: &str, default: &str) -> String {
+        self.optional(key).unwrap_or_else(|| {
+            debug!(key = %key, default = %default, "Using default configuration value");
+            self.report
+                .record(key, Some(default.to_string()), ConfigSource::Default);
+            default.to_string()
+        })
+    }
+
+    /// Returns the value of `key`, recording an issue when it is missing or empty.
+    pub fn required(&mut self, key: &str) -> String {
+        match self.optional(key) {
+            Some(value) if !value.trim().is_empty() => value,
+            _ => {
+                self.report.record(key, None, ConfigSource::Default);
+                // An unreadable secret file has already been reported for this key
+                if !self.issues.iter().any(|issue| issue.key == key) {
+                    self.invalid(key, "is required but not set");
+                }
+                String::new()
+            }
+        }
+    }
+
+    /// Returns a required sensitive value, which can also be supplied through
+    /// `KEY_FILE` or the secrets directory.
+    pub fn secret(&mut self, key: &str) -> Secret {
+        self.report.mark_sensitive(key);
+        Secret::from(self.required(key))
+    }
+
+    /// Returns an optional sensitive value.
+    pub fn optional_secret(&mut self, key: &str) -> Option<Secret> {
+        self.report.mark_sensitive(key);
+        self.optional(key)
+            .filter(|value| !value.trim().is_empty())
+            .map(Secret::from)
+    }
+
+    /// Parses the value of `key`, recording an issue when it is present but invalid.
+    pub fn parsed<T>(&mut self, key: &str, default: T) -> T
+    where
+        T: FromStr + std::fmt::Debug,
+    {
+        let Some(value) = self.optional(key) else {
+            self.defaulted(key, &default);
+            return default;
+        };
+
+        match value.trim().parse::<T>() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                let message = format!("is not a valid {}", std::any::type_name::<T>());
+                self.rejected(key, message, default)
+            }
+        }
+    }
+
+    /// Parses a boolean value of `key`, see [`env::parse_bool`].
+    pub fn bool(&mut self, key: &str, default: bool) -> bool {
+        let Some(value) = self.optional(key) else {
+            self.defaulted(key, &default);
+            return default;
+        };
+
+        match env::parse_bool(&value) {
+            Some(parsed) => parsed,
+            None => self.rejected(key, "is not a valid boolean", default),
+        }
+    }
+
+    /// Splits the comma-separated value of `key`, dropping empty entries.
+    pub fn list(&mut self, key: &str, default: &str) -> Vec<String> {
+        env::parse_list(&self.string(key, default))
+    }
+
+    /// Parses a duration such as `500ms`, `30s` or `2m`; bare numbers are seconds.
+    pub fn duration(&mut self, key: &str, default: Duration) -> Duration {
+        self.with(
+            key,
+            default,
+            "a duration such as 500ms, 30s or 2m",
+            env::parse_duration,
+        )
+    }
+
+    /// Parses a byte size such as `512`, `64KB` or `10MiB`.
+    pub fn byte_size(&mut self, key: &str, default: u64) -> u64 {
+        self.with(
+            key,
+            default,
+            "a byte size such as 64KB or 10MiB",
+            env::parse_byte_size,
+        )
+    }
+
+    /// Parses a case-insensitive enum name.
+    pub fn enumeration<T>(&mut self, key: &str, default: T) -> T
+    where
+        T: EnvEnum + std::fmt::Debug,
+    {
+        let expected = format!("one of {}", T::names().join(", "));
+        self.with(key, default, &expected, env::parse_enum::<T>)
+    }
+
+    /// Parses a required absolute URL.
+    pub fn url(&mut self, key: &str) -> Option<Url> {
+        let value = self.required(key);
+        if value.is_empty() {
+            return None;
+        }
+
+        let url = env::parse_url(&value);
+        if url.is_none() {
+            self.invalid(key, "is not a valid URL");
+        }
+        url
+    }
+
+    /// Parses the value of `key` with `parse`, recording an issue that names
+    /// the `expected` format when it is present but invalid.
+    pub fn with<T, F>(&mut self, key: &str, default: T, expected: &str, parse: F) -> T
+    where
+        T: std::fmt::Debug,
+        F: FnOnce(&str) -> Option<T>,
+    {
+        let Some(value) = self.optional(key) else {
+            self.defaulted(key, &default);
+            return default;
+        };
+
+        match parse(&value) {
+            Some(parsed) => parsed,
+            None => self.rejected(key, format!("is not {expected}"), default),
+        }
+    }
+
+    /// Handles a present-but-invalid value of `key`: records an issue in
+    /// strict mode, otherwise logs it and records `default` as used. The
+    /// value itself is left out, as it may be a secret.
+    fn rejected<T>(&mut self, key: &str, message: impl Into<String>, default: T) -> T
+    where
+        T: std::fmt::Debug,
+    {
+        let message = message.into();
+        if self.strict {
+            self.invalid(key, message);
+        } else {
+            warn!(key = %key, default = ?default, "Ignoring invalid configuration value: {message}");
+            self.report
+                .record(key, Some(format!("{default:?}")), ConfigSource::Default);
+        }
+        default
+    }
+
+    /// Returns the names of the groups configured under `group`, e.g. the
+    /// `ANALYTICS` in `DB__ANALYTICS__ENDPOINT`.
+    #[must_use]
+    pub fn groups(&self, group: &str) -> BTreeSet<String> {
+        self.sources.group_names(group)
+    }
+
+    /// Records a validation failure for `key`.
+    pub fn invalid(&mut self, key: &str, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            key: key.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Completes loading, failing with every recorded issue.
+    ///
+    /// On success, returns the source of every resolved setting together with
+    /// file entries and environment variables that no setting read but that
+    /// look like they were meant for this application.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if any value was missing or invalid, or if a CLI
+    /// flag does not correspond to a known setting.
+    pub fn finish(mut self) -> Result<ConfigReport, ConfigError> {
+        let unknown_flags: Vec<String> = self
+            .sources
+            .cli_keys()
+            .filter(|key| !self.used.contains(*key))
+            .cloned()
+            .collect();
+        for key in unknown_flags {
+            self.invalid(&key, "does not match any known setting");
+        }
+
+        if !self.issues.is_empty() {
+            return Err(ConfigError {
+                issues: self.issues,
+            });
+        }
+
+        let unknown = self.unknown_settings();
+        Ok(self.report.build(unknown))
+    }
+
+    /// Finds file entries and related environment variables that were never read.
+    fn unknown_settings(&self) -> Vec<UnknownSetting> {
+        let is_unused =
+            |key: &String| !self.used.contains(key) && !META_KEYS.contains(&key.as_str());
+        let sections: HashSet<&str> = self
+            .used
+            .iter()
+            .filter_map(|key| key.split('_').next())
+            .filter(|section| !FOREIGN_SECTIONS.contains(section))
+            .collect();
+
+        let file_path = self.sources.file_path.clone().unwrap_or_default();
+        let from_file = self
+            .sources
+            .file_keys()
+            .filter(|key| is_unused(key))
+            .map(|key| UnknownSetting {
+                key: key.clone(),
+                source: ConfigSource::File(file_path.clone()),
+                suggestion: suggest(key, &self.used),
+            });
+
+        let from_env = env::keys().into_iter().filter(is_unused).filter_map(|key| {
+            let suggestion = suggest(&key, &self.used);
+            let section = key.split('_').next().unwrap_or_default();
+            (suggestion.is_some() || sections.contains(section)).then_some(UnknownSetting {
+                key,
+                source: ConfigSource::Environment,
+                suggestion,
+            })
+        });
+
+        from_file.chain(from_env).collect()
+    }
+}
@@ -0,0 +1,217 @@
+use super::{AppConfig, ConfigError, ConfigReader};
+use crate::sys::events::{Event, EventBus};
+use arc_swap::{ArcSwap, Guard};
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// Settings for configuration hot reload.
+#[derive(Debug, Clone)]
+pub struct ReloadConfig {
+    pub enabled: bool,
+    /// How often the configuration file is checked for changes.
+    pub watch_interval: Duration,
+}
+
+impl ReloadConfig {
+    /// Creates a `ReloadConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config.bool("CONFIG_RELOAD_ENABLED", true);
+        let watch_interval = config.duration("CONFIG_WATCH_INTERVAL", Duration::from_secs(5));
+        if watch_interval < Duration::from_millis(100) {
+            config.invalid("CONFIG_WATCH_INTERVAL", "must be at least 100ms");
+        }
+
+        Self {
+            enabled,
+            watch_interval: watch_interval.max(Duration::from_millis(100)),
+        }
+    }
+}
+
+/// Shared handle to the live configuration.
+///
+/// Reads are lock-free snapshots; a successful reload atomically replaces the
+/// configuration and notifies every subscriber.
+pub struct ConfigHandle {
+    current: ArcSwap<AppConfig>,
+    notifier: watch::Sender<Arc<AppConfig>>,
+}
+
+impl ConfigHandle {
+    #[must_use]
+    pub fn new(config: AppConfig) -> Self {
+        let config = Arc::new(config);
+        let (notifier, _) = watch::channel(config.clone());
+        Self {
+            current: ArcSwap::new(config),
+            notifier,
+        }
+    }
+
+    /// Returns a cheap snapshot of the current configuration.
+    pub fn load(&self) -> Guard<Arc<AppConfig>> {
+        self.current.load()
+    }
+
+    /// Returns an owned reference to the current configuration.
+    #[must_use]
+    pub fn current(&self) -> Arc<AppConfig> {
+        self.current.load_full()
+    }
+
+    /// Subscribes to configuration changes. The receiver yields the new
+    /// configuration after every successful reload.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<Arc<AppConfig>> {
+        self.notifier.subscribe()
+    }
+
+    /// Re-reads and validates configuration, swapping it in on success.
+    ///
+    /// Settings bound at startup (listeners, TLS and database connection)
+    /// cannot change at runtime: they keep their current values and a warning
+    /// is logged if the new configuration differs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if the new configuration is invalid, in which
+    /// case the current configuration stays in effect.
+    pub fn reload(&self) -> Result<(), ConfigError> {
+        let mut next = AppConfig::load()?;
+        let current = self.current();
+
+        if next.server != current.server {
+            warn!("Server listener changes require a restart and were not applied");
+        }
+        if next.tls != current.tls {
+            warn!("TLS setting changes require a restart and were not applied");
+        }
+        let databases_changed = next.databases.keys().ne(current.databases.keys())
+            || std::iter::once((&next.database, &current.database))
+                .chain(next.databases.values().zip(current.databases.values()))
+                .any(|(next, current)| next.connection_differs(current));
+        if databases_changed {
+            warn!("Database connection changes require a restart and were not applied");
+        }
+        next.server = current.server.clone();
+        next.tls = current.tls.clone();
+        next.database = current.database.clone();
+        next.databases = current.databases.clone();
+
+        let next = Arc::new(next);
+        self.current.store(next.clone());
+        self.notifier.send_replace(next);
+        info!("Configuration reloaded");
+        Ok(())
+    }
+
+    fn reload_and_report(&self, trigger: &str) {
+        info!(trigger = %trigger, "Reloading configuration");
+        if let Err(e) = self.reload() {
+            for issue in &e.issues {
+                error!(key = %issue.key, "Rejected configuration reload: {}", issue.message);
+            }
+        }
+    }
+}
+
+/// Published on the event bus after every successful reload.
+#[derive(Debug, Clone)]
+pub struct ConfigReloaded {
+    pub config: Arc<AppConfig>,
+}
+
+impl Event for ConfigReloaded {
+    const NAME: &'static str = "config.reloaded";
+}
+
+/// Spawns the task that publishes [`ConfigReloaded`] on `events` after
+/// every successful reload. Reloads in quick succession may be reported
+/// once, with the latest configuration.
+pub fn spawn_reload_events(handle: &ConfigHandle, events: EventBus) {
+    let mut changes = handle.subscribe();
+    tokio::spawn(async move {
+        while changes.changed().await.is_ok() {
+            let config = changes.borrow_and_update().clone();
+            events.publish(ConfigReloaded { config });
+        }
+    });
+}
+
+/// Spawns the task that reloads configuration on `SIGHUP` and when the
+/// configuration file changes on disk.
+pub fn spawn_config_watcher(handle: Arc<ConfigHandle>) {
+    let config = handle.current();
+    if !config.reload.enabled {
+        info!("Configuration hot reload is disabled");
+        return;
+    }
+    let file = config.file.clone();
+    let interval = config.reload.watch_interval;
+
+    tokio::spawn(async move {
+        let mut hangup = hangup_signal();
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_modified = file.as_deref().and_then(modified_at);
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => handle.reload_and_report("SIGHUP"),
+                _ = ticker.tick() => {
+                    let Some(path) = file.as_deref() else { continue };
+                    let modified = modified_at(path);
+                    if modified != last_modified {
+                        last_modified = modified;
+                        handle.reload_and_report("file change");
+                    }
+                }
+            }
+        }
+    });
+
+    info!(
+        interval_secs = interval.as_secs(),
+        "Configuration watcher started"
+    );
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Receives `SIGHUP` on Unix; never fires elsewhere.
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut() {
+            signal.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await;
+    }
+}
+
+fn hangup_signal() -> Hangup {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let signal = signal(SignalKind::hangup())
+            .map_err(|e| warn!(error = %e, "Failed to register SIGHUP handler"))
+            .ok();
+        Hangup { signal }
+    }
+    #[cfg(not(unix))]
+    {
+        Hangup {}
+    }
+}
@@ -0,0 +1,142 @@
+use crate::sys::env::EnvOrigin;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::PathBuf,
+};
+
+/// Placeholder shown instead of sensitive values.
+const MASK: &str = "********";
+
+/// Key fragments that mark a setting as sensitive even when it was not read
+/// as a secret.
+const SENSITIVE_FRAGMENTS: [&str; 5] = ["PASSWORD", "SECRET", "TOKEN", "PRIVATE_KEY", "API_KEY"];
+
+/// The layer a configuration value was resolved from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "path", rename_all = "snake_case")]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    DotEnv,
+    Environment,
+    SecretFile(PathBuf),
+    Cli,
+}
+
+impl From<EnvOrigin> for ConfigSource {
+    fn from(origin: EnvOrigin) -> Self {
+        match origin {
+            EnvOrigin::Process => Self::Environment,
+            EnvOrigin::DotEnv => Self::DotEnv,
+            EnvOrigin::SecretFile(path) => Self::SecretFile(path),
+        }
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::DotEnv => write!(f, ".env"),
+            Self::Environment => write!(f, "environment"),
+            Self::SecretFile(path) => write!(f, "secret_file:{}", path.display()),
+            Self::Cli => write!(f, "cli"),
+        }
+    }
+}
+
+/// The effective value of one setting and where it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedSetting {
+    pub key: String,
+    /// The value, masked for sensitive settings. `None` if unset without default.
+    pub value: Option<String>,
+    pub source: ConfigSource,
+}
+
+/// A variable or file entry that no setting read, usually a typo.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnknownSetting {
+    pub key: String,
+    pub source: ConfigSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Every setting resolved while loading configuration.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigReport {
+    pub settings: Vec<ResolvedSetting>,
+    pub unknown: Vec<UnknownSetting>,
+}
+
+/// Accumulates resolved settings while the configuration is read.
+#[derive(Debug, Default)]
+pub(super) struct ReportBuilder {
+    settings: BTreeMap<String, (Option<String>, ConfigSource)>,
+    sensitive: BTreeSet<String>,
+}
+
+impl ReportBuilder {
+    pub(super) fn record(&mut self, key: &str, value: Option<String>, source: ConfigSource) {
+        self.settings.insert(key.to_string(), (value, source));
+    }
+
+    pub(super) fn mark_sensitive(&mut self, key: &str) {
+        self.sensitive.insert(key.to_string());
+    }
+
+    pub(super) fn build(self, unknown: Vec<UnknownSetting>) -> ConfigReport {
+        let settings = self
+            .settings
+            .into_iter()
+            .map(|(key, (value, source))| {
+                let sensitive = self.sensitive.contains(&key) || is_sensitive_key(&key);
+                let value = value.map(|v| if sensitive { MASK.to_string() } else { v });
+                ResolvedSetting { key, value, source }
+            })
+            .collect();
+
+        ConfigReport { settings, unknown }
+    }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    SENSITIVE_FRAGMENTS
+        .iter()
+        .any(|fragment| key.contains(fragment))
+}
+
+/// Returns the known key closest to `key` if it is within a small edit
+/// distance, which suggests `key` is a misspelling of it.
+pub(super) fn suggest<'a>(
+    key: &str,
+    known: impl IntoIterator<Item = &'a String>,
+) -> Option<String> {
+    known
+        .into_iter()
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance > 0 && *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/// Levenshtein distance between two ASCII keys.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
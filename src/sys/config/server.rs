@@ -1,19 +1,66 @@
-use crate::sys::env;
+use super::reader::ConfigReader;
+use std::path::PathBuf;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Unix domain socket that also serves the public routes, for example
+    /// for a sidecar proxy on the same host.
+    pub unix_socket: Option<PathBuf>,
+    /// Serves `/health`, `/metrics` and `/admin/*` on a separate listener
+    /// instead of the public one.
+    pub admin_listener: bool,
+    pub admin_host: String,
+    pub admin_port: u16,
+    /// Also serves `/health` on the public listener when the admin listener
+    /// is enabled, for probes that cannot reach it.
+    pub public_health: bool,
 }
 
 impl ServerConfig {
-    /// Creates a `ServerConfig` from environment variables.
-    #[must_use]
-    pub fn from_env() -> Self {
-        let host = env::get_or_default("SERVER_HOST", "0.0.0.0");
-        let port: u16 = env::get_parsed_or_default("SERVER_PORT", 3000);
+    /// Creates a `ServerConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let host = config.string("SERVER_HOST", "0.0.0.0");
+        let port: u16 = config.parsed("SERVER_PORT", 3000);
+
+        if host.trim().is_empty() {
+            config.invalid("SERVER_HOST", "must not be empty");
+        }
+        if port == 0 {
+            config.invalid("SERVER_PORT", "must be between 1 and 65535");
+        }
+
+        let unix_socket = config
+            .optional("SERVER_UNIX_SOCKET")
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+
+        let admin_listener = config.bool("ADMIN_LISTENER_ENABLED", true);
+        let admin_host = config.string("ADMIN_HOST", "127.0.0.1");
+        let admin_port: u16 = config.parsed("ADMIN_PORT", 9090);
+        let public_health = config.bool("ADMIN_PUBLIC_HEALTH", false);
 
-        Self { host, port }
+        if admin_listener {
+            if admin_host.trim().is_empty() {
+                config.invalid("ADMIN_HOST", "must not be empty");
+            }
+            if admin_port == 0 {
+                config.invalid("ADMIN_PORT", "must be between 1 and 65535");
+            } else if admin_port == port {
+                config.invalid("ADMIN_PORT", "must differ from SERVER_PORT");
+            }
+        }
+
+        Self {
+            host,
+            port,
+            unix_socket,
+            admin_listener,
+            admin_host,
+            admin_port,
+            public_health,
+        }
     }
 
     /// Returns the full address as a string (host:port).
@@ -21,4 +68,12 @@ impl ServerConfig {
     pub fn address(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
+
+    /// Returns the admin listener address, or `None` if admin routes are
+    /// served on the public listener.
+    #[must_use]
+    pub fn admin_address(&self) -> Option<String> {
+        self.admin_listener
+            .then(|| format!("{}:{}", self.admin_host, self.admin_port))
+    }
 }
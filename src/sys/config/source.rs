@@ -0,0 +1,157 @@
+use super::{cli::CliArgs, error::ConfigIssue, report::ConfigSource};
+use crate::sys::env::{self, EnvironmentError};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The layers configuration values are resolved from, in increasing priority:
+/// defaults, configuration file, environment variables and CLI flags.
+#[derive(Debug, Default)]
+pub struct ConfigSources {
+    pub file_path: Option<PathBuf>,
+    file: BTreeMap<String, String>,
+    cli: BTreeMap<String, String>,
+}
+
+impl ConfigSources {
+    /// Builds the layers from parsed CLI flags and the configuration file they
+    /// (or `CONFIG_FILE`) point to. Problems are appended to `issues`.
+    pub fn load(cli: CliArgs, issues: &mut Vec<ConfigIssue>) -> Self {
+        issues.extend(cli.issues);
+
+        let file_path = cli
+            .config_file
+            .or_else(|| env::get_optional("CONFIG_FILE").map(PathBuf::from));
+
+        let file = match &file_path {
+            Some(path) => read_file(path).unwrap_or_else(|message| {
+                issues.push(ConfigIssue {
+                    key: "CONFIG_FILE".to_string(),
+                    message,
+                });
+                BTreeMap::new()
+            }),
+            None => BTreeMap::new(),
+        };
+
+        Self {
+            file_path,
+            file,
+            cli: cli.overrides,
+        }
+    }
+
+    /// Resolves a key from the highest priority layer that defines it,
+    /// together with the layer it came from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EnvironmentError::FileError` if the key points at a secret
+    /// file that cannot be read.
+    pub fn lookup(&self, key: &str) -> Result<Option<(String, ConfigSource)>, EnvironmentError> {
+        if let Some(value) = self.cli.get(key) {
+            return Ok(Some((value.clone(), ConfigSource::Cli)));
+        }
+        if let Some((value, origin)) = env::try_get_with_origin(key)? {
+            return Ok(Some((value, origin.into())));
+        }
+        Ok(self.file.get(key).map(|value| {
+            let path = self.file_path.clone().unwrap_or_default();
+            (value.clone(), ConfigSource::File(path))
+        }))
+    }
+
+    /// Returns the keys defined in the configuration file.
+    pub fn file_keys(&self) -> impl Iterator<Item = &String> {
+        self.file.keys()
+    }
+
+    /// Returns the names of groups such as `ANALYTICS` in `DB__ANALYTICS__ENDPOINT`
+    /// defined in any layer.
+    #[must_use]
+    pub fn group_names(&self, group: &str) -> BTreeSet<String> {
+        let env_keys = env::keys();
+        env::group_names(
+            group,
+            self.cli
+                .keys()
+                .chain(self.file.keys())
+                .chain(env_keys.iter()),
+        )
+    }
+
+    /// Returns the CLI overrides, used to detect flags that match no setting.
+    pub fn cli_keys(&self) -> impl Iterator<Item = &String> {
+        self.cli.keys()
+    }
+}
+
+/// Reads a TOML or YAML file and flattens it into environment-style keys,
+/// so `[server] port = 3000` becomes `SERVER_PORT=3000`.
+fn read_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {e}", path.display()))?;
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let value: Value = match extension.as_str() {
+        "toml" => toml::from_str::<toml::Value>(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|v| serde_json::to_value(v).map_err(|e| e.to_string())),
+        "yaml" | "yml" => serde_yaml::from_str::<serde_yaml::Value>(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|v| serde_json::to_value(v).map_err(|e| e.to_string())),
+        other => Err(format!("Unsupported configuration file format '{other}'")),
+    }
+    .map_err(|e| format!("Failed to parse '{}': {e}", path.display()))?;
+
+    let mut values = BTreeMap::new();
+    flatten(&[], &value, &mut values);
+    Ok(values)
+}
+
+fn flatten(path: &[String], value: &Value, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                let mut child_path = path.to_vec();
+                child_path.push(name.clone());
+                flatten(&child_path, child, out);
+            }
+        }
+        Value::Array(items) => {
+            let joined = items
+                .iter()
+                .map(scalar_to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            out.insert(path_to_key(path), joined);
+        }
+        Value::Null => {}
+        scalar => {
+            out.insert(path_to_key(path), scalar_to_string(scalar));
+        }
+    }
+}
+
+/// Builds the environment key for a path of table names. Sections join with a
+/// single underscore (`[db] endpoint` is `DB_ENDPOINT`), while deeper tables
+/// name a group (`[db.analytics] endpoint` is `DB__ANALYTICS__ENDPOINT`).
+fn path_to_key(path: &[String]) -> String {
+    let separator = if path.len() > 2 { "__" } else { "_" };
+    path.join(separator).to_uppercase()
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -1,8 +1,51 @@
-use crate::{dbs::models::DbConnection, sys::health::models::HealthCheck};
-use std::sync::Arc;
+use crate::{
+    dbs::models::DbConnection,
+    sys::{
+        cache::Cache,
+        config::ConfigHandle,
+        events::EventBus,
+        health::models::{HealthCheck, HealthTracker},
+        jobs::JobQueue,
+        metrics::MetricsRegistry,
+        middleware::{
+            idempotency::IdempotencyStore, load_shed::LoadShedder, rate_limit::RateLimitStore,
+        },
+        outbox::Outbox,
+        scheduler::Scheduler,
+        server::Shutdown,
+        tenancy::Tenants,
+        webhooks::Webhooks,
+    },
+};
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db_connection: DbConnection,
+    /// Named databases configured as `DB__<NAME>__*`, keyed by lowercase name.
+    pub databases: BTreeMap<String, DbConnection>,
     pub health_checkers: Arc<Vec<Box<dyn HealthCheck>>>,
+    /// Last status of each health check, to publish changes.
+    pub health_tracker: Arc<HealthTracker>,
+    pub config: Arc<ConfigHandle>,
+    pub metrics: Arc<MetricsRegistry>,
+    pub rate_limiter: Arc<dyn RateLimitStore>,
+    pub load_shedder: LoadShedder,
+    pub idempotency: IdempotencyStore,
+    pub cache: Cache,
+    pub jobs: JobQueue,
+    pub scheduler: Scheduler,
+    pub webhooks: Webhooks,
+    pub outbox: Outbox,
+    pub events: EventBus,
+    pub tenants: Tenants,
+    pub shutdown: Shutdown,
+}
+
+impl AppState {
+    /// Returns the connection of a named database.
+    #[must_use]
+    pub fn database(&self, name: &str) -> Option<&DbConnection> {
+        self.databases.get(&name.to_lowercase())
+    }
 }
@@ -0,0 +1,29 @@
+use std::{collections::HashSet, env, sync::OnceLock};
+
+/// Variables that were set by the `.env` file rather than the process environment.
+static DOTENV_KEYS: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Loads the `.env` file into the process environment, remembering which
+/// variables it provided. Variables already set in the environment win.
+///
+/// Returns true if a `.env` file was found.
+pub fn load_dotenv() -> bool {
+    let keys = dotenvy::dotenv_iter()
+        .map(|iter| {
+            iter.filter_map(Result::ok)
+                .map(|(key, _)| key)
+                .filter(|key| env::var_os(key).is_none())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let loaded = dotenvy::dotenv().is_ok();
+    let _ = DOTENV_KEYS.set(keys);
+    loaded
+}
+
+/// Returns true if the variable was provided by the `.env` file.
+#[must_use]
+pub fn is_from_dotenv(name: &str) -> bool {
+    DOTENV_KEYS.get().is_some_and(|keys| keys.contains(name))
+}
@@ -8,6 +8,11 @@ pub enum EnvironmentError {
         value: String,
         type_name: &'static str,
     },
+    FileError {
+        key: String,
+        path: String,
+        message: String,
+    },
 }
 
 impl fmt::Display for EnvironmentError {
@@ -23,6 +28,9 @@ impl fmt::Display for EnvironmentError {
             } => {
                 write!(f, "Failed to parse '{key}={value}' as {type_name}")
             }
+            Self::FileError { key, path, message } => {
+                write!(f, "Failed to read '{key}' from file '{path}': {message}")
+            }
         }
     }
 }
@@ -1,18 +1,135 @@
-use super::error::EnvironmentError;
-use std::{env, str::FromStr};
-use tracing::debug;
+use super::{
+    dotenv::is_from_dotenv,
+    error::EnvironmentError,
+    parse::{
+        EnvEnum, parse_bool, parse_byte_size, parse_duration, parse_enum, parse_list, parse_url,
+    },
+    prefix::prefixed,
+    secret::Secret,
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+    time::Duration,
+};
+use tracing::{debug, warn};
+use url::Url;
+
+/// Directory searched for secret files when `SECRETS_DIR` is not set.
+const DEFAULT_SECRETS_DIR: &str = "/run/secrets";
+
+/// Resolves an environment variable, falling back to secret files.
+///
+/// Lookup order:
+/// 1. the variable itself (`KEY`)
+/// 2. the trimmed contents of the file named by `KEY_FILE`
+/// 3. a file named `KEY` or `key` in `SECRETS_DIR` (default `/run/secrets`)
+///
+/// When an application prefix is set, every name above carries it, e.g.
+/// `MYAPP_KEY` and `MYAPP_KEY_FILE`.
+///
+/// # Errors
+///
+/// Returns `EnvironmentError::FileError` if `KEY_FILE` is set but the file
+/// cannot be read.
+pub fn try_get_optional(key: &str) -> Result<Option<String>, EnvironmentError> {
+    Ok(try_get_with_origin(key)?.map(|(value, _)| value))
+}
+
+/// Where a resolved environment value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvOrigin {
+    /// The process environment.
+    Process,
+    /// The `.env` file.
+    DotEnv,
+    /// A secret file, via `KEY_FILE` or the secrets directory.
+    SecretFile(PathBuf),
+}
+
+/// Resolves an environment variable like [`try_get_optional`], also
+/// reporting where the value was found.
+///
+/// # Errors
+///
+/// Returns `EnvironmentError::FileError` if `KEY_FILE` is set but the file
+/// cannot be read.
+pub fn try_get_with_origin(key: &str) -> Result<Option<(String, EnvOrigin)>, EnvironmentError> {
+    let key = prefixed(key);
+    let key = key.as_str();
+
+    if let Ok(value) = env::var(key) {
+        let origin = if is_from_dotenv(key) {
+            EnvOrigin::DotEnv
+        } else {
+            EnvOrigin::Process
+        };
+        return Ok(Some((value, origin)));
+    }
+
+    if let Ok(path) = env::var(format!("{key}_FILE")) {
+        let path = PathBuf::from(path);
+        let value = read_secret_file(key, &path)?;
+        return Ok(Some((value, EnvOrigin::SecretFile(path))));
+    }
+
+    let secrets_dir =
+        env::var("SECRETS_DIR").map_or_else(|_| PathBuf::from(DEFAULT_SECRETS_DIR), PathBuf::from);
+    for name in [key.to_string(), key.to_lowercase()] {
+        let path = secrets_dir.join(name);
+        if path.is_file() {
+            let value = read_secret_file(key, &path)?;
+            return Ok(Some((value, EnvOrigin::SecretFile(path))));
+        }
+    }
+
+    Ok(None)
+}
+
+fn read_secret_file(key: &str, path: &Path) -> Result<String, EnvironmentError> {
+    let value = fs::read_to_string(path).map_err(|e| EnvironmentError::FileError {
+        key: key.to_string(),
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })?;
+
+    debug!(key = %key, path = %path.display(), "Loaded environment variable from secret file");
+    Ok(value.trim().to_string())
+}
 
 /// Retrieves a required environment variable as a String
 /// # Errors
-/// Returns `EnvironmentError::NotFoundError` if the variable is not set
+/// - `EnvironmentError::NotFoundError` if the variable is not set
+/// - `EnvironmentError::FileError` if its secret file cannot be read
 pub fn get_required(key: &str) -> Result<String, EnvironmentError> {
-    env::var(key).map_err(|_| EnvironmentError::NotFoundError(key.to_string()))
+    try_get_optional(key)?.ok_or_else(|| EnvironmentError::NotFoundError(prefixed(key)))
+}
+
+/// Retrieves a required secret, see [`try_get_optional`] for the lookup order.
+/// # Errors
+/// - `EnvironmentError::NotFoundError` if the secret is not set
+/// - `EnvironmentError::FileError` if its secret file cannot be read
+pub fn get_secret(key: &str) -> Result<Secret, EnvironmentError> {
+    get_required(key).map(Secret::from)
+}
+
+/// Retrieves an optional environment variable
+///
+/// A secret file that cannot be read is logged and treated as unset.
+#[must_use]
+pub fn get_optional(key: &str) -> Option<String> {
+    try_get_optional(key).unwrap_or_else(|e| {
+        warn!(error = %e, "Ignoring unreadable secret file");
+        None
+    })
 }
 
 /// Retrieves an optional environment variable with a default value
 #[must_use]
 pub fn get_or_default(key: &str, default: &str) -> String {
-    env::var(key).unwrap_or_else(|_| {
+    get_optional(key).unwrap_or_else(|| {
         debug!(key = %key, default = %default, "Using default value for environment variable");
         default.to_string()
     })
@@ -40,30 +157,154 @@ where
 }
 
 /// Retrieves and parses an environment variable with a default value
+///
+/// A value that cannot be parsed is logged and replaced by the default; use
+/// [`try_get_parsed_or_default`] to honor strict mode instead.
 #[must_use]
 pub fn get_parsed_or_default<T>(key: &str, default: T) -> T
 where
     T: FromStr + std::fmt::Debug,
 {
-    env::var(key)
-        .ok()
-        .and_then(|v| v.parse::<T>().ok())
-        .unwrap_or_else(|| {
+    match get_optional(key) {
+        Some(value) => value.parse::<T>().unwrap_or_else(|_| {
+            warn!(key = %key, default = ?default, "Ignoring invalid environment variable");
+            default
+        }),
+        None => {
             debug!(key = %key, default = ?default, "Using default parsed value for environment variable");
             default
-        })
+        }
+    }
+}
+
+/// Retrieves and parses an environment variable with a default value,
+/// honoring strict mode.
+///
+/// # Errors
+///
+/// Returns `EnvironmentError::ParseError` in strict mode if the variable is
+/// set but invalid.
+pub fn try_get_parsed_or_default<T>(key: &str, default: T) -> Result<T, EnvironmentError>
+where
+    T: FromStr + std::fmt::Debug,
+{
+    get_with(key, default, |v| v.parse::<T>().ok())
+}
+
+/// Retrieves a duration such as `500ms`, `30s` or `2m`; bare numbers are seconds.
+///
+/// # Errors
+///
+/// Returns `EnvironmentError::ParseError` in strict mode if the variable is
+/// set but invalid.
+pub fn get_duration(key: &str, default: Duration) -> Result<Duration, EnvironmentError> {
+    get_with(key, default, parse_duration)
+}
+
+/// Retrieves a byte size such as `512`, `64KB` or `10MiB`.
+///
+/// # Errors
+///
+/// Returns `EnvironmentError::ParseError` in strict mode if the variable is
+/// set but invalid.
+pub fn get_byte_size(key: &str, default: u64) -> Result<u64, EnvironmentError> {
+    get_with(key, default, parse_byte_size)
+}
+
+/// Retrieves a case-insensitive enum value.
+///
+/// # Errors
+///
+/// Returns `EnvironmentError::ParseError` in strict mode if the variable is
+/// set but not one of the enum's names.
+pub fn get_enum<T>(key: &str, default: T) -> Result<T, EnvironmentError>
+where
+    T: EnvEnum + std::fmt::Debug,
+{
+    get_with(key, default, parse_enum::<T>)
+}
+
+/// Retrieves a required absolute URL.
+///
+/// # Errors
+///
+/// - `EnvironmentError::NotFoundError` if the variable is not set.
+/// - `EnvironmentError::ParseError` if the variable is not a valid URL.
+pub fn get_url(key: &str) -> Result<Url, EnvironmentError> {
+    let value = get_required(key)?;
+    parse_url(&value).ok_or_else(|| parse_error::<Url>(key, &value))
+}
+
+/// Retrieves a comma-separated list, using `default` when the variable is not set.
+#[must_use]
+pub fn get_list(key: &str, default: &str) -> Vec<String> {
+    parse_list(&get_or_default(key, default))
+}
+
+/// Resolves `key` with a custom parser, falling back to `default` when the
+/// variable is unset, or invalid outside strict mode.
+fn get_with<T, F>(key: &str, default: T, parse: F) -> Result<T, EnvironmentError>
+where
+    T: std::fmt::Debug,
+    F: FnOnce(&str) -> Option<T>,
+{
+    let Some(value) = try_get_optional(key)? else {
+        debug!(key = %key, default = ?default, "Using default parsed value for environment variable");
+        return Ok(default);
+    };
+
+    match parse(&value) {
+        Some(parsed) => Ok(parsed),
+        None if is_strict() => Err(parse_error::<T>(key, &value)),
+        None => {
+            warn!(key = %key, default = ?default, "Ignoring invalid environment variable");
+            Ok(default)
+        }
+    }
+}
+
+fn parse_error<T>(key: &str, value: &str) -> EnvironmentError {
+    EnvironmentError::ParseError {
+        key: key.to_string(),
+        value: value.to_string(),
+        type_name: std::any::type_name::<T>(),
+    }
+}
+
+/// Strict mode state: 0 = read from `ENV_STRICT` on first use, 1 = off, 2 = on.
+static STRICT_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Enables or disables strict mode.
+///
+/// In strict mode the fallible getters return `EnvironmentError::ParseError`,
+/// and layered configuration records an issue, for a present-but-invalid
+/// value instead of falling back to the default. It defaults to the
+/// `ENV_STRICT` environment variable, and is off when that is unset.
+pub fn set_strict(strict: bool) {
+    STRICT_MODE.store(if strict { 2 } else { 1 }, Ordering::Relaxed);
+}
+
+/// Returns true if strict mode is enabled.
+#[must_use]
+pub fn is_strict() -> bool {
+    match STRICT_MODE.load(Ordering::Relaxed) {
+        0 => {
+            let strict = env::var("ENV_STRICT")
+                .ok()
+                .and_then(|v| parse_bool(&v))
+                .unwrap_or(false);
+            set_strict(strict);
+            strict
+        }
+        mode => mode == 2,
+    }
 }
 
 /// Retrieves a boolean environment variable
 /// Accepts: true/false, 1/0, yes/no, on/off (case-insensitive)
 #[must_use]
 pub fn get_bool(key: &str, default: bool) -> bool {
-    env::var(key)
-        .ok()
-        .and_then(|v| match v.to_lowercase().as_str() {
-            "true" | "1" | "yes" | "on" => Some(true),
-            "false" | "0" | "no" | "off" => Some(false),
-            _ => None,
-        })
+    get_optional(key)
+        .and_then(|v| parse_bool(&v))
         .unwrap_or(default)
 }
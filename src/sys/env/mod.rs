@@ -1,4 +1,18 @@
+pub mod dotenv;
 pub mod error;
 pub mod loader;
+pub mod parse;
+pub mod prefix;
+pub mod secret;
+pub use dotenv::{is_from_dotenv, load_dotenv};
 pub use error::EnvironmentError;
-pub use loader::{get_bool, get_or_default, get_parsed, get_parsed_or_default, get_required};
+pub use loader::{
+    EnvOrigin, get_bool, get_byte_size, get_duration, get_enum, get_list, get_optional,
+    get_or_default, get_parsed, get_parsed_or_default, get_required, get_secret, get_url,
+    is_strict, set_strict, try_get_optional, try_get_parsed_or_default, try_get_with_origin,
+};
+pub use parse::{
+    EnvEnum, parse_bool, parse_byte_size, parse_duration, parse_enum, parse_list, parse_url,
+};
+pub use prefix::{group_key, group_names, keys, prefix, prefixed, set_prefix};
+pub use secret::Secret;
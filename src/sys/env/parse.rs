@@ -0,0 +1,118 @@
+use std::time::Duration;
+use url::Url;
+
+/// An enum that can be parsed case-insensitively from configuration values.
+pub trait EnvEnum: Sized + Copy + 'static {
+    /// Accepted names and the variant each maps to.
+    const VARIANTS: &'static [(&'static str, Self)];
+
+    /// Returns the accepted names, for error messages.
+    #[must_use]
+    fn names() -> Vec<&'static str> {
+        Self::VARIANTS.iter().map(|(name, _)| *name).collect()
+    }
+}
+
+/// Parses a boolean value
+/// Accepts: true/false, 1/0, yes/no, on/off (case-insensitive)
+#[must_use]
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a case-insensitive enum name.
+#[must_use]
+pub fn parse_enum<T: EnvEnum>(value: &str) -> Option<T> {
+    let value = value.trim();
+    T::VARIANTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|(_, variant)| *variant)
+}
+
+/// Parses a human readable duration such as `500ms`, `30s`, `2m`, `1h30m` or `1d`.
+///
+/// A bare number is interpreted as seconds for compatibility with existing
+/// integer timeout settings.
+#[must_use]
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let (number, tail) = rest.split_at(digits);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+
+        let number: f64 = number.parse().ok()?;
+        let unit_secs = match unit.trim().to_lowercase().as_str() {
+            "ms" => 0.001,
+            "s" | "sec" | "secs" => 1.0,
+            "m" | "min" | "mins" => 60.0,
+            "h" | "hr" | "hrs" => 3_600.0,
+            "d" => 86_400.0,
+            _ => return None,
+        };
+        total = total.checked_add(Duration::try_from_secs_f64(number * unit_secs).ok()?)?;
+        rest = tail.trim_start();
+    }
+
+    Some(total)
+}
+
+/// Parses a byte size such as `512`, `64KB`, `10MiB` or `1.5GB`.
+///
+/// Decimal units (`KB`, `MB`, `GB`) are powers of 1000, binary units (`KiB`,
+/// `MiB`, `GiB`) are powers of 1024. A bare number is a count of bytes.
+#[must_use]
+pub fn parse_byte_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" | "k" => 1e3,
+        "mb" | "m" => 1e6,
+        "gb" | "g" => 1e9,
+        "kib" => 1024.0,
+        "mib" => 1_048_576.0,
+        "gib" => 1_073_741_824.0,
+        _ => return None,
+    };
+
+    let bytes = (number * multiplier).round();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    (bytes.is_finite() && bytes >= 0.0 && bytes <= u64::MAX as f64).then_some(bytes as u64)
+}
+
+/// Splits a comma-separated list, trimming entries and dropping empty ones.
+#[must_use]
+pub fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parses an absolute URL.
+#[must_use]
+pub fn parse_url(value: &str) -> Option<Url> {
+    Url::parse(value.trim()).ok()
+}
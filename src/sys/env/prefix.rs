@@ -0,0 +1,83 @@
+use std::{
+    collections::BTreeSet,
+    env,
+    sync::{OnceLock, RwLock},
+};
+
+/// The application prefix, initialized from `ENV_PREFIX` on first use.
+static PREFIX: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+
+fn prefix_lock() -> &'static RwLock<Option<String>> {
+    PREFIX.get_or_init(|| RwLock::new(env::var("ENV_PREFIX").ok().and_then(normalize)))
+}
+
+fn normalize(prefix: impl AsRef<str>) -> Option<String> {
+    let prefix = prefix.as_ref().trim().trim_end_matches('_').to_uppercase();
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+/// Sets the application prefix, so `DB_ENDPOINT` is read from
+/// `MYAPP_DB_ENDPOINT` when the prefix is `MYAPP`. `None` removes it.
+///
+/// Defaults to the `ENV_PREFIX` environment variable.
+pub fn set_prefix(prefix: Option<&str>) {
+    let mut current = prefix_lock()
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    *current = prefix.and_then(normalize);
+}
+
+/// Returns the current application prefix, if any.
+#[must_use]
+pub fn prefix() -> Option<String> {
+    prefix_lock()
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
+
+/// Returns the name of the process variable that holds `key`.
+#[must_use]
+pub fn prefixed(key: &str) -> String {
+    match prefix() {
+        Some(prefix) => format!("{prefix}_{key}"),
+        None => key.to_string(),
+    }
+}
+
+/// Returns the keys of all variables visible under the current prefix, with
+/// the prefix and any `_FILE` suffix removed.
+#[must_use]
+pub fn keys() -> BTreeSet<String> {
+    let prefix = prefix().map(|p| format!("{p}_"));
+
+    env::vars_os()
+        .filter_map(|(name, _)| name.into_string().ok())
+        .filter_map(|name| match &prefix {
+            Some(prefix) => name.strip_prefix(prefix.as_str()).map(String::from),
+            None => Some(name),
+        })
+        .map(|key| key.strip_suffix("_FILE").map_or(key.clone(), String::from))
+        .collect()
+}
+
+/// Returns the names of groups such as `ANALYTICS` in `DB__ANALYTICS__ENDPOINT`
+/// found among `keys` for the given group prefix (`DB`).
+pub fn group_names<'a>(
+    group: &str,
+    keys: impl IntoIterator<Item = &'a String>,
+) -> BTreeSet<String> {
+    let marker = format!("{group}__");
+    keys.into_iter()
+        .filter_map(|key| key.strip_prefix(marker.as_str()))
+        .filter_map(|rest| rest.split_once("__").map(|(name, _)| name))
+        .filter(|name| !name.is_empty())
+        .map(str::to_uppercase)
+        .collect()
+}
+
+/// Returns the key of `field` within a named group, e.g. `DB__ANALYTICS__ENDPOINT`.
+#[must_use]
+pub fn group_key(group: &str, name: &str, field: &str) -> String {
+    format!("{group}__{}__{field}", name.to_uppercase())
+}
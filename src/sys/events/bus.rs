@@ -0,0 +1,240 @@
+use super::models::{Event, EventsConfig};
+use crate::sys::{metrics::MetricsRegistry, middleware::catch_panic::uncaptured, server::Shutdown};
+use futures::FutureExt;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    future::Future,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{Arc, PoisonError, RwLock},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{Instrument, Span, error, info_span, warn};
+
+type SyncHandler<E> = Arc<dyn Fn(&E) + Send + Sync>;
+
+/// An event with the span it was published in.
+#[derive(Clone)]
+struct Envelope<E> {
+    event: E,
+    span: Span,
+}
+
+/// The subscribers of one event type.
+struct Channel<E> {
+    sender: broadcast::Sender<Envelope<E>>,
+    sync: RwLock<Vec<(String, SyncHandler<E>)>>,
+}
+
+/// Typed publish/subscribe between modules of this process.
+///
+/// Every subscriber of an event type receives every event of that type
+/// published after it subscribed. Sync subscribers run inside
+/// [`EventBus::publish`] and must return quickly. Async subscribers run in
+/// their own task with a bounded buffer, in spans that are children of the
+/// publisher's span; one that falls behind misses the oldest events, which
+/// is logged and counted in `events_lagged_total`.
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<BusInner>,
+}
+
+struct BusInner {
+    channels: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    config: EventsConfig,
+    shutdown: Shutdown,
+    metrics: Arc<MetricsRegistry>,
+}
+
+impl EventBus {
+    #[must_use]
+    pub fn new(config: EventsConfig, shutdown: Shutdown, metrics: Arc<MetricsRegistry>) -> Self {
+        Self {
+            inner: Arc::new(BusInner {
+                channels: RwLock::new(HashMap::new()),
+                config,
+                shutdown,
+                metrics,
+            }),
+        }
+    }
+
+    /// Publishes `event` to every subscriber of its type. Returns the number
+    /// of subscribers it was delivered or queued to.
+    pub fn publish<E: Event>(&self, event: E) -> usize {
+        let channel = self.channel::<E>();
+        self.inner
+            .metrics
+            .counter(
+                "events_published_total",
+                "Events published on the event bus, by event",
+                &[("event", E::NAME)],
+            )
+            .inc();
+
+        let sync = channel
+            .sync
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        for (name, handler) in &sync {
+            // Not the request's panic, so it is printed by the previous hook
+            if uncaptured(|| catch_unwind(AssertUnwindSafe(|| handler(&event)))).is_err() {
+                self.panicked::<E>(name);
+            }
+        }
+
+        let queued = channel
+            .sender
+            .send(Envelope {
+                event,
+                span: Span::current(),
+            })
+            .unwrap_or(0);
+        sync.len() + queued
+    }
+
+    /// Runs `handler` inside `publish` for every event of type `E`.
+    pub fn on_sync<E, F>(&self, name: &str, handler: F)
+    where
+        E: Event,
+        F: Fn(&E) + Send + Sync + 'static,
+    {
+        self.channel::<E>()
+            .sync
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name.to_string(), Arc::new(handler)));
+    }
+
+    /// Runs `handler` for every event of type `E` in a task of its own,
+    /// one event at a time, until shutdown. `name` identifies the
+    /// subscriber in logs and metrics.
+    pub fn on<E, F, Fut>(&self, name: &str, handler: F)
+    where
+        E: Event,
+        F: Fn(E) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        // Subscribed before spawning so no event published after this
+        // returns is missed
+        let mut subscription = self.subscribe::<E>(name);
+        let shutdown = self.inner.shutdown.clone();
+        let bus = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            loop {
+                let envelope = tokio::select! {
+                    envelope = subscription.recv_envelope() => envelope,
+                    () = shutdown.triggered() => None,
+                };
+                let Some(envelope) = envelope else { break };
+
+                let span = info_span!(
+                    parent: &envelope.span,
+                    "event",
+                    event = E::NAME,
+                    subscriber = %name
+                );
+                let handled = AssertUnwindSafe(handler(envelope.event))
+                    .catch_unwind()
+                    .instrument(span)
+                    .await;
+                if handled.is_err() {
+                    bus.panicked::<E>(&name);
+                }
+            }
+        });
+    }
+
+    /// Subscribes to events of type `E`, to receive them in a loop of the
+    /// caller's own. `name` identifies the subscriber in logs and metrics.
+    #[must_use]
+    pub fn subscribe<E: Event>(&self, name: &str) -> Subscription<E> {
+        Subscription {
+            receiver: self.channel::<E>().sender.subscribe(),
+            name: name.to_string(),
+            metrics: self.inner.metrics.clone(),
+        }
+    }
+
+    /// Returns the channel of `E`, creating it on first use.
+    fn channel<E: Event>(&self) -> Arc<Channel<E>> {
+        let key = TypeId::of::<E>();
+        let existing = self
+            .inner
+            .channels
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .cloned();
+        let channel = existing.unwrap_or_else(|| {
+            self.inner
+                .channels
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(key)
+                .or_insert_with(|| {
+                    Arc::new(Channel::<E> {
+                        sender: broadcast::channel(self.inner.config.capacity).0,
+                        sync: RwLock::new(Vec::new()),
+                    })
+                })
+                .clone()
+        });
+        channel
+            .downcast::<Channel<E>>()
+            .unwrap_or_else(|_| unreachable!("channels are keyed by their event type"))
+    }
+
+    fn panicked<E: Event>(&self, subscriber: &str) {
+        error!(event = E::NAME, subscriber, "Event subscriber panicked");
+        self.inner
+            .metrics
+            .counter(
+                "events_subscriber_panics_total",
+                "Event subscribers that panicked, by event and subscriber",
+                &[("event", E::NAME), ("subscriber", subscriber)],
+            )
+            .inc();
+    }
+}
+
+/// Events of one type received by a subscriber.
+pub struct Subscription<E> {
+    receiver: broadcast::Receiver<Envelope<E>>,
+    name: String,
+    metrics: Arc<MetricsRegistry>,
+}
+
+impl<E: Event> Subscription<E> {
+    /// Waits for the next event. Events missed because the subscriber fell
+    /// behind are reported and skipped. Returns `None` once the bus is gone.
+    pub async fn recv(&mut self) -> Option<E> {
+        self.recv_envelope().await.map(|envelope| envelope.event)
+    }
+
+    async fn recv_envelope(&mut self) -> Option<Envelope<E>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(envelope) => return Some(envelope),
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(
+                        event = E::NAME,
+                        subscriber = %self.name,
+                        missed,
+                        "Event subscriber fell behind and missed events"
+                    );
+                    self.metrics
+                        .counter(
+                            "events_lagged_total",
+                            "Events missed by subscribers that fell behind, by event and subscriber",
+                            &[("event", E::NAME), ("subscriber", &self.name)],
+                        )
+                        .add(missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
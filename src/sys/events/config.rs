@@ -0,0 +1,16 @@
+use super::models::EventsConfig;
+use crate::sys::config::ConfigReader;
+
+impl EventsConfig {
+    /// Creates an `EventsConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let capacity = config.parsed("EVENTS_CAPACITY", 1024);
+        if capacity == 0 {
+            config.invalid("EVENTS_CAPACITY", "must be greater than zero");
+        }
+
+        Self {
+            capacity: capacity.max(1),
+        }
+    }
+}
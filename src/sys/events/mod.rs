@@ -0,0 +1,5 @@
+mod bus;
+mod config;
+mod models;
+pub use bus::{EventBus, Subscription};
+pub use models::{Event, EventsConfig};
@@ -0,0 +1,16 @@
+#[derive(Debug, Clone)]
+pub struct EventsConfig {
+    /// Events buffered for each async subscriber of an event type. A
+    /// subscriber that falls further behind misses the oldest events.
+    pub capacity: usize,
+}
+
+/// A message published on the event bus.
+///
+/// Every subscriber receives its own clone of each event, so events should
+/// be cheap to clone, for example by sharing large data in an `Arc`.
+pub trait Event: Clone + Send + Sync + 'static {
+    /// Name identifying the event type in logs and metrics, such as
+    /// `config.reloaded`.
+    const NAME: &'static str;
+}
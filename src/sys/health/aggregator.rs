@@ -5,12 +5,44 @@ use crate::sys::{
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use futures::future::join_all;
 use std::sync::Arc;
+use tokio::time::sleep;
+use tracing::{Instrument, info, info_span};
 
-/// Aggregates the health of all system components.
-pub async fn aggregate_health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+/// Runs every health check and publishes `HealthChanged` for components
+/// whose status changed since the last check.
+pub async fn check_health(state: &AppState) -> Vec<ComponentHealth> {
     let check_futures = state.health_checkers.iter().map(|checker| checker.check());
 
     let results: Vec<ComponentHealth> = join_all(check_futures).await;
+    for change in state.health_tracker.observe(&results) {
+        state.events.publish(change);
+    }
+    results
+}
+
+/// Spawns the task that runs health checks every `HEALTH_CHECK_INTERVAL`
+/// until shutdown, so changes are published without polling `/health`.
+pub fn spawn_health_checks(state: &Arc<AppState>) {
+    let state = state.clone();
+    tokio::spawn(
+        async move {
+            while !state.shutdown.is_triggered() {
+                check_health(&state).await;
+                let interval = state.config.load().health.check_interval;
+                tokio::select! {
+                    () = sleep(interval) => {}
+                    () = state.shutdown.triggered() => {}
+                }
+            }
+            info!("Health checks stopped");
+        }
+        .instrument(info_span!("health")),
+    );
+}
+
+/// Aggregates the health of all system components.
+pub async fn aggregate_health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let results = check_health(&state).await;
 
     let overall_status = if results
         .iter()
@@ -1,6 +1,11 @@
+use std::collections::BTreeMap;
+
 use crate::{
     dbs::models::{Database, DbConnection},
-    sys::health::models::HealthCheck,
+    sys::{
+        cache::Cache, config::AppConfig, health::models::HealthCheck, jobs::JobQueue,
+        middleware::load_shed::LoadShedder, outbox::Outbox,
+    },
 };
 
 /// Creates and returns a vector of all system health checkers.
@@ -10,12 +15,45 @@ use crate::{
 /// Each checker is boxed and added to the vector, which can then be used by the
 /// health aggregation service.
 #[must_use = "health checkers should be registered or used"]
-pub fn create_health_checkers(db_connection: DbConnection) -> Vec<Box<dyn HealthCheck>> {
-    vec![Box::new(Database {
+pub fn create_health_checkers(
+    db_connection: DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    load_shedder: &LoadShedder,
+    cache: &Cache,
+    jobs: &JobQueue,
+    outbox: &Outbox,
+    config: &AppConfig,
+) -> Vec<Box<dyn HealthCheck>> {
+    let mut checkers: Vec<Box<dyn HealthCheck>> = vec![Box::new(Database {
+        name: "Database".to_string(),
         db: db_connection,
-        // No clone needed since db_connection is only used once.
-        // When adding more components, clone all but the last:
-        // Box::new(Database { db: db_connection.clone() }),
-        // Box::new(Cache { db: db_connection }),
-    })]
+        health_check_timeout: config.database.health_check_timeout,
+    })];
+
+    for (name, db) in databases {
+        let health_check_timeout = config
+            .databases
+            .get(name)
+            .map_or(config.database.health_check_timeout, |c| {
+                c.health_check_timeout
+            });
+        checkers.push(Box::new(Database {
+            name: format!("Database ({name})"),
+            db: db.clone(),
+            health_check_timeout,
+        }));
+    }
+
+    if config.load_shed.enabled {
+        checkers.push(Box::new(load_shedder.clone()));
+    }
+
+    if cache.is_enabled() {
+        checkers.push(Box::new(cache.clone()));
+    }
+
+    checkers.push(Box::new(jobs.clone()));
+    checkers.push(Box::new(outbox.clone()));
+
+    checkers
 }
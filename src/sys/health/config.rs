@@ -0,0 +1,17 @@
+use super::models::HealthConfig;
+use crate::sys::config::ConfigReader;
+use std::time::Duration;
+
+impl HealthConfig {
+    /// Creates a `HealthConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let check_interval = config.duration("HEALTH_CHECK_INTERVAL", Duration::from_secs(30));
+        if check_interval < Duration::from_secs(1) {
+            config.invalid("HEALTH_CHECK_INTERVAL", "must be at least 1s");
+        }
+
+        Self {
+            check_interval: check_interval.max(Duration::from_secs(1)),
+        }
+    }
+}
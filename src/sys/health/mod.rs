@@ -1,5 +1,7 @@
 pub mod aggregator;
 pub mod components;
+mod config;
 pub mod models;
 
-pub use aggregator::aggregate_health;
+pub use aggregator::{aggregate_health, check_health, spawn_health_checks};
+pub use models::HealthConfig;
@@ -1,5 +1,19 @@
+use crate::sys::events::Event;
 use serde::Serialize;
-#[derive(Serialize, Clone, Debug)]
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// How often health checks run in the background to publish
+    /// `HealthChanged`, besides every request to `/health`.
+    pub check_interval: Duration,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum HealthStatus {
     Healthy,
@@ -28,3 +42,49 @@ pub trait HealthCheck: Send + Sync {
     /// Performs the health check and returns component health status
     async fn check(&self) -> ComponentHealth;
 }
+
+/// Published on the event bus when a health check reports a status other
+/// than the one it reported last.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthChanged {
+    pub component: String,
+    /// `None` the first time the component is checked.
+    pub previous: Option<HealthStatus>,
+    pub current: HealthStatus,
+    pub message: Option<String>,
+}
+
+impl Event for HealthChanged {
+    const NAME: &'static str = "health.changed";
+}
+
+/// The last status reported by each component, to detect changes.
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    statuses: Mutex<HashMap<String, HealthStatus>>,
+}
+
+impl HealthTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the reported statuses and returns the components whose
+    /// status changed.
+    pub fn observe(&self, results: &[ComponentHealth]) -> Vec<HealthChanged> {
+        let mut statuses = self.statuses.lock().unwrap_or_else(PoisonError::into_inner);
+        results
+            .iter()
+            .filter_map(|result| {
+                let previous = statuses.insert(result.name.clone(), result.status);
+                (previous != Some(result.status)).then(|| HealthChanged {
+                    component: result.name.clone(),
+                    previous,
+                    current: result.status,
+                    message: result.message.clone(),
+                })
+            })
+            .collect()
+    }
+}
@@ -2,48 +2,72 @@ use crate::{
     AppError,
     dbs::{
         connector::connect,
+        error::DatabaseError,
+        migrations::Migrations,
         models::{DbConfig, DbConnection},
     },
     init_tracing,
     sys::{
-        config::{server::ServerConfig, state::AppState},
+        cache::{Cache, CacheStore, CacheStoreKind, MemoryCache, SurrealCache},
+        config::{
+            AppConfig, ConfigHandle, spawn_config_watcher, spawn_reload_events, state::AppState,
+        },
         env,
-        health::components::create_health_checkers,
+        events::EventBus,
+        health::{components::create_health_checkers, models::HealthTracker, spawn_health_checks},
+        jobs::JobQueue,
+        log::{LogConfig, spawn_log_reloader},
+        metrics::{MetricsRegistry, track_requests},
+        middleware::{
+            access_log::access_log,
+            catch_panic::{catch_panic, install_panic_hook},
+            conditional::conditional,
+            http::http_layers,
+            idempotency::{IdempotencyStore, idempotency},
+            load_shed::{LoadShedder, load_shed},
+            rate_limit::{
+                MemoryStore, RateLimitStore, RateLimitStoreKind, SurrealStore, rate_limit,
+            },
+        },
+        outbox::{FileSink, LocalSink, Outbox, SinkKind, StdoutSink, WebhookSink},
+        scheduler::Scheduler,
+        server::{Listeners, ServerListener, Shutdown, spawn_signal_listener},
+        tenancy::Tenants,
+        tls::TlsListener,
+        webhooks::{DeliverWebhook, Webhooks, release_webhook_nonces},
     },
 };
-use axum::Router;
-use std::sync::Arc;
-use tokio::time::{Duration, timeout};
-use tower_http::trace::TraceLayer;
-use tracing::{error, info};
+use axum::{Router, middleware::from_fn_with_state};
+use std::{collections::BTreeMap, path::Path, sync::Arc, time::Duration};
+use tokio::time::timeout;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+use tracing::{error, info, warn};
 
 /// Loads and establishes a database connection.
 ///
 /// # Errors
 ///
-/// - `AppError::Database` if configuration is invalid or connection/authentication fails
+/// - `AppError::Database` if the connection or authentication fails
 /// - `AppError::ServerError` if the connection times out
-pub async fn load_database() -> Result<DbConnection, AppError> {
-    info!("Loading database configuration from environment");
-    let config = DbConfig::from_env()?;
-
+pub async fn load_database(config: &DbConfig) -> Result<DbConnection, AppError> {
     info!(
         endpoint = %config.endpoint,
         namespace = %config.namespace,
         database = %config.database,
         "Attempting to connect to the database"
     );
-    let timeout_secs = env::get_parsed_or_default("DB_CONNECTION_TIMEOUT", 10);
-    let connection = timeout(Duration::from_secs(timeout_secs), connect(&config))
+    let timeout_ms = config.connection_timeout.as_millis();
+    let connection = timeout(config.connection_timeout, connect(config))
         .await
         .map_err(|_| {
             error!(
-                "Failed to connect to the database: connection timed out after {} seconds",
-                timeout_secs
+                "Failed to connect to the database: connection timed out after {}ms",
+                timeout_ms
             );
-            AppError::ServerError(format!(
-                "Database connection timeout after {timeout_secs} seconds"
-            ))
+            AppError::ServerError(format!("Database connection timeout after {timeout_ms}ms"))
         })??;
 
     info!("Successfully connected to the database");
@@ -54,28 +78,471 @@ pub async fn load_database() -> Result<DbConnection, AppError> {
 /// Loads environment variables from .env file
 #[must_use]
 pub fn load_env() -> bool {
-    dotenvy::dotenv().is_ok()
+    env::load_dotenv()
 }
 
-/// Creates a TCP listener bound to the specified address.
+/// Loads and validates the layered application configuration.
+///
+/// Tracing is initialized from the loaded log settings, or from defaults when
+/// configuration is invalid so that every issue can be reported.
 ///
 /// # Errors
 ///
-/// Returns `AppError::BindError` if the server fails to bind to the address,
-/// for example if the port is already in use.
-pub async fn load_listener(addr: &str) -> Result<tokio::net::TcpListener, AppError> {
-    let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
-        error!(error = %e, "Failed to bind server to {}", addr);
+/// Returns `AppError::Config` listing every missing or invalid value.
+pub fn load_config() -> Result<AppConfig, AppError> {
+    match AppConfig::load() {
+        Ok(config) => {
+            init_tracing(&config.log);
+            log_config_report(&config);
+            Ok(config)
+        }
+        Err(e) => {
+            init_tracing(&LogConfig::default());
+            for issue in &e.issues {
+                error!(key = %issue.key, "Invalid configuration: {}", issue.message);
+            }
+            Err(e.into())
+        }
+    }
+}
+
+/// Logs the effective configuration and any unknown settings as one event.
+fn log_config_report(config: &AppConfig) {
+    let settings = serde_json::to_string(&config.report.settings).unwrap_or_default();
+    let file = config.file.as_ref().map(|p| p.display().to_string());
+
+    if config.report.unknown.is_empty() {
+        info!(
+            file = file.as_deref(),
+            env_prefix = env::prefix().as_deref(),
+            settings = %settings,
+            "Effective configuration"
+        );
+    } else {
+        let unknown = serde_json::to_string(&config.report.unknown).unwrap_or_default();
+        warn!(
+            file = file.as_deref(),
+            env_prefix = env::prefix().as_deref(),
+            settings = %settings,
+            unknown = %unknown,
+            "Effective configuration has unknown settings, check for typos"
+        );
+    }
+}
+
+/// Binds every configured listener: the public TCP address (with TLS when
+/// enabled), the optional Unix socket and the optional admin listener.
+///
+/// # Errors
+///
+/// - `AppError::BindError` if a listener fails to bind, for example if the
+///   port is already in use
+/// - `AppError::TlsError` if the certificate or key cannot be loaded
+pub async fn load_listeners(config: &AppConfig) -> Result<Listeners, AppError> {
+    let mut public = vec![load_listener(config).await?];
+    #[cfg(unix)]
+    if let Some(path) = &config.server.unix_socket {
+        public.push(load_unix_listener(path)?);
+    }
+
+    let admin = match config.server.admin_address() {
+        Some(addr) => {
+            let listener = bind_tcp(&addr).await?;
+            info!(
+                "Admin routes (/health, /metrics, /admin/*) are served on http://{}",
+                addr
+            );
+            Some(ServerListener::Plain(listener))
+        }
+        None => None,
+    };
+
+    Ok(Listeners { public, admin })
+}
+
+/// Creates the public listener for the configured address, terminating TLS
+/// on it when enabled.
+///
+/// # Errors
+///
+/// - `AppError::BindError` if the server fails to bind to the address
+/// - `AppError::TlsError` if the certificate or key cannot be loaded
+pub async fn load_listener(config: &AppConfig) -> Result<ServerListener, AppError> {
+    let addr = config.server.address();
+    let tcp = bind_tcp(&addr).await?;
+
+    let listener = if config.tls.enabled {
+        let listener = TlsListener::new(tcp, &config.tls).inspect_err(|e| {
+            error!(error = %e, "Failed to configure TLS");
+        })?;
+        info!(
+            cert = %config.tls.cert_path.display(),
+            client_ca = config.tls.client_ca_path.as_ref().map(|p| p.display().to_string()),
+            "TLS enabled"
+        );
+        ServerListener::Tls(listener)
+    } else {
+        ServerListener::Plain(tcp)
+    };
+
+    info!(
+        "Server is listening for requests on {}://{}",
+        listener.scheme(),
+        addr
+    );
+
+    Ok(listener)
+}
+
+/// Binds a Unix domain socket, replacing a stale socket file left behind by
+/// a previous run.
+///
+/// # Errors
+///
+/// Returns `AppError::BindError` if the socket cannot be bound.
+#[cfg(unix)]
+pub fn load_unix_listener(path: &Path) -> Result<ServerListener, AppError> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path).map_err(|e| {
+        error!(error = %e, "Failed to bind Unix socket {}", path.display());
         AppError::BindError(e.to_string())
     })?;
 
-    info!("Server is listening for requests on http://{}", addr);
+    info!(
+        "Server is listening for requests on unix:{}",
+        path.display()
+    );
 
-    Ok(listener)
+    Ok(ServerListener::Unix(listener))
+}
+
+async fn bind_tcp(addr: &str) -> Result<tokio::net::TcpListener, AppError> {
+    tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+        error!(error = %e, "Failed to bind server to {}", addr);
+        AppError::BindError(e.to_string())
+    })
+}
+
+/// Returns the named database selected by the setting `key`, or the primary
+/// database when no name is set.
+fn select_database<'a>(
+    key: &str,
+    name: Option<&str>,
+    primary: &'a DbConnection,
+    databases: &'a BTreeMap<String, DbConnection>,
+) -> Result<&'a DbConnection, DatabaseError> {
+    match name {
+        Some(name) => databases.get(name).ok_or_else(|| {
+            DatabaseError::ConfigError(format!("{key} '{name}' is not a configured database"))
+        }),
+        None => Ok(primary),
+    }
+}
+
+/// Creates the rate limit store selected by `RATE_LIMIT_STORE`.
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if `RATE_LIMIT_DATABASE` names a database
+/// that is not configured.
+pub fn load_rate_limiter(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    shutdown: &Shutdown,
+) -> Result<Arc<dyn RateLimitStore>, AppError> {
+    let config = &config.rate_limit;
+    match config.store {
+        RateLimitStoreKind::Memory => Ok(Arc::new(MemoryStore::new())),
+        RateLimitStoreKind::SurrealDb => {
+            let db = select_database(
+                "RATE_LIMIT_DATABASE",
+                config.database.as_deref(),
+                primary,
+                databases,
+            )?;
+            info!(
+                database = config.database.as_deref().unwrap_or("primary"),
+                "Rate limit state is shared through SurrealDB"
+            );
+            Ok(Arc::new(SurrealStore::new(
+                db.clone(),
+                Duration::from_secs(60),
+                shutdown.clone(),
+            )))
+        }
+    }
+}
+
+/// Creates the store for `Idempotency-Key` records.
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if `IDEMPOTENCY_DATABASE` names a database
+/// that is not configured.
+pub fn load_idempotency_store(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    shutdown: &Shutdown,
+) -> Result<IdempotencyStore, AppError> {
+    let db = select_database(
+        "IDEMPOTENCY_DATABASE",
+        config.idempotency.database.as_deref(),
+        primary,
+        databases,
+    )?;
+    Ok(IdempotencyStore::new(
+        db.clone(),
+        Duration::from_secs(600),
+        shutdown.clone(),
+    ))
+}
+
+/// Creates the cache selected by `CACHE_STORE`.
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if `CACHE_DATABASE` names a database that is
+/// not configured.
+pub fn load_cache(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    shutdown: &Shutdown,
+    metrics: &MetricsRegistry,
+) -> Result<Cache, AppError> {
+    let cache = &config.cache;
+    let store: Arc<dyn CacheStore> = match cache.store {
+        CacheStoreKind::Memory => {
+            Arc::new(MemoryCache::new(cache.max_entries, cache.max_size, metrics))
+        }
+        CacheStoreKind::SurrealDb => {
+            let db = select_database(
+                "CACHE_DATABASE",
+                cache.database.as_deref(),
+                primary,
+                databases,
+            )?;
+            info!(
+                database = cache.database.as_deref().unwrap_or("primary"),
+                "Cache is shared through SurrealDB"
+            );
+            Arc::new(SurrealCache::new(
+                db.clone(),
+                Duration::from_secs(60),
+                shutdown.clone(),
+            ))
+        }
+    };
+    Ok(Cache::new(store, cache.clone(), metrics))
+}
+
+/// Creates the job queue. Workers are started separately once the
+/// application state exists.
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if `JOBS_DATABASE` names a database that is
+/// not configured.
+pub fn load_job_queue(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    shutdown: &Shutdown,
+    metrics: &Arc<MetricsRegistry>,
+) -> Result<JobQueue, AppError> {
+    let db = select_database(
+        "JOBS_DATABASE",
+        config.jobs.database.as_deref(),
+        primary,
+        databases,
+    )?;
+    Ok(JobQueue::new(
+        db.clone(),
+        config.jobs.clone(),
+        shutdown.clone(),
+        metrics.clone(),
+    ))
+}
+
+/// Creates the task scheduler. It is started separately once the
+/// application state exists.
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if `SCHEDULER_DATABASE` names a database
+/// that is not configured.
+pub fn load_scheduler(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    shutdown: &Shutdown,
+    metrics: &Arc<MetricsRegistry>,
+) -> Result<Scheduler, AppError> {
+    let db = select_database(
+        "SCHEDULER_DATABASE",
+        config.scheduler.database.as_deref(),
+        primary,
+        databases,
+    )?;
+    Ok(Scheduler::new(
+        db.clone(),
+        config.scheduler.clone(),
+        shutdown.clone(),
+        metrics.clone(),
+    ))
+}
+
+/// Creates the outbound webhook client and registers its delivery job.
+///
+/// # Errors
+///
+/// - `AppError::Database` if `WEBHOOKS_DATABASE` names a database that is not
+///   configured
+/// - `AppError::ServerError` if the HTTP client cannot be built
+pub fn load_webhooks(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    jobs: &JobQueue,
+    shutdown: &Shutdown,
+    metrics: &Arc<MetricsRegistry>,
+) -> Result<Webhooks, AppError> {
+    let db = select_database(
+        "WEBHOOKS_DATABASE",
+        config.webhooks.database.as_deref(),
+        primary,
+        databases,
+    )?;
+    if config.webhooks.enabled {
+        jobs.register::<DeliverWebhook>();
+    }
+    Webhooks::new(
+        db.clone(),
+        config.webhooks.clone(),
+        jobs.clone(),
+        shutdown.clone(),
+        metrics.clone(),
+    )
+}
+
+/// Creates the outbox on the primary database, where repositories record
+/// their events, with the sinks selected by `OUTBOX_SINKS`. The relay is
+/// started separately once the application state exists.
+#[must_use]
+pub fn load_outbox(
+    config: &AppConfig,
+    primary: &DbConnection,
+    webhooks: &Webhooks,
+    events: &EventBus,
+    shutdown: &Shutdown,
+    metrics: &Arc<MetricsRegistry>,
+) -> Outbox {
+    let config = &config.outbox;
+    let outbox = Outbox::new(
+        primary.clone(),
+        config.clone(),
+        shutdown.clone(),
+        metrics.clone(),
+    );
+    for sink in &config.sinks {
+        match sink {
+            SinkKind::Webhooks => outbox.add_sink(WebhookSink::new(
+                webhooks.clone(),
+                config.webhook_tenant.clone(),
+            )),
+            SinkKind::Local => outbox.add_sink(LocalSink::new(events.clone())),
+            SinkKind::Stdout => outbox.add_sink(StdoutSink::new()),
+            SinkKind::File => {
+                if let Some(path) = &config.file {
+                    outbox.add_sink(FileSink::new(path));
+                }
+            }
+        }
+    }
+    outbox
+}
+
+/// Creates the tenant registry with the migrations applied to every tenant.
+/// Tenant connections are opened on first use.
+///
+/// # Errors
+///
+/// Returns `AppError::Database` if `TENANCY_DATABASE` names a database that
+/// is not configured, or the migrations cannot be read.
+pub fn load_tenants(
+    config: &AppConfig,
+    primary: &DbConnection,
+    databases: &BTreeMap<String, DbConnection>,
+    events: &EventBus,
+    metrics: &Arc<MetricsRegistry>,
+) -> Result<Tenants, AppError> {
+    let tenancy = &config.tenancy;
+    let db = select_database(
+        "TENANCY_DATABASE",
+        tenancy.database.as_deref(),
+        primary,
+        databases,
+    )?;
+    let migrations = if tenancy.enabled {
+        let migrations = Migrations::load(&tenancy.migrations_dir)?;
+        info!(
+            mode = ?tenancy.mode,
+            migrations = migrations.all().len(),
+            "Multi-tenancy is enabled"
+        );
+        migrations
+    } else {
+        Migrations::default()
+    };
+    Ok(Tenants::new(
+        db.clone(),
+        tenancy.clone(),
+        config.database.clone(),
+        migrations,
+        events.clone(),
+        metrics.clone(),
+    ))
 }
 
 pub fn load_router() -> Router<Arc<AppState>> {
-    Router::new().layer(TraceLayer::new_for_http())
+    Router::new()
+}
+
+/// Wraps every route registered on `router` with the HTTP middleware stack.
+///
+/// Layers only apply to routes that already exist, so this must be called
+/// after all routes have been added. The stack is built from the startup
+/// configuration; apart from allowed CORS origins, changes to HTTP settings
+/// need a restart.
+pub fn load_middleware(
+    router: Router<Arc<AppState>>,
+    state: &Arc<AppState>,
+) -> Router<Arc<AppState>> {
+    // Panics are caught next to the handlers so every other layer still sees
+    // a response, including the release of nonces of failed webhooks. ETags are computed before compression changes the body.
+    // Idempotency replays run after overload protection so they count
+    // against limits. Rate limiting runs before load shedding so rejected
+    // clients never hold or wait for a slot. Overload protection sits inside
+    // CORS so rejections carry CORS headers.
+    let router = router
+        .layer(from_fn_with_state(state.clone(), catch_panic))
+        .layer(from_fn_with_state(state.clone(), release_webhook_nonces))
+        .layer(from_fn_with_state(state.clone(), conditional))
+        .layer(from_fn_with_state(state.clone(), idempotency))
+        .layer(from_fn_with_state(state.clone(), load_shed))
+        .layer(from_fn_with_state(state.clone(), rate_limit));
+    http_layers(router, &state.config)
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(from_fn_with_state(state.clone(), track_requests))
+        .layer(from_fn_with_state(state.clone(), access_log))
+        .layer(TraceLayer::new_for_http())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
 }
 
 /// Initializes the application.
@@ -85,20 +552,17 @@ pub fn load_router() -> Router<Arc<AppState>> {
 /// - `AppError::Database` for database configuration or connection failures
 /// - `AppError::ServerError` for connection timeouts
 /// - `AppError::BindError` if the server fails to bind to its address
-/// - `AppError::Environment` if required environment variables are missing
-pub async fn initialize() -> Result<
-    (
-        Router<Arc<AppState>>,
-        Arc<AppState>,
-        tokio::net::TcpListener,
-    ),
-    AppError,
-> {
+/// - `AppError::TlsError` if TLS is enabled but the certificates are invalid
+/// - `AppError::Config` if the configuration is missing values or invalid
+pub async fn initialize() -> Result<(Router<Arc<AppState>>, Arc<AppState>, Listeners), AppError> {
     // Load environment variables
     let env_loaded = load_env();
 
-    // Initialize tracing
-    init_tracing();
+    // Report panics in request handlers through tracing
+    install_panic_hook();
+
+    // Load configuration and initialize tracing
+    let config = load_config()?;
     if env_loaded {
         info!("Loaded .env file");
     } else {
@@ -108,31 +572,119 @@ pub async fn initialize() -> Result<
     info!(version = env!("CARGO_PKG_VERSION"), "Application");
     info!("Application is starting");
 
-    // Load server configuration
-    let server_config = ServerConfig::from_env();
     info!(
-        host = %server_config.host,
-        port = server_config.port,
+        host = %config.server.host,
+        port = config.server.port,
         "Server configuration loaded"
     );
 
-    // Load database connection
-    let connection = load_database().await?;
+    // Load database connections
+    let connection = load_database(&config.database).await?;
+    let mut databases = BTreeMap::new();
+    for (name, db_config) in &config.databases {
+        info!(name = %name, "Loading named database");
+        databases.insert(name.clone(), load_database(db_config).await?);
+    }
+
+    // Stop serving and cancel background work on Ctrl+C or SIGTERM
+    let shutdown = Shutdown::new();
+    spawn_signal_listener(shutdown.clone());
+
+    // Create overload protection
+    let metrics = Arc::new(MetricsRegistry::new());
+    let rate_limiter = load_rate_limiter(&config, &connection, &databases, &shutdown)?;
+    let load_shedder = LoadShedder::new(config.load_shed.clone(), metrics.clone());
+
+    // Let modules publish and subscribe to each other's events
+    let events = EventBus::new(config.events.clone(), shutdown.clone(), metrics.clone());
+
+    // Create request stores, the cache and background work
+    let idempotency = load_idempotency_store(&config, &connection, &databases, &shutdown)?;
+    let cache = load_cache(&config, &connection, &databases, &shutdown, &metrics)?;
+    let jobs = load_job_queue(&config, &connection, &databases, &shutdown, &metrics)?;
+    let scheduler = load_scheduler(&config, &connection, &databases, &shutdown, &metrics)?;
+    let webhooks = load_webhooks(&config, &connection, &databases, &jobs, &shutdown, &metrics)?;
+    let outbox = load_outbox(
+        &config,
+        &connection,
+        &webhooks,
+        &events,
+        &shutdown,
+        &metrics,
+    );
+    let tenants = load_tenants(&config, &connection, &databases, &events, &metrics)?;
 
     // Create health checkers
-    let health_checkers = Arc::new(create_health_checkers(connection.clone()));
+    let mut health_checkers = create_health_checkers(
+        connection.clone(),
+        &databases,
+        &load_shedder,
+        &cache,
+        &jobs,
+        &outbox,
+        &config,
+    );
+    if tenants.is_enabled() {
+        health_checkers.push(Box::new(tenants.clone()));
+    }
+    let health_checkers = Arc::new(health_checkers);
+
+    // Watch for configuration changes
+    let config = Arc::new(ConfigHandle::new(config));
+    spawn_log_reloader(config.subscribe());
+    spawn_config_watcher(config.clone());
+    spawn_reload_events(&config, events.clone());
 
     // Create application state
     let state = Arc::new(AppState {
         db_connection: connection,
+        databases,
         health_checkers,
+        health_tracker: Arc::new(HealthTracker::new()),
+        config,
+        metrics,
+        rate_limiter,
+        load_shedder,
+        idempotency,
+        cache,
+        jobs,
+        scheduler,
+        webhooks,
+        outbox,
+        events,
+        tenants,
+        shutdown,
     });
 
+    // Start job workers
+    if state.config.load().jobs.enabled {
+        state.jobs.start(&state);
+    } else {
+        info!("Job workers are disabled");
+    }
+
+    // Check health in the background to publish status changes
+    spawn_health_checks(&state);
+
+    // Start the scheduler; tasks registered later are picked up as they come
+    if state.config.load().scheduler.enabled {
+        state.scheduler.start(&state);
+    } else {
+        info!("Scheduled tasks are disabled");
+    }
+
+    // Start relaying outbox events
+    if state.config.load().outbox.enabled {
+        state.outbox.start();
+    } else {
+        info!("Outbox relay is disabled");
+    }
+
     // Load router with state
     let router = load_router();
 
-    // Load listener
-    let listener = load_listener(&server_config.address()).await?;
+    // Load listeners
+    let listeners = load_listeners(&state.config.load()).await?;
 
-    Ok((router, state, listener))
+    Ok((router, state, listeners))
 }
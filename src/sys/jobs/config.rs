@@ -0,0 +1,38 @@
+use super::models::JobsConfig;
+use crate::sys::config::ConfigReader;
+use std::time::Duration;
+
+impl JobsConfig {
+    /// Creates a `JobsConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let concurrency = config.parsed("JOBS_CONCURRENCY", 4);
+        if concurrency == 0 {
+            config.invalid("JOBS_CONCURRENCY", "must be greater than zero");
+        }
+        let visibility_timeout =
+            config.duration("JOBS_VISIBILITY_TIMEOUT", Duration::from_secs(30));
+        if visibility_timeout < Duration::from_secs(1) {
+            config.invalid("JOBS_VISIBILITY_TIMEOUT", "must be at least 1s");
+        }
+        let max_attempts = config.parsed("JOBS_MAX_ATTEMPTS", 5);
+        if max_attempts == 0 {
+            config.invalid("JOBS_MAX_ATTEMPTS", "must be greater than zero");
+        }
+
+        Self {
+            enabled: config.bool("JOBS_ENABLED", true),
+            concurrency,
+            poll_interval: config.duration("JOBS_POLL_INTERVAL", Duration::from_secs(1)),
+            visibility_timeout,
+            max_attempts,
+            backoff_base: config.duration("JOBS_BACKOFF_BASE", Duration::from_secs(1)),
+            backoff_max: config.duration("JOBS_BACKOFF_MAX", Duration::from_secs(600)),
+            stuck_threshold: config.duration("JOBS_STUCK_THRESHOLD", Duration::from_secs(300)),
+            shutdown_timeout: config.duration("JOBS_SHUTDOWN_TIMEOUT", Duration::from_secs(30)),
+            database: config
+                .optional("JOBS_DATABASE")
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::sys::config::state::AppState;
+use futures::future::BoxFuture;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::{error::Error, sync::Arc};
+
+/// Outcome of a job run. Errors are retried with backoff.
+pub type JobResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// A unit of deferred work, stored as JSON until a worker runs it.
+///
+/// Jobs must be registered with [`JobQueue::register`](super::JobQueue::register)
+/// on every instance that should run them.
+#[async_trait::async_trait]
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Name identifying the job type in the queue. Must stay stable while
+    /// jobs of this type are queued.
+    const KIND: &'static str;
+
+    /// Attempts before the job is dead-lettered, the configured default
+    /// when `None`.
+    const MAX_ATTEMPTS: Option<u32> = None;
+
+    async fn run(self, context: JobContext) -> JobResult;
+}
+
+/// What a running job knows about itself and the application.
+#[derive(Clone)]
+pub struct JobContext {
+    pub state: Arc<AppState>,
+    pub id: String,
+    /// 1 for the first run.
+    pub attempt: u32,
+}
+
+pub(super) type Handler =
+    Arc<dyn Fn(Value, JobContext) -> BoxFuture<'static, JobResult> + Send + Sync>;
+
+pub(super) fn handler<J: Job>() -> Handler {
+    Arc::new(|payload, context| {
+        Box::pin(async move {
+            let job: J = serde_json::from_value(payload)?;
+            job.run(context).await
+        })
+    })
+}
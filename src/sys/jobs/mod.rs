@@ -0,0 +1,8 @@
+mod config;
+mod job;
+mod models;
+mod queue;
+mod worker;
+pub use job::{Job, JobContext, JobResult};
+pub use models::{DeadJob, EnqueueOptions, JobRecord, JobsConfig, QueueStats};
+pub use queue::JobQueue;
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct JobsConfig {
+    /// Run workers in this instance. Jobs can be enqueued either way.
+    pub enabled: bool,
+    /// Jobs run at once by this instance.
+    pub concurrency: usize,
+    /// How often idle workers look for due jobs.
+    pub poll_interval: Duration,
+    /// How long a claimed job stays invisible to other workers. Running jobs
+    /// extend it; jobs of a crashed worker are retried once it expires.
+    pub visibility_timeout: Duration,
+    /// Attempts before a failing job is moved to the dead-letter table.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further attempt.
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    /// Due jobs waiting longer than this make the health check degraded.
+    pub stuck_threshold: Duration,
+    /// How long running jobs get to finish after shutdown starts before
+    /// they are aborted and left for another worker.
+    pub shutdown_timeout: Duration,
+    /// Named database for the queue, the primary one when `None`.
+    pub database: Option<String>,
+}
+
+/// A queued job as stored in the `job` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    pub kind: String,
+    /// Missing for jobs serialized as `null`, such as unit structs.
+    #[serde(default)]
+    pub payload: Value,
+    /// Higher priorities run first.
+    pub priority: i32,
+    /// Earliest run time, in milliseconds since the Unix epoch.
+    pub run_at: i64,
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Identifies the current claim, so only its holder can finish the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_token: Option<String>,
+    /// When the current claim expires, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_until: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+/// A job that failed every attempt, as stored in the `job_dead` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadJob {
+    pub id: String,
+    pub kind: String,
+    #[serde(default)]
+    pub payload: Value,
+    pub priority: i32,
+    pub attempts: u32,
+    /// Attempts the job gets again when it is retried.
+    pub max_attempts: u32,
+    pub error: String,
+    pub created_at: i64,
+    pub failed_at: i64,
+}
+
+/// How a job is scheduled.
+#[derive(Debug, Clone, Default)]
+pub struct EnqueueOptions {
+    /// Run no earlier than this long after enqueueing.
+    pub delay: Duration,
+    /// Higher priorities run first among due jobs.
+    pub priority: i32,
+    /// Overrides the job's and the configured attempt limit.
+    pub max_attempts: Option<u32>,
+}
+
+impl EnqueueOptions {
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    #[must_use]
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+}
+
+/// Job counts reported by the health check.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueueStats {
+    /// Due jobs waiting for a worker.
+    pub ready: u64,
+    /// Jobs scheduled for later, including retries.
+    pub delayed: u64,
+    pub running: u64,
+    /// Claimed jobs whose lease expired without the job finishing.
+    pub stuck: u64,
+    pub dead: u64,
+    /// How long the oldest due job has been waiting.
+    pub oldest_wait: Duration,
+}
@@ -0,0 +1,518 @@
+use super::{
+    job::{Handler, Job, handler},
+    models::{DeadJob, EnqueueOptions, JobRecord, JobsConfig, QueueStats},
+    worker::run_worker,
+};
+use crate::{
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::{
+        config::state::AppState,
+        health::models::{ComponentHealth, HealthCheck, HealthStatus},
+        metrics::MetricsRegistry,
+        server::Shutdown,
+    },
+};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::Duration,
+};
+use tokio::{sync::Notify, task::JoinSet};
+use tracing::{info, warn};
+
+/// Fields selected for every job: its own fields plus the record key as `id`.
+const FIELDS: &str = "*, meta::id(id) AS id";
+
+/// Due jobs fetched per claim attempt, so workers racing for the same jobs
+/// still find one.
+const CLAIM_CANDIDATES: u64 = 16;
+
+#[derive(Debug, Deserialize)]
+struct IdRow {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountRow {
+    count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OldestRow {
+    oldest: Option<i64>,
+}
+
+/// Durable queue of jobs in the `job` table, with failed jobs moved to
+/// `job_dead`.
+///
+/// Workers claim a job by setting a lease; a job whose worker dies becomes
+/// visible again when the lease expires. Failed jobs are retried with
+/// exponential backoff until they run out of attempts.
+#[derive(Clone)]
+pub struct JobQueue {
+    pub(super) inner: Arc<QueueInner>,
+}
+
+pub(super) struct QueueInner {
+    db: DbConnection,
+    pub(super) config: JobsConfig,
+    handlers: RwLock<HashMap<String, Handler>>,
+    /// Wakes idle workers when a job is enqueued for immediate execution.
+    pub(super) notify: Notify,
+    pub(super) shutdown: Shutdown,
+    workers: Mutex<JoinSet<()>>,
+    pub(super) metrics: Arc<MetricsRegistry>,
+}
+
+impl JobQueue {
+    #[must_use]
+    pub fn new(
+        db: DbConnection,
+        config: JobsConfig,
+        shutdown: Shutdown,
+        metrics: Arc<MetricsRegistry>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(QueueInner {
+                db,
+                config,
+                handlers: RwLock::new(HashMap::new()),
+                notify: Notify::new(),
+                shutdown,
+                workers: Mutex::new(JoinSet::new()),
+                metrics,
+            }),
+        }
+    }
+
+    /// Lets the workers of this instance run jobs of type `J`.
+    pub fn register<J: Job>(&self) {
+        self.inner
+            .handlers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(J::KIND.to_string(), handler::<J>());
+        info!(kind = J::KIND, "Registered job");
+    }
+
+    /// Starts the configured number of workers, which run jobs until
+    /// shutdown.
+    pub fn start(&self, state: &Arc<AppState>) {
+        let mut workers = self
+            .inner
+            .workers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for worker in 0..self.inner.config.concurrency {
+            workers.spawn(run_worker(self.clone(), state.clone(), worker));
+        }
+        info!(
+            concurrency = self.inner.config.concurrency,
+            "Job workers started"
+        );
+    }
+
+    /// Waits for workers to finish or abort their running jobs. Call once
+    /// the shutdown has been triggered.
+    pub async fn stopped(&self) {
+        let mut workers = std::mem::take(
+            &mut *self
+                .inner
+                .workers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        if !workers.is_empty() {
+            info!(workers = workers.len(), "Waiting for job workers to stop");
+        }
+        while workers.join_next().await.is_some() {}
+    }
+
+    /// Adds a job to the queue and returns its id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the job cannot be serialized or
+    /// stored.
+    pub async fn enqueue<J: Job>(
+        &self,
+        job: &J,
+        options: EnqueueOptions,
+    ) -> Result<String, DatabaseError> {
+        let payload = serde_json::to_value(job)
+            .map_err(|e| DatabaseError::QueryError(format!("Job cannot be stored: {e}")))?;
+        let now = now_millis();
+        let max_attempts = options
+            .max_attempts
+            .or(J::MAX_ATTEMPTS)
+            .unwrap_or(self.inner.config.max_attempts)
+            .max(1);
+        let content = json!({
+            "kind": J::KIND,
+            "payload": payload,
+            "priority": options.priority,
+            "run_at": now.saturating_add(millis(options.delay)),
+            "attempts": 0,
+            "max_attempts": max_attempts,
+            "created_at": now,
+        });
+
+        let created: Option<IdRow> = self
+            .inner
+            .db
+            .query("CREATE job CONTENT $content RETURN meta::id(id) AS id")
+            .bind(("content", content))
+            .await?
+            .take(0)?;
+        let id = created
+            .ok_or_else(|| DatabaseError::QueryError("Failed to enqueue job".to_string()))?
+            .id;
+
+        if options.delay.is_zero() {
+            self.inner.notify.notify_one();
+        }
+        Ok(id)
+    }
+
+    /// Returns up to `limit` dead-lettered jobs, most recent failures first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn dead_letters(
+        &self,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<DeadJob>, DatabaseError> {
+        Ok(self
+            .inner
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM job_dead ORDER BY failed_at DESC LIMIT $limit START $start"
+            ))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+
+    /// Moves a dead-lettered job back to the queue with fresh attempts.
+    /// Returns false if no such job exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the job cannot be moved.
+    pub async fn retry_dead(&self, id: &str) -> Result<bool, DatabaseError> {
+        let moved: Option<IdRow> = self
+            .inner
+            .db
+            .query(
+                "BEGIN TRANSACTION;
+                 LET $dead = (DELETE type::thing('job_dead', $id) RETURN BEFORE)[0];
+                 IF $dead {
+                     CREATE type::thing('job', $id) CONTENT {
+                         kind: $dead.kind,
+                         payload: $dead.payload,
+                         priority: $dead.priority,
+                         run_at: $now,
+                         attempts: 0,
+                         max_attempts: $dead.max_attempts,
+                         created_at: $dead.created_at
+                     } RETURN NONE
+                 };
+                 RETURN IF $dead { { id: $id } } ELSE { NONE };
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("id", id.to_string()))
+            .bind(("now", now_millis()))
+            .await?
+            .take(0)?;
+        if moved.is_some() {
+            self.inner.notify.notify_one();
+        }
+        Ok(moved.is_some())
+    }
+
+    /// Counts jobs by state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the tables cannot be queried.
+    pub async fn stats(&self) -> Result<QueueStats, DatabaseError> {
+        let now = now_millis();
+        let mut response = self
+            .inner
+            .db
+            .query(
+                "SELECT count() FROM job WHERE run_at <= $now AND lease_until IS NONE GROUP ALL;
+                 SELECT count() FROM job WHERE run_at > $now AND lease_until IS NONE GROUP ALL;
+                 SELECT count() FROM job WHERE lease_until >= $now GROUP ALL;
+                 SELECT count() FROM job WHERE lease_until != NONE AND lease_until < $now GROUP ALL;
+                 SELECT count() FROM job_dead GROUP ALL;
+                 SELECT math::min(run_at) AS oldest FROM job
+                     WHERE run_at <= $now AND lease_until IS NONE GROUP ALL;",
+            )
+            .bind(("now", now))
+            .await?;
+
+        let mut count = |index: usize| -> Result<u64, DatabaseError> {
+            let row: Option<CountRow> = response.take(index)?;
+            Ok(row.map_or(0, |row| row.count))
+        };
+        let mut stats = QueueStats {
+            ready: count(0)?,
+            delayed: count(1)?,
+            running: count(2)?,
+            stuck: count(3)?,
+            dead: count(4)?,
+            oldest_wait: Duration::ZERO,
+        };
+        let oldest: Option<OldestRow> = response.take(5)?;
+        if let Some(oldest) = oldest.and_then(|row| row.oldest) {
+            stats.oldest_wait =
+                Duration::from_millis(u64::try_from(now - oldest).unwrap_or_default());
+        }
+        Ok(stats)
+    }
+
+    /// Claims the highest priority due job of a registered kind.
+    pub(super) async fn claim(&self) -> Result<Option<(JobRecord, Handler)>, DatabaseError> {
+        let handlers = self
+            .inner
+            .handlers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if handlers.is_empty() {
+            return Ok(None);
+        }
+        let kinds: Vec<String> = handlers.keys().cloned().collect();
+
+        let now = now_millis();
+        let candidates: Vec<IdRow> = self
+            .inner
+            .db
+            .query(
+                "SELECT meta::id(id) AS id, priority, run_at FROM job \
+                 WHERE kind IN $kinds AND run_at <= $now \
+                 AND (lease_until IS NONE OR lease_until < $now) \
+                 ORDER BY priority DESC, run_at ASC LIMIT $limit",
+            )
+            .bind(("kinds", kinds))
+            .bind(("now", now))
+            .bind(("limit", CLAIM_CANDIDATES))
+            .await?
+            .take(0)?;
+
+        for candidate in candidates {
+            // Only one worker can move the lease forward from an expired state
+            let token = lease_token();
+            let claimed: Option<JobRecord> = self
+                .inner
+                .db
+                .query(format!(
+                    "UPDATE type::thing('job', $id) \
+                     SET lease_token = $lease, lease_until = $until, attempts += 1 \
+                     WHERE run_at <= $now AND (lease_until IS NONE OR lease_until < $now) \
+                     RETURN {FIELDS}"
+                ))
+                .bind(("id", candidate.id))
+                .bind(("lease", token))
+                .bind(("now", now))
+                .bind((
+                    "until",
+                    now.saturating_add(millis(self.inner.config.visibility_timeout)),
+                ))
+                .await?
+                .take(0)?;
+            if let Some(job) = claimed {
+                let Some(handler) = handlers.get(&job.kind).cloned() else {
+                    continue;
+                };
+                return Ok(Some((job, handler)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Extends the lease of a running job. Returns false if the lease was lost.
+    pub(super) async fn heartbeat(&self, job: &JobRecord) -> Result<bool, DatabaseError> {
+        let extended: Option<IdRow> = self
+            .inner
+            .db
+            .query(
+                "UPDATE type::thing('job', $id) SET lease_until = $until \
+                 WHERE lease_token = $lease RETURN meta::id(id) AS id",
+            )
+            .bind(("id", job.id.clone()))
+            .bind(("lease", job.lease_token.clone()))
+            .bind((
+                "until",
+                now_millis().saturating_add(millis(self.inner.config.visibility_timeout)),
+            ))
+            .await?
+            .take(0)?;
+        Ok(extended.is_some())
+    }
+
+    /// Removes a finished job.
+    pub(super) async fn complete(&self, job: &JobRecord) -> Result<(), DatabaseError> {
+        self.inner
+            .db
+            .query("DELETE type::thing('job', $id) WHERE lease_token = $lease")
+            .bind(("id", job.id.clone()))
+            .bind(("lease", job.lease_token.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Gives up the lease of a job that was interrupted by shutdown, so it
+    /// runs again at once without using up an attempt.
+    pub(super) async fn release(&self, job: &JobRecord) -> Result<(), DatabaseError> {
+        self.inner
+            .db
+            .query(
+                "UPDATE type::thing('job', $id) \
+                 SET attempts -= 1, lease_token = NONE, lease_until = NONE \
+                 WHERE lease_token = $lease",
+            )
+            .bind(("id", job.id.clone()))
+            .bind(("lease", job.lease_token.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Schedules a retry of a failed job. Returns when it will run.
+    pub(super) async fn retry(
+        &self,
+        job: &JobRecord,
+        error: &str,
+    ) -> Result<Duration, DatabaseError> {
+        let delay = self.backoff(job.attempts);
+        self.inner
+            .db
+            .query(
+                "UPDATE type::thing('job', $id) \
+                 SET run_at = $run_at, last_error = $error, lease_token = NONE, lease_until = NONE \
+                 WHERE lease_token = $lease",
+            )
+            .bind(("id", job.id.clone()))
+            .bind(("lease", job.lease_token.clone()))
+            .bind(("run_at", now_millis().saturating_add(millis(delay))))
+            .bind(("error", error.to_string()))
+            .await?
+            .check()?;
+        Ok(delay)
+    }
+
+    /// Moves a job that ran out of attempts to the dead-letter table.
+    pub(super) async fn dead_letter(
+        &self,
+        job: &JobRecord,
+        error: &str,
+    ) -> Result<(), DatabaseError> {
+        let dead = DeadJob {
+            id: job.id.clone(),
+            kind: job.kind.clone(),
+            payload: job.payload.clone(),
+            priority: job.priority,
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            error: error.to_string(),
+            created_at: job.created_at,
+            failed_at: now_millis(),
+        };
+        let mut content = serde_json::to_value(&dead)
+            .map_err(|e| DatabaseError::QueryError(format!("Job cannot be stored: {e}")))?;
+        if let Some(fields) = content.as_object_mut() {
+            fields.remove("id");
+        }
+
+        self.inner
+            .db
+            .query(
+                "BEGIN TRANSACTION;
+                 LET $deleted = (DELETE type::thing('job', $id) WHERE lease_token = $lease RETURN BEFORE);
+                 IF array::len($deleted) > 0 {
+                     CREATE type::thing('job_dead', $id) CONTENT $content RETURN NONE
+                 };
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("id", job.id.clone()))
+            .bind(("lease", job.lease_token.clone()))
+            .bind(("content", content))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Exponential backoff with jitter for the retry after `attempts` runs.
+    fn backoff(&self, attempts: u32) -> Duration {
+        let config = &self.inner.config;
+        let exponent = attempts.saturating_sub(1).min(31);
+        let delay = config
+            .backoff_base
+            .saturating_mul(1 << exponent)
+            .min(config.backoff_max);
+        // Half fixed, half random, so failed jobs do not retry in lockstep
+        let half = delay / 2;
+        half + half.mul_f64(rand::rng().random::<f64>())
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for JobQueue {
+    /// Reports degraded while jobs are stuck or due jobs wait too long.
+    async fn check(&self) -> ComponentHealth {
+        let (status, message) = match self.stats().await {
+            Ok(stats) => {
+                let waiting = stats.oldest_wait > self.inner.config.stuck_threshold;
+                let status = if stats.stuck > 0 || waiting {
+                    HealthStatus::Degraded
+                } else {
+                    HealthStatus::Healthy
+                };
+                (
+                    status,
+                    format!(
+                        "Ready: {}, delayed: {}, running: {}, stuck: {}, dead: {}, oldest wait: {}s",
+                        stats.ready,
+                        stats.delayed,
+                        stats.running,
+                        stats.stuck,
+                        stats.dead,
+                        stats.oldest_wait.as_secs()
+                    ),
+                )
+            }
+            Err(e) => {
+                warn!(error = %e, "Job queue health check failed");
+                (HealthStatus::Unhealthy, format!("Query error: {e}"))
+            }
+        };
+
+        ComponentHealth {
+            name: "Jobs".to_string(),
+            status,
+            message: Some(message),
+        }
+    }
+}
+
+/// Current time in milliseconds since the Unix epoch.
+pub(super) fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+fn lease_token() -> String {
+    hex::encode(rand::rng().random::<[u8; 16]>())
+}
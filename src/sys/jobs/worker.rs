@@ -0,0 +1,146 @@
+use super::{
+    job::{Handler, JobContext},
+    models::JobRecord,
+    queue::JobQueue,
+};
+use crate::sys::config::state::AppState;
+use std::sync::Arc;
+use tokio::time::{Instant, interval_at, sleep};
+use tracing::{Instrument, debug, error, info_span, warn};
+
+/// Claims and runs jobs until shutdown, letting the running job finish.
+pub(super) async fn run_worker(queue: JobQueue, state: Arc<AppState>, worker: usize) {
+    let poll_interval = queue.inner.config.poll_interval;
+    let shutdown = queue.inner.shutdown.clone();
+    while !shutdown.is_triggered() {
+        match queue.claim().await {
+            Ok(Some((job, handler))) => {
+                let span = info_span!("job", worker, id = %job.id, kind = %job.kind, attempt = job.attempts);
+                run_job(&queue, &state, job, handler).instrument(span).await;
+            }
+            Ok(None) => {
+                tokio::select! {
+                    () = queue.inner.notify.notified() => {}
+                    () = sleep(poll_interval) => {}
+                    () = shutdown.triggered() => {}
+                }
+            }
+            Err(e) => {
+                warn!(worker, error = %e, "Failed to claim job");
+                tokio::select! {
+                    () = sleep(poll_interval) => {}
+                    () = shutdown.triggered() => {}
+                }
+            }
+        }
+    }
+    debug!(worker, "Job worker stopped");
+}
+
+async fn run_job(queue: &JobQueue, state: &Arc<AppState>, job: JobRecord, handler: Handler) {
+    // The last attempt was claimed before but its worker never finished it
+    if job.attempts > job.max_attempts {
+        finish(
+            queue,
+            &job,
+            Err("Lease expired on the last attempt".to_string()),
+        )
+        .await;
+        return;
+    }
+
+    let running = queue.inner.metrics.gauge(
+        "jobs_running",
+        "Jobs currently being run by this instance",
+        &[],
+    );
+    running.inc();
+
+    let context = JobContext {
+        state: state.clone(),
+        id: job.id.clone(),
+        attempt: job.attempts,
+    };
+    // Spawned so a panicking job fails like any other
+    let mut task = tokio::spawn(handler(job.payload.clone(), context).in_current_span());
+
+    let period = queue.inner.config.visibility_timeout / 3;
+    let mut heartbeat = interval_at(Instant::now() + period, period);
+    let abort = async {
+        queue.inner.shutdown.triggered().await;
+        sleep(queue.inner.config.shutdown_timeout).await;
+    };
+    tokio::pin!(abort);
+    let outcome = loop {
+        tokio::select! {
+            result = &mut task => {
+                break match result {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(e) if e.is_panic() => Err("Job panicked".to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+            }
+            _ = heartbeat.tick() => match queue.heartbeat(&job).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    // Another worker owns the job now; leave it to them
+                    warn!("Lost the job lease, abandoning the run");
+                    task.abort();
+                    running.dec();
+                    return;
+                }
+                Err(e) => warn!(error = %e, "Failed to extend the job lease"),
+            },
+            () = &mut abort => {
+                warn!("Job aborted after the shutdown timeout, releasing it");
+                task.abort();
+                running.dec();
+                if let Err(e) = queue.release(&job).await {
+                    // The lease expires and the job runs again
+                    warn!(error = %e, "Failed to release the job");
+                }
+                return;
+            }
+        }
+    };
+
+    running.dec();
+    finish(queue, &job, outcome).await;
+}
+
+/// Records the outcome of a run: removes the job, schedules a retry or moves
+/// it to the dead-letter table.
+async fn finish(queue: &JobQueue, job: &JobRecord, outcome: Result<(), String>) {
+    let (label, result) = match outcome {
+        Ok(()) => {
+            debug!("Job completed");
+            ("completed", queue.complete(job).await)
+        }
+        Err(message) if job.attempts >= job.max_attempts => {
+            error!(error = %message, "Job failed its last attempt, moving it to the dead-letter table");
+            ("dead", queue.dead_letter(job, &message).await)
+        }
+        Err(message) => match queue.retry(job, &message).await {
+            Ok(delay) => {
+                warn!(error = %message, retry_in_ms = delay.as_millis(), "Job failed, retrying");
+                ("retried", Ok(()))
+            }
+            Err(e) => ("retried", Err(e)),
+        },
+    };
+
+    if let Err(e) = result {
+        // The lease expires and the job runs again
+        warn!(error = %e, "Failed to record the job outcome");
+    }
+    queue
+        .inner
+        .metrics
+        .counter(
+            "jobs_processed_total",
+            "Job runs by kind and outcome",
+            &[("kind", &job.kind), ("outcome", label)],
+        )
+        .inc();
+}
@@ -1,40 +1,64 @@
-use crate::sys::env;
+use crate::sys::{config::ConfigReader, env::EnvEnum};
 
 use super::models::{LogConfig, LogFormat};
 
+impl EnvEnum for LogFormat {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("json", Self::Json),
+        ("compact", Self::Compact),
+        ("auto", Self::auto()),
+    ];
+}
+
 impl LogFormat {
-    /// Creates a `LogFormat` from the `LOG_FORMAT` environment variable.
-    pub fn from_env() -> Self {
-        let format_str = env::get_or_default("LOG_FORMAT", "auto");
-
-        match format_str.to_lowercase().as_str() {
-            "json" => Self::Json,
-            "compact" => Self::Compact,
-            _ => {
-                if cfg!(debug_assertions) {
-                    Self::Compact
-                } else {
-                    Self::Json
-                }
-            }
+    /// Returns the default format for the current build profile.
+    #[must_use]
+    pub const fn auto() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Compact
+        } else {
+            Self::Json
         }
     }
 }
 
 impl LogConfig {
-    /// Creates a `LogConfig` from environment variables.
-    pub fn from_env() -> Self {
-        let format = LogFormat::from_env();
-
-        let filter = env::get_or_default(
-            "RUST_LOG",
-            if cfg!(debug_assertions) {
-                "axum_backend=debug,tower_http=debug,info"
-            } else {
-                "axum_backend=info,tower_http=info,warn"
-            },
-        );
+    /// Creates a `LogConfig` from layered configuration.
+    ///
+    /// The filter is read from `LOG_FILTER`, falling back to `RUST_LOG`.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let format = config.enumeration("LOG_FORMAT", LogFormat::auto());
+
+        let filter = config
+            .optional("LOG_FILTER")
+            .or_else(|| config.optional("RUST_LOG"))
+            .unwrap_or_else(|| config.string("LOG_FILTER", &default_filter()));
+
+        if let Err(e) = tracing_subscriber::EnvFilter::try_new(&filter) {
+            config.invalid(
+                "LOG_FILTER",
+                format!("'{filter}' is not a valid filter: {e}"),
+            );
+        }
 
         Self { format, filter }
     }
 }
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::auto(),
+            filter: default_filter(),
+        }
+    }
+}
+
+fn default_filter() -> String {
+    if cfg!(debug_assertions) {
+        "axum_backend=debug,tower_http=debug,info"
+    } else {
+        "axum_backend=info,tower_http=info,warn"
+    }
+    .to_string()
+}
@@ -1,36 +1,48 @@
 use super::models::{LogConfig, LogFormat};
-use tracing_subscriber::{EnvFilter, fmt};
+use std::sync::OnceLock;
+use tracing_subscriber::{
+    EnvFilter, Registry, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt,
+};
+
+/// Handle used to swap the active filter when configuration is reloaded.
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 /// Initializes the tracing subscriber for logging.
-pub fn init_tracing() {
-    let config = LogConfig::from_env();
+pub fn init_tracing(config: &LogConfig) {
     let env_filter = EnvFilter::try_new(&config.filter).unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter_layer, handle) = reload::Layer::new(env_filter);
+    let registry = tracing_subscriber::registry().with(filter_layer);
 
     match config.format {
         LogFormat::Json => {
-            fmt()
-                .json()
-                .with_env_filter(env_filter)
-                .with_target(true)
-                .with_line_number(false)
-                .with_file(false)
-                .with_thread_ids(false)
-                .with_level(true)
-                .with_current_span(true)
+            registry
+                .with(
+                    fmt::layer()
+                        .json()
+                        .with_target(true)
+                        .with_line_number(false)
+                        .with_file(false)
+                        .with_thread_ids(false)
+                        .with_level(true)
+                        .with_current_span(true),
+                )
                 .init();
         }
         LogFormat::Compact => {
-            fmt()
-                .compact()
-                .with_env_filter(env_filter)
-                .with_target(true)
-                .with_line_number(true)
-                .with_file(true)
-                .with_thread_ids(false)
-                .with_level(true)
+            registry
+                .with(
+                    fmt::layer()
+                        .compact()
+                        .with_target(true)
+                        .with_line_number(true)
+                        .with_file(true)
+                        .with_thread_ids(false)
+                        .with_level(true),
+                )
                 .init();
         }
     }
+    let _ = FILTER_HANDLE.set(handle);
 
     tracing::info!(
         format = ?config.format,
@@ -38,3 +50,17 @@ pub fn init_tracing() {
         "Tracing initialized"
     );
 }
+
+/// Replaces the active log filter.
+///
+/// # Errors
+///
+/// Returns an error message if the filter is invalid or tracing has not been
+/// initialized.
+pub fn reload_filter(filter: &str) -> Result<(), String> {
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "tracing is not initialized".to_string())?;
+    let env_filter = EnvFilter::try_new(filter).map_err(|e| e.to_string())?;
+    handle.reload(env_filter).map_err(|e| e.to_string())
+}
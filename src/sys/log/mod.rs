@@ -1,4 +1,7 @@
 mod config;
 mod init;
 mod models;
-pub use init::init_tracing;
+mod reload;
+pub use init::{init_tracing, reload_filter};
+pub use models::{LogConfig, LogFormat};
+pub use reload::spawn_log_reloader;
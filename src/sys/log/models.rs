@@ -3,6 +3,8 @@ pub enum LogFormat {
     Json,
     Compact,
 }
+
+#[derive(Debug, Clone)]
 pub struct LogConfig {
     pub format: LogFormat,
     pub filter: String,
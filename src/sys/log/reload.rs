@@ -0,0 +1,29 @@
+use super::init::reload_filter;
+use crate::sys::config::AppConfig;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Applies log filter changes from reloaded configuration.
+///
+/// The output format is fixed at startup; changing it requires a restart.
+pub fn spawn_log_reloader(mut changes: watch::Receiver<Arc<AppConfig>>) {
+    let mut current = changes.borrow_and_update().log.clone();
+
+    tokio::spawn(async move {
+        while changes.changed().await.is_ok() {
+            let next = changes.borrow_and_update().log.clone();
+
+            if next.format != current.format {
+                warn!("Log format changes require a restart and were not applied");
+            }
+            if next.filter != current.filter {
+                match reload_filter(&next.filter) {
+                    Ok(()) => info!(filter = %next.filter, "Log filter updated"),
+                    Err(e) => warn!(error = %e, "Failed to apply reloaded log filter"),
+                }
+            }
+            current = next;
+        }
+    });
+}
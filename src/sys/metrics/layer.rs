@@ -0,0 +1,45 @@
+use crate::sys::config::state::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Middleware that counts requests by method, route and status, and tracks
+/// how many are in flight.
+pub async fn track_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |p| p.as_str().to_string());
+
+    let in_flight = state.metrics.gauge(
+        "http_requests_in_flight",
+        "Requests currently being processed",
+        &[],
+    );
+    in_flight.inc();
+    let response = next.run(request).await;
+    in_flight.dec();
+
+    state
+        .metrics
+        .counter(
+            "http_requests_total",
+            "Requests served by method, route and status",
+            &[
+                ("method", method.as_str()),
+                ("route", &route),
+                ("status", response.status().as_str()),
+            ],
+        )
+        .inc();
+
+    response
+}
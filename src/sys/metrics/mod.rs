@@ -0,0 +1,9 @@
+pub mod layer;
+pub mod models;
+pub mod registry;
+pub mod routes;
+
+pub use layer::track_requests;
+pub use models::{Counter, Gauge, MetricKind};
+pub use registry::MetricsRegistry;
+pub use routes::get_metrics;
@@ -0,0 +1,117 @@
+use super::models::{Counter, Gauge, MetricKind, Series};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{PoisonError, RwLock},
+};
+
+#[derive(Debug)]
+struct Family {
+    help: String,
+    kind: MetricKind,
+    /// Series keyed by their rendered label set, e.g. `method="GET"`.
+    series: BTreeMap<String, Series>,
+}
+
+/// Process-wide metrics, rendered in the Prometheus text format by `/metrics`.
+///
+/// Metrics are created on first use, so callers fetch a handle with
+/// `counter`/`gauge` and keep it or look it up again as needed.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    families: RwLock<BTreeMap<String, Family>>,
+}
+
+impl MetricsRegistry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the counter with the given name and labels, creating it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is already registered as a gauge.
+    pub fn counter(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Counter {
+        match self.series(name, help, MetricKind::Counter, labels) {
+            Series::Counter(counter) => counter,
+            Series::Gauge(_) => panic!("metric '{name}' is registered as a gauge"),
+        }
+    }
+
+    /// Returns the gauge with the given name and labels, creating it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is already registered as a counter.
+    pub fn gauge(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Gauge {
+        match self.series(name, help, MetricKind::Gauge, labels) {
+            Series::Gauge(gauge) => gauge,
+            Series::Counter(_) => panic!("metric '{name}' is registered as a counter"),
+        }
+    }
+
+    fn series(&self, name: &str, help: &str, kind: MetricKind, labels: &[(&str, &str)]) -> Series {
+        let labels = render_labels(labels);
+
+        let families = self.families.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(series) = families.get(name).and_then(|f| f.series.get(&labels)) {
+            return series.clone();
+        }
+        drop(families);
+
+        let mut families = self
+            .families
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let family = families.entry(name.to_string()).or_insert_with(|| Family {
+            help: help.to_string(),
+            kind,
+            series: BTreeMap::new(),
+        });
+        family
+            .series
+            .entry(labels)
+            .or_insert_with(|| match family.kind {
+                MetricKind::Counter => Series::Counter(Counter::default()),
+                MetricKind::Gauge => Series::Gauge(Gauge::default()),
+            })
+            .clone()
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let families = self.families.read().unwrap_or_else(PoisonError::into_inner);
+        let mut output = String::new();
+
+        for (name, family) in families.iter() {
+            let _ = writeln!(output, "# HELP {name} {}", family.help);
+            let _ = writeln!(output, "# TYPE {name} {}", family.kind.as_str());
+            for (labels, series) in &family.series {
+                if labels.is_empty() {
+                    let _ = writeln!(output, "{name} {}", series.value());
+                } else {
+                    let _ = writeln!(output, "{name}{{{labels}}} {}", series.value());
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{key}=\"{value}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -0,0 +1,14 @@
+use crate::sys::config::state::AppState;
+use axum::{extract::State, http::header, response::IntoResponse};
+use std::sync::Arc;
+
+/// Content type of the Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Returns every registered metric in the Prometheus text format.
+pub async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        state.metrics.render(),
+    )
+}
@@ -0,0 +1,60 @@
+use crate::sys::{config::ConfigReader, env};
+
+use super::models::{AccessLogConfig, AccessLogField};
+
+impl AccessLogConfig {
+    /// Creates an `AccessLogConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config.bool("ACCESS_LOG_ENABLED", true);
+
+        let field_names = config.list("ACCESS_LOG_FIELDS", "all");
+        let fields = if field_names.iter().any(|f| f.eq_ignore_ascii_case("all")) {
+            AccessLogField::ALL.to_vec()
+        } else {
+            field_names
+                .iter()
+                .filter_map(|name| {
+                    let field = env::parse_enum::<AccessLogField>(name);
+                    if field.is_none() {
+                        config.invalid("ACCESS_LOG_FIELDS", format!("unknown field '{name}'"));
+                    }
+                    field
+                })
+                .collect()
+        };
+
+        let exclude_paths = config.list("ACCESS_LOG_EXCLUDE_PATHS", "/health,/metrics");
+
+        let success_sample_rate: f64 = config.parsed("ACCESS_LOG_SAMPLE_RATE", 1.0_f64);
+        if !(0.0..=1.0).contains(&success_sample_rate) {
+            config.invalid(
+                "ACCESS_LOG_SAMPLE_RATE",
+                format!("{success_sample_rate} is not between 0.0 and 1.0"),
+            );
+        }
+
+        let mut latency_buckets_ms: Vec<u64> = config
+            .list("ACCESS_LOG_LATENCY_BUCKETS", "10,50,100,250,500,1000,2500")
+            .iter()
+            .filter_map(|bucket| {
+                let parsed = bucket.parse().ok();
+                if parsed.is_none() {
+                    config.invalid(
+                        "ACCESS_LOG_LATENCY_BUCKETS",
+                        format!("'{bucket}' is not a number of milliseconds"),
+                    );
+                }
+                parsed
+            })
+            .collect();
+        latency_buckets_ms.sort_unstable();
+
+        Self {
+            enabled,
+            fields,
+            exclude_paths,
+            success_sample_rate,
+            latency_buckets_ms,
+        }
+    }
+}
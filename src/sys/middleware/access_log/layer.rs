@@ -0,0 +1,126 @@
+use super::models::{AccessLogField, UserId};
+use crate::sys::config::state::AppState;
+use axum::{
+    body::HttpBody,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::time::Instant;
+use tracing::Level;
+
+/// Emits the same access log event at a level chosen at runtime.
+macro_rules! access_event {
+    ($level:expr, $($fields:tt)+) => {
+        match $level {
+            Level::ERROR => tracing::error!(target: "access_log", $($fields)+),
+            Level::WARN => tracing::warn!(target: "access_log", $($fields)+),
+            _ => tracing::info!(target: "access_log", $($fields)+),
+        }
+    };
+}
+
+/// Middleware that emits one structured event per HTTP request.
+///
+/// Server errors are logged at `error`, client errors at `warn` and everything
+/// else at `info`, subject to path exclusions and success sampling.
+pub async fn access_log(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let app_config = state.config.load();
+    let config = &app_config.access_log;
+    let path = request.uri().path().to_owned();
+    if !config.enabled || config.is_excluded(&path) {
+        return next.run(request).await;
+    }
+
+    let start = Instant::now();
+    let method = request.method().clone();
+
+    let client_ip = config
+        .has(AccessLogField::ClientIp)
+        .then(|| request.extensions().get::<ConnectInfo<SocketAddr>>())
+        .flatten()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let user_agent = config
+        .has(AccessLogField::UserAgent)
+        .then(|| header_value(&request, header::USER_AGENT.as_str()))
+        .flatten();
+    let route = config
+        .has(AccessLogField::Route)
+        .then(|| request.extensions().get::<MatchedPath>())
+        .flatten()
+        .map(|p| p.as_str().to_owned());
+    let request_id = config
+        .has(AccessLogField::RequestId)
+        .then(|| header_value(&request, "x-request-id"))
+        .flatten();
+    let request_user = request.extensions().get::<UserId>().cloned();
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    if status.as_u16() < 400
+        && config.success_sample_rate < 1.0
+        && rand::random::<f64>() >= config.success_sample_rate
+    {
+        return response;
+    }
+
+    let latency_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let latency_bucket = config.latency_bucket(latency_ms);
+    let response_size = config
+        .has(AccessLogField::ResponseSize)
+        .then(|| response.body().size_hint().exact())
+        .flatten();
+    // Handlers may attach the user to the response when they authenticate it themselves.
+    let user_id = config
+        .has(AccessLogField::UserId)
+        .then(|| {
+            response
+                .extensions()
+                .get::<UserId>()
+                .cloned()
+                .or(request_user)
+        })
+        .flatten()
+        .map(|UserId(id)| id);
+
+    let level = if status.is_server_error() {
+        Level::ERROR
+    } else if status.is_client_error() {
+        Level::WARN
+    } else {
+        Level::INFO
+    };
+
+    access_event!(
+        level,
+        method = %method,
+        path = %path,
+        status = status.as_u16(),
+        latency_ms,
+        latency_bucket = %latency_bucket,
+        route = route.as_deref(),
+        client_ip = client_ip.as_deref(),
+        user_agent = user_agent.as_deref(),
+        response_size,
+        user_id = user_id.as_deref(),
+        request_id = request_id.as_deref(),
+        "HTTP request completed"
+    );
+
+    response
+}
+
+fn header_value(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
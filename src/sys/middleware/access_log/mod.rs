@@ -0,0 +1,5 @@
+mod config;
+mod layer;
+mod models;
+pub use layer::access_log;
+pub use models::{AccessLogConfig, AccessLogField, UserId};
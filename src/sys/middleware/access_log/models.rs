@@ -0,0 +1,86 @@
+use crate::sys::{env::EnvEnum, middleware::route_pattern};
+
+/// Optional fields that can be attached to each access log event.
+///
+/// Method, path, status and latency are always emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessLogField {
+    ClientIp,
+    UserAgent,
+    Route,
+    ResponseSize,
+    UserId,
+    RequestId,
+}
+
+impl AccessLogField {
+    pub const ALL: [Self; 6] = [
+        Self::ClientIp,
+        Self::UserAgent,
+        Self::Route,
+        Self::ResponseSize,
+        Self::UserId,
+        Self::RequestId,
+    ];
+}
+
+impl EnvEnum for AccessLogField {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("client_ip", Self::ClientIp),
+        ("ip", Self::ClientIp),
+        ("user_agent", Self::UserAgent),
+        ("route", Self::Route),
+        ("response_size", Self::ResponseSize),
+        ("size", Self::ResponseSize),
+        ("user_id", Self::UserId),
+        ("user", Self::UserId),
+        ("request_id", Self::RequestId),
+    ];
+}
+
+#[derive(Debug, Clone)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    pub fields: Vec<AccessLogField>,
+    /// Paths that are never logged. Entries ending in `*` match by prefix.
+    pub exclude_paths: Vec<String>,
+    /// Probability (0.0..=1.0) that a successful (< 400) request is logged.
+    /// Client and server errors are always logged.
+    pub success_sample_rate: f64,
+    /// Upper bounds in milliseconds used to bucket request latency.
+    pub latency_buckets_ms: Vec<u64>,
+}
+
+impl AccessLogConfig {
+    /// Returns true if the field should be emitted.
+    #[must_use]
+    pub fn has(&self, field: AccessLogField) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// Returns true if requests to `path` must not be logged.
+    #[must_use]
+    pub fn is_excluded(&self, path: &str) -> bool {
+        route_pattern::is_excluded(&self.exclude_paths, path)
+    }
+
+    /// Returns the label of the latency bucket for the given duration.
+    #[must_use]
+    pub fn latency_bucket(&self, latency_ms: u64) -> String {
+        self.latency_buckets_ms
+            .iter()
+            .find(|&&bound| latency_ms <= bound)
+            .map_or_else(
+                || {
+                    let last = self.latency_buckets_ms.last().copied().unwrap_or(0);
+                    format!("gt_{last}ms")
+                },
+                |bound| format!("le_{bound}ms"),
+            )
+    }
+}
+
+/// Identifier of the authenticated user, inserted into request extensions by
+/// authentication layers so the access log can report it.
+#[derive(Debug, Clone)]
+pub struct UserId(pub String);
@@ -0,0 +1,82 @@
+use std::{
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    panic::{self, PanicHookInfo},
+    sync::Once,
+};
+
+/// What the panic hook captured about a panic caught by the middleware.
+#[derive(Debug, Clone)]
+pub struct PanicDetails {
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+thread_local! {
+    /// Set while a request future is polled by the catch-panic middleware.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static CAPTURED: RefCell<Option<PanicDetails>> = const { RefCell::new(None) };
+}
+
+static INSTALL: Once = Once::new();
+
+/// Installs a panic hook that records the location and backtrace of panics
+/// inside requests for the catch-panic middleware to log, instead of
+/// printing them to stderr. Other panics, including those run through
+/// [`uncaptured`], go to the previous hook.
+pub fn install_panic_hook() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info: &PanicHookInfo<'_>| {
+            if CATCHING.get() {
+                let details = PanicDetails {
+                    location: info.location().map(ToString::to_string),
+                    backtrace: Backtrace::force_capture().to_string(),
+                };
+                CAPTURED.set(Some(details));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+/// Marks the current thread as catching panics until dropped.
+pub(super) struct CatchingGuard {
+    previous: bool,
+}
+
+impl CatchingGuard {
+    /// Also drops details left by a panic that something inside the
+    /// request caught, so they are not logged for a later one.
+    pub(super) fn enter() -> Self {
+        CAPTURED.take();
+        Self {
+            previous: CATCHING.replace(true),
+        }
+    }
+
+    fn leave() -> Self {
+        Self {
+            previous: CATCHING.replace(false),
+        }
+    }
+}
+
+impl Drop for CatchingGuard {
+    fn drop(&mut self) {
+        CATCHING.set(self.previous);
+    }
+}
+
+/// Runs `f` with its panics going to the previous panic hook, for code that
+/// catches and handles panics itself while it may run inside a request.
+pub fn uncaptured<R>(f: impl FnOnce() -> R) -> R {
+    let _leaving = CatchingGuard::leave();
+    f()
+}
+
+/// Takes the details captured for the last panic on this thread.
+pub(super) fn take_captured() -> Option<PanicDetails> {
+    CAPTURED.take()
+}
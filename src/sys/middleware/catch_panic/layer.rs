@@ -0,0 +1,71 @@
+use super::hook::{CatchingGuard, take_captured};
+use crate::{AppError, sys::config::state::AppState};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::FutureExt;
+use std::{
+    any::Any,
+    future::{Future, poll_fn},
+    panic::AssertUnwindSafe,
+    pin::pin,
+    sync::Arc,
+};
+use tracing::error;
+
+/// Middleware that turns a panic in a handler into a JSON 500 response
+/// carrying the request id, logs the panic with its backtrace, and counts it.
+///
+/// It must wrap the routes directly so that the other layers still observe
+/// the response.
+pub async fn catch_panic(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let mut response = pin!(AssertUnwindSafe(next.run(request)).catch_unwind());
+    let result = poll_fn(|cx| {
+        let _catching = CatchingGuard::enter();
+        response.as_mut().poll(cx)
+    })
+    .await;
+
+    match result {
+        Ok(response) => response,
+        Err(payload) => {
+            let details = take_captured();
+            error!(
+                request_id = request_id.as_deref(),
+                method = %method,
+                path = %path,
+                panic = %panic_message(payload.as_ref()),
+                location = details.as_ref().and_then(|d| d.location.as_deref()),
+                backtrace = details.as_ref().map(|d| d.backtrace.as_str()),
+                "Request handler panicked"
+            );
+            state
+                .metrics
+                .counter("http_panics_total", "Request handlers that panicked", &[])
+                .inc();
+            AppError::Panic { request_id }.into_response()
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>")
+}
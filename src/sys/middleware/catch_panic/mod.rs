@@ -0,0 +1,4 @@
+mod hook;
+mod layer;
+pub use hook::{PanicDetails, install_panic_hook, uncaptured};
+pub use layer::catch_panic;
@@ -0,0 +1,25 @@
+use super::models::{ConditionalConfig, ETagMode};
+use crate::sys::config::ConfigReader;
+use axum::http::HeaderValue;
+
+impl ConditionalConfig {
+    /// Creates a `ConditionalConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let cache_control = config.string("HTTP_CACHE_CONTROL", "no-cache");
+        let cache_control = if cache_control.trim().is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(cache_control.trim())
+                .inspect_err(|_| {
+                    config.invalid("HTTP_CACHE_CONTROL", "is not a valid header value");
+                })
+                .ok()
+        };
+
+        Self {
+            etag: config.enumeration("HTTP_ETAG", ETagMode::Weak),
+            max_body_size: config.byte_size("HTTP_ETAG_MAX_SIZE", 1024 * 1024),
+            cache_control,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use super::models::{ETag, ETagList, parse_http_date};
+use crate::{AppError, dbs::error::DatabaseError};
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+use chrono::{DateTime, Utc};
+use std::future::Future;
+
+const MISSING_IF_MATCH: &str = "Updates must send If-Match with the ETag of the current version";
+
+/// The `If-Match` and `If-Unmodified-Since` headers of an update.
+///
+/// Handlers compare them with the current version of the resource before
+/// changing it, so clients updating a stale copy get 412 instead of
+/// overwriting someone else's change.
+#[derive(Debug, Clone, Default)]
+pub struct Preconditions {
+    pub if_match: Option<ETagList>,
+    pub if_unmodified_since: Option<DateTime<Utc>>,
+}
+
+impl Preconditions {
+    /// Returns true if the request carries `If-Match`.
+    #[must_use]
+    pub fn has_if_match(&self) -> bool {
+        self.if_match.is_some()
+    }
+
+    /// Returns the record version named by `If-Match`, if it holds exactly
+    /// one ETag created with [`ETag::from_version`].
+    #[must_use]
+    pub fn version(&self) -> Option<u64> {
+        match &self.if_match {
+            Some(ETagList::Tags(tags)) if tags.len() == 1 && !tags[0].weak => tags[0].version(),
+            _ => None,
+        }
+    }
+
+    /// Checks the preconditions against the current version of the resource,
+    /// `None` when it does not exist.
+    ///
+    /// `If-Unmodified-Since` is only evaluated without `If-Match`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::PreconditionFailed` if the client's copy is stale.
+    pub fn check(
+        &self,
+        current: Option<&ETag>,
+        last_modified: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        let fresh = match (&self.if_match, self.if_unmodified_since) {
+            (Some(tags), _) => current.is_some_and(|etag| tags.matches_strong(etag)),
+            (None, Some(since)) => last_modified.is_some_and(|modified| modified <= since),
+            (None, None) => true,
+        };
+        if fresh {
+            Ok(())
+        } else {
+            Err(AppError::PreconditionFailed(
+                "The resource was modified since it was retrieved".to_string(),
+            ))
+        }
+    }
+
+    /// Like [`Preconditions::check`], but also rejects requests without
+    /// `If-Match`.
+    ///
+    /// # Errors
+    ///
+    /// - `AppError::PreconditionRequired` if the request has no `If-Match`
+    /// - `AppError::PreconditionFailed` if the client's copy is stale
+    pub fn require(
+        &self,
+        current: Option<&ETag>,
+        last_modified: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        if !self.has_if_match() {
+            return Err(AppError::PreconditionRequired(MISSING_IF_MATCH.to_string()));
+        }
+        self.check(current, last_modified)
+    }
+
+    /// Runs a [`Repository`](crate::dbs::repository::Repository) update or
+    /// delete at the version named by `If-Match`, such as
+    /// `preconditions.write(|version| users.update(&id, version, &user))`.
+    ///
+    /// # Errors
+    ///
+    /// - `AppError::PreconditionRequired` if the request has no `If-Match`
+    /// - `AppError::PreconditionFailed` if `If-Match` does not name a single
+    ///   version, or the record is at another version
+    /// - the error of `write` otherwise
+    pub async fn write<T, F, Fut>(&self, write: F) -> Result<T, AppError>
+    where
+        F: FnOnce(u64) -> Fut,
+        Fut: Future<Output = Result<T, DatabaseError>>,
+    {
+        if !self.has_if_match() {
+            return Err(AppError::PreconditionRequired(MISSING_IF_MATCH.to_string()));
+        }
+        let version = self.version().ok_or_else(|| {
+            AppError::PreconditionFailed(
+                "If-Match must hold the ETag of a single version".to_string(),
+            )
+        })?;
+        write(version).await.map_err(|e| match e {
+            DatabaseError::VersionMismatch(message) => AppError::PreconditionFailed(message),
+            e => e.into(),
+        })
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Preconditions {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let if_match = match parts.headers.get(header::IF_MATCH) {
+            None => None,
+            Some(value) => Some(value.to_str().ok().and_then(ETagList::parse).ok_or_else(
+                || AppError::BadRequest("If-Match is not a valid ETag list".to_string()),
+            )?),
+        };
+        let if_unmodified_since = match parts.headers.get(header::IF_UNMODIFIED_SINCE) {
+            None => None,
+            Some(value) => Some(value.to_str().ok().and_then(parse_http_date).ok_or_else(
+                || AppError::BadRequest("If-Unmodified-Since is not a valid HTTP date".to_string()),
+            )?),
+        };
+
+        Ok(Self {
+            if_match,
+            if_unmodified_since,
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use super::models::{ETag, ETagList, ETagMode, parse_http_date};
+use crate::{AppError, sys::config::state::AppState};
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{Request, State},
+    http::{HeaderMap, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Headers kept on 304 responses, as they would be sent with a 200.
+const NOT_MODIFIED_HEADERS: [header::HeaderName; 6] = [
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::ETAG,
+    header::EXPIRES,
+    header::LAST_MODIFIED,
+    header::VARY,
+];
+
+/// Middleware that adds validators to GET responses and answers conditional
+/// requests whose cached copy is still current with 304 Not Modified.
+///
+/// Responses without an `ETag` get one computed from their body, and
+/// responses without `Cache-Control` get the configured default. A request
+/// matching on `If-None-Match`, or on `If-Modified-Since` when it has no
+/// `If-None-Match`, is answered without a body.
+pub async fn conditional(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return next.run(request).await;
+    }
+    let config = state.config.load().conditional.clone();
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ETagList::parse);
+    let if_modified_since = request
+        .headers()
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_http_date);
+
+    let mut response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    if let Some(cache_control) = config.cache_control {
+        response
+            .headers_mut()
+            .entry(header::CACHE_CONTROL)
+            .or_insert(cache_control);
+    }
+
+    let fits = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= config.max_body_size);
+    if config.etag != ETagMode::Off && fits && !response.headers().contains_key(header::ETAG) {
+        let (mut parts, body) = response.into_parts();
+        let body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!(error = %e, "Failed to buffer response to compute its ETag");
+                return AppError::ServerError("Failed to produce the response".to_string())
+                    .into_response();
+            }
+        };
+        let mut etag = ETag::from_body(&body);
+        etag.weak = config.etag == ETagMode::Weak;
+        if let Some(value) = etag.to_header_value() {
+            parts.headers.insert(header::ETAG, value);
+        }
+        response = Response::from_parts(parts, Body::from(body));
+    }
+
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ETag::parse);
+    let not_modified = match (if_none_match, if_modified_since) {
+        (Some(tags), _) => etag.is_some_and(|etag| tags.matches_weak(&etag)),
+        (None, Some(since)) => response
+            .headers()
+            .get(header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_http_date)
+            .is_some_and(|modified| modified <= since),
+        (None, None) => false,
+    };
+
+    if not_modified {
+        return not_modified_response(response.headers());
+    }
+    response
+}
+
+fn not_modified_response(headers: &HeaderMap) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::NOT_MODIFIED;
+    for name in NOT_MODIFIED_HEADERS {
+        for value in headers.get_all(&name) {
+            response.headers_mut().append(name.clone(), value.clone());
+        }
+    }
+    response
+}
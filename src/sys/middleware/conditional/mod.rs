@@ -0,0 +1,9 @@
+mod config;
+mod extract;
+mod layer;
+mod models;
+mod response;
+pub use extract::Preconditions;
+pub use layer::conditional;
+pub use models::{ConditionalConfig, ETag, ETagList, ETagMode, format_http_date, parse_http_date};
+pub use response::Validated;
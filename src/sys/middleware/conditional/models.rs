@@ -0,0 +1,182 @@
+use crate::sys::env::EnvEnum;
+use axum::http::HeaderValue;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct ConditionalConfig {
+    /// How ETags are computed for GET responses that do not set one.
+    pub etag: ETagMode,
+    /// Responses larger than this many bytes get no computed ETag.
+    pub max_body_size: u64,
+    /// `Cache-Control` for GET responses that do not set one.
+    pub cache_control: Option<HeaderValue>,
+}
+
+/// How ETags are computed from response bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ETagMode {
+    /// Weak ETags, equal for responses with the same meaning.
+    Weak,
+    /// Strong ETags, equal only for byte-identical responses.
+    Strong,
+    /// ETags are only sent when handlers set them.
+    Off,
+}
+
+impl EnvEnum for ETagMode {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("weak", Self::Weak),
+        ("strong", Self::Strong),
+        ("off", Self::Off),
+        ("none", Self::Off),
+    ];
+}
+
+/// An entity tag identifying one version of a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag {
+    pub tag: String,
+    pub weak: bool,
+}
+
+impl ETag {
+    #[must_use]
+    pub fn strong(tag: impl Into<String>) -> Self {
+        Self {
+            tag: tag.into(),
+            weak: false,
+        }
+    }
+
+    #[must_use]
+    pub fn weak(tag: impl Into<String>) -> Self {
+        Self {
+            tag: tag.into(),
+            weak: true,
+        }
+    }
+
+    /// Strong ETag from the SHA-256 of a response body.
+    #[must_use]
+    pub fn from_body(body: &[u8]) -> Self {
+        let digest = Sha256::digest(body);
+        // 128 bits are plenty to tell versions of one resource apart
+        Self::strong(hex::encode(&digest[..16]))
+    }
+
+    /// Strong ETag from a record version, usable with `If-Match`.
+    #[must_use]
+    pub fn from_version(version: u64) -> Self {
+        Self::strong(format!("v{version}"))
+    }
+
+    /// Returns the version of an ETag created with [`ETag::from_version`].
+    #[must_use]
+    pub fn version(&self) -> Option<u64> {
+        self.tag.strip_prefix('v')?.parse().ok()
+    }
+
+    /// Parses a single ETag such as `"abc"` or `W/"abc"`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let tag = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        if tag.contains('"') {
+            return None;
+        }
+        Some(Self {
+            tag: tag.to_string(),
+            weak,
+        })
+    }
+
+    /// Strong comparison: both tags are strong and equal. Used by `If-Match`.
+    #[must_use]
+    pub fn strong_eq(&self, other: &Self) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison: the tags are equal. Used by `If-None-Match`.
+    #[must_use]
+    pub fn weak_eq(&self, other: &Self) -> bool {
+        self.tag == other.tag
+    }
+
+    #[must_use]
+    pub fn to_header_value(&self) -> Option<HeaderValue> {
+        HeaderValue::from_str(&self.to_string()).ok()
+    }
+}
+
+impl fmt::Display for ETag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            write!(f, "W/\"{}\"", self.tag)
+        } else {
+            write!(f, "\"{}\"", self.tag)
+        }
+    }
+}
+
+/// The value of an `If-Match` or `If-None-Match` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ETagList {
+    /// `*`, any current version.
+    Any,
+    Tags(Vec<ETag>),
+}
+
+impl ETagList {
+    /// Parses `*` or a comma-separated list of ETags.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.trim() == "*" {
+            return Some(Self::Any);
+        }
+        let tags = value
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(ETag::parse)
+            .collect::<Option<Vec<_>>>()?;
+        (!tags.is_empty()).then_some(Self::Tags(tags))
+    }
+
+    /// Returns true if the list matches `current` using strong comparison.
+    #[must_use]
+    pub fn matches_strong(&self, current: &ETag) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Tags(tags) => tags.iter().any(|tag| tag.strong_eq(current)),
+        }
+    }
+
+    /// Returns true if the list matches `current` using weak comparison.
+    #[must_use]
+    pub fn matches_weak(&self, current: &ETag) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Tags(tags) => tags.iter().any(|tag| tag.weak_eq(current)),
+        }
+    }
+}
+
+/// Formats a time as an HTTP date such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+#[must_use]
+pub fn format_http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses an HTTP date such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+#[must_use]
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
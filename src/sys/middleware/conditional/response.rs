@@ -0,0 +1,69 @@
+use super::models::{ETag, format_http_date};
+use axum::{
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+
+/// A response with cache validators.
+///
+/// Conditional GET requests against these validators are answered with 304
+/// by the conditional middleware.
+#[derive(Debug, Clone)]
+pub struct Validated<T> {
+    body: T,
+    etag: Option<ETag>,
+    last_modified: Option<DateTime<Utc>>,
+    cache_control: Option<HeaderValue>,
+}
+
+impl<T> Validated<T> {
+    #[must_use]
+    pub fn new(body: T) -> Self {
+        Self {
+            body,
+            etag: None,
+            last_modified: None,
+            cache_control: None,
+        }
+    }
+
+    #[must_use]
+    pub fn etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    #[must_use]
+    pub fn last_modified(mut self, time: DateTime<Utc>) -> Self {
+        self.last_modified = Some(time);
+        self
+    }
+
+    /// Sets `Cache-Control`; invalid values are ignored.
+    #[must_use]
+    pub fn cache_control(mut self, value: &str) -> Self {
+        self.cache_control = HeaderValue::from_str(value).ok();
+        self
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Validated<T> {
+    fn into_response(self) -> Response {
+        let mut response = self.body.into_response();
+        let headers = response.headers_mut();
+        if let Some(value) = self.etag.as_ref().and_then(ETag::to_header_value) {
+            headers.insert(header::ETAG, value);
+        }
+        if let Some(value) = self
+            .last_modified
+            .and_then(|time| HeaderValue::from_str(&format_http_date(time)).ok())
+        {
+            headers.insert(header::LAST_MODIFIED, value);
+        }
+        if let Some(value) = self.cache_control {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+        response
+    }
+}
@@ -0,0 +1,151 @@
+use super::models::{
+    CompressionAlgorithm, CompressionConfig, CorsConfig, HttpConfig, SecurityHeadersConfig,
+    TimeoutConfig, TimeoutStatus,
+};
+use crate::sys::{
+    config::ConfigReader,
+    env::{self, EnvEnum},
+};
+use axum::http::HeaderValue;
+use std::{str::FromStr, time::Duration};
+
+/// Default `Content-Security-Policy` for a JSON API that serves no documents.
+const DEFAULT_CSP: &str = "default-src 'none'; frame-ancestors 'none'";
+
+impl HttpConfig {
+    /// Creates an `HttpConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let body_limit = config.byte_size("HTTP_BODY_LIMIT", 2 * 1024 * 1024);
+        if body_limit == 0 {
+            config.invalid("HTTP_BODY_LIMIT", "must be greater than zero");
+        }
+
+        Self {
+            cors: CorsConfig::from_config(config),
+            compression: CompressionConfig::from_config(config),
+            timeout: TimeoutConfig {
+                duration: config.duration("HTTP_REQUEST_TIMEOUT", Duration::from_secs(30)),
+                status: config.enumeration("HTTP_TIMEOUT_STATUS", TimeoutStatus::RequestTimeout),
+            },
+            body_limit,
+            security_headers: SecurityHeadersConfig::from_config(config),
+        }
+    }
+}
+
+impl CorsConfig {
+    fn from_config(config: &mut ConfigReader) -> Self {
+        let origins = config.list("CORS_ALLOWED_ORIGINS", "");
+        let any_origin = origins.iter().any(|o| o == "*");
+        let allowed_origins = if any_origin {
+            Vec::new()
+        } else {
+            parse_each(config, "CORS_ALLOWED_ORIGINS", &origins, "origin")
+        };
+
+        let methods = config.list("CORS_ALLOWED_METHODS", "GET,POST,PUT,PATCH,DELETE,OPTIONS");
+        let allowed_methods = parse_each(config, "CORS_ALLOWED_METHODS", &methods, "method");
+        let headers = config.list(
+            "CORS_ALLOWED_HEADERS",
+            "authorization,content-type,idempotency-key,if-match,if-none-match,x-request-id",
+        );
+        let allowed_headers = parse_each(config, "CORS_ALLOWED_HEADERS", &headers, "header");
+        let exposed = config.list(
+            "CORS_EXPOSED_HEADERS",
+            "etag,idempotent-replayed,x-request-id",
+        );
+        let exposed_headers = parse_each(config, "CORS_EXPOSED_HEADERS", &exposed, "header");
+
+        let allow_credentials = config.bool("CORS_ALLOW_CREDENTIALS", false);
+        if allow_credentials && any_origin {
+            config.invalid(
+                "CORS_ALLOW_CREDENTIALS",
+                "cannot be combined with CORS_ALLOWED_ORIGINS=*",
+            );
+        }
+
+        Self {
+            allowed_origins,
+            any_origin,
+            allowed_methods,
+            allowed_headers,
+            exposed_headers,
+            allow_credentials,
+            max_age: config.duration("CORS_MAX_AGE", Duration::from_secs(3600)),
+        }
+    }
+}
+
+impl CompressionConfig {
+    fn from_config(config: &mut ConfigReader) -> Self {
+        let names = config.list("HTTP_COMPRESSION", "gzip,br,zstd");
+        let algorithms = if names.iter().any(|n| n.eq_ignore_ascii_case("none")) {
+            Vec::new()
+        } else {
+            names
+                .iter()
+                .filter_map(|name| {
+                    let algorithm = env::parse_enum::<CompressionAlgorithm>(name);
+                    if algorithm.is_none() {
+                        config.invalid(
+                            "HTTP_COMPRESSION",
+                            format!(
+                                "unknown algorithm '{name}', expected one of: {} or none",
+                                CompressionAlgorithm::names().join(", ")
+                            ),
+                        );
+                    }
+                    algorithm
+                })
+                .collect()
+        };
+
+        let min_size = config.byte_size("HTTP_COMPRESSION_MIN_SIZE", 1024);
+        let min_size = u16::try_from(min_size).unwrap_or_else(|_| {
+            config.invalid("HTTP_COMPRESSION_MIN_SIZE", "must be at most 65535 bytes");
+            u16::MAX
+        });
+
+        Self {
+            algorithms,
+            min_size,
+            decompress_requests: config.bool("HTTP_DECOMPRESSION", true),
+        }
+    }
+}
+
+impl SecurityHeadersConfig {
+    fn from_config(config: &mut ConfigReader) -> Self {
+        let csp = config.string("SECURITY_CSP", DEFAULT_CSP);
+        let content_security_policy = HeaderValue::from_str(&csp).unwrap_or_else(|_| {
+            config.invalid("SECURITY_CSP", "is not a valid header value");
+            HeaderValue::from_static(DEFAULT_CSP)
+        });
+
+        Self {
+            enabled: config.bool("SECURITY_HEADERS_ENABLED", true),
+            content_security_policy,
+            hsts_max_age: config.duration("SECURITY_HSTS_MAX_AGE", Duration::from_secs(31_536_000)),
+            hsts_include_subdomains: config.bool("SECURITY_HSTS_INCLUDE_SUBDOMAINS", true),
+        }
+    }
+}
+
+/// Parses every entry of a list setting, recording an issue for each invalid one.
+fn parse_each<T: FromStr>(
+    config: &mut ConfigReader,
+    key: &str,
+    values: &[String],
+    what: &str,
+) -> Vec<T> {
+    values
+        .iter()
+        .filter_map(|value| {
+            let parsed = value.parse().ok();
+            if parsed.is_none() {
+                config.invalid(key, format!("'{value}' is not a valid {what}"));
+            }
+            parsed
+        })
+        .collect()
+}
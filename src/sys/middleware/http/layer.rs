@@ -0,0 +1,201 @@
+use super::models::{
+    CompressionAlgorithm, CompressionConfig, CorsConfig, HttpConfig, TimeoutConfig, TimeoutStatus,
+};
+use crate::{AppError, sys::config::ConfigHandle};
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Request, State},
+    http::{HeaderName, HeaderValue, StatusCode, header},
+    middleware::{Next, from_fn_with_state, map_response_with_state},
+    response::{IntoResponse, Response},
+};
+use std::{sync::Arc, time::Duration};
+use tower::{Layer, ServiceExt};
+use tower_http::{
+    compression::{
+        CompressionLayer, DefaultPredicate, Predicate,
+        predicate::{And, SizeAbove},
+    },
+    cors::{AllowOrigin, CorsLayer},
+    decompression::RequestDecompressionLayer,
+    limit::RequestBodyLimitLayer,
+    set_header::SetResponseHeaderLayer,
+};
+
+/// Wraps every route registered on `router` with CORS, compression, body
+/// limit, timeout and security header layers.
+///
+/// The stack is built from the current configuration, except that allowed
+/// CORS origins are looked up on every request so reloads apply to them,
+/// including reloads that enable or disable CORS.
+pub fn http_layers<S>(router: Router<S>, handle: &Arc<ConfigHandle>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let current = handle.load();
+    let config: &HttpConfig = &current.http;
+    let mut router = router;
+
+    if !config.timeout.duration.is_zero() {
+        router = router.layer(from_fn_with_state(config.timeout, request_timeout));
+    }
+
+    // Axum's own 2 MB extractor limit is replaced by the configured one
+    let body_limit = usize::try_from(config.body_limit).unwrap_or(usize::MAX);
+    router = router
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(body_limit))
+        .layer(map_response_with_state(
+            config.body_limit,
+            payload_too_large,
+        ));
+
+    let compression = &config.compression;
+    if compression.decompress_requests && !compression.algorithms.is_empty() {
+        router = router.layer(
+            RequestDecompressionLayer::new()
+                .gzip(compression.has(CompressionAlgorithm::Gzip))
+                .br(compression.has(CompressionAlgorithm::Brotli))
+                .zstd(compression.has(CompressionAlgorithm::Zstd))
+                .no_deflate(),
+        );
+    }
+    if !compression.algorithms.is_empty() {
+        router = router.layer(compression_layer(compression));
+    }
+
+    router = router.layer(from_fn_with_state(
+        LiveCors {
+            layer: cors_layer(&config.cors, handle.clone()),
+            handle: handle.clone(),
+        },
+        live_cors,
+    ));
+
+    let security = &config.security_headers;
+    if security.enabled {
+        router = router
+            .layer(set_if_missing(
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ))
+            .layer(set_if_missing(
+                header::X_FRAME_OPTIONS,
+                HeaderValue::from_static("DENY"),
+            ))
+            .layer(set_if_missing(
+                header::REFERRER_POLICY,
+                HeaderValue::from_static("no-referrer"),
+            ))
+            .layer(set_if_missing(
+                header::CONTENT_SECURITY_POLICY,
+                security.content_security_policy.clone(),
+            ));
+        // Browsers ignore HSTS over plain HTTP, and a proxy terminating TLS
+        // sets its own
+        if let Some(hsts) = security.hsts().filter(|_| current.tls.enabled) {
+            router = router.layer(set_if_missing(header::STRICT_TRANSPORT_SECURITY, hsts));
+        }
+    }
+
+    router
+}
+
+fn compression_layer(
+    config: &CompressionConfig,
+) -> CompressionLayer<And<DefaultPredicate, SizeAbove>> {
+    CompressionLayer::new()
+        .gzip(config.has(CompressionAlgorithm::Gzip))
+        .br(config.has(CompressionAlgorithm::Brotli))
+        .zstd(config.has(CompressionAlgorithm::Zstd))
+        .no_deflate()
+        .compress_when(DefaultPredicate::new().and(SizeAbove::new(config.min_size)))
+}
+
+fn cors_layer(config: &CorsConfig, handle: Arc<ConfigHandle>) -> CorsLayer {
+    // Credentials are fixed at startup, so any origin is only allowed without
+    let allow_credentials = config.allow_credentials;
+    let origin = AllowOrigin::predicate(move |origin, _| {
+        let cors = &handle.load().http.cors;
+        (cors.any_origin && !allow_credentials) || cors.allowed_origins.contains(origin)
+    });
+
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(config.allowed_methods.clone())
+        .allow_headers(config.allowed_headers.clone())
+        .expose_headers(config.exposed_headers.clone())
+        .allow_credentials(config.allow_credentials)
+        .max_age(config.max_age)
+}
+
+/// The CORS layer with the configuration deciding whether it applies.
+#[derive(Clone)]
+struct LiveCors {
+    layer: CorsLayer,
+    handle: Arc<ConfigHandle>,
+}
+
+/// Middleware that applies CORS while the live configuration allows any
+/// origin, and passes requests through untouched otherwise.
+async fn live_cors(State(cors): State<LiveCors>, request: Request, next: Next) -> Response {
+    if !cors.handle.load().http.cors.is_enabled() {
+        return next.run(request).await;
+    }
+    match cors.layer.layer(next).oneshot(request).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+fn set_if_missing(name: HeaderName, value: HeaderValue) -> SetResponseHeaderLayer<HeaderValue> {
+    SetResponseHeaderLayer::if_not_present(name, value)
+}
+
+/// Middleware that fails requests taking longer than the configured timeout.
+pub async fn request_timeout(
+    State(config): State<TimeoutConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match tokio::time::timeout(config.duration, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            let message = format!(
+                "The request did not complete within {}",
+                format_duration(config.duration)
+            );
+            match config.status {
+                TimeoutStatus::RequestTimeout => AppError::RequestTimeout(message),
+                TimeoutStatus::GatewayTimeout => AppError::GatewayTimeout(message),
+            }
+            .into_response()
+        }
+    }
+}
+
+/// Rewrites plain-text 413 responses from the body limit layer and body
+/// extractors into the JSON error format.
+pub async fn payload_too_large(State(limit): State<u64>, response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return AppError::PayloadTooLarge(format!(
+            "The request body exceeds the limit of {limit} bytes"
+        ))
+        .into_response();
+    }
+    response
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
@@ -0,0 +1,8 @@
+mod config;
+mod layer;
+mod models;
+pub use layer::{http_layers, payload_too_large, request_timeout};
+pub use models::{
+    CompressionAlgorithm, CompressionConfig, CorsConfig, HttpConfig, SecurityHeadersConfig,
+    TimeoutConfig, TimeoutStatus,
+};
@@ -0,0 +1,118 @@
+use crate::sys::env::EnvEnum;
+use axum::http::{HeaderName, HeaderValue, Method};
+use std::time::Duration;
+
+/// Settings for the standard HTTP middleware stack.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub cors: CorsConfig,
+    pub compression: CompressionConfig,
+    pub timeout: TimeoutConfig,
+    /// Maximum request body size in bytes, after decompression.
+    pub body_limit: u64,
+    pub security_headers: SecurityHeadersConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to make cross-origin requests. CORS is disabled when
+    /// empty; `*` allows any origin.
+    pub allowed_origins: Vec<HeaderValue>,
+    pub any_origin: bool,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    pub exposed_headers: Vec<HeaderName>,
+    pub allow_credentials: bool,
+    pub max_age: Duration,
+}
+
+impl CorsConfig {
+    /// Returns true if cross-origin requests are allowed from any origin.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.any_origin || !self.allowed_origins.is_empty()
+    }
+}
+
+/// A content coding used for response compression and request decompression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+impl EnvEnum for CompressionAlgorithm {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("gzip", Self::Gzip),
+        ("br", Self::Brotli),
+        ("brotli", Self::Brotli),
+        ("zstd", Self::Zstd),
+    ];
+}
+
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    /// Algorithms offered for responses. Compression is disabled when empty.
+    pub algorithms: Vec<CompressionAlgorithm>,
+    /// Responses smaller than this many bytes are sent uncompressed.
+    pub min_size: u16,
+    /// Accept request bodies encoded with any of `algorithms`.
+    pub decompress_requests: bool,
+}
+
+impl CompressionConfig {
+    #[must_use]
+    pub fn has(&self, algorithm: CompressionAlgorithm) -> bool {
+        self.algorithms.contains(&algorithm)
+    }
+}
+
+/// The status returned when a request exceeds its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutStatus {
+    /// 408, the request took too long.
+    RequestTimeout,
+    /// 504, for deployments where the application acts as a gateway.
+    GatewayTimeout,
+}
+
+impl EnvEnum for TimeoutStatus {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("408", Self::RequestTimeout),
+        ("request_timeout", Self::RequestTimeout),
+        ("504", Self::GatewayTimeout),
+        ("gateway_timeout", Self::GatewayTimeout),
+    ];
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutConfig {
+    /// Maximum time to produce a response. Disabled when zero.
+    pub duration: Duration,
+    pub status: TimeoutStatus,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    pub content_security_policy: HeaderValue,
+    /// `max-age` of `Strict-Transport-Security`. Not sent when zero.
+    pub hsts_max_age: Duration,
+    pub hsts_include_subdomains: bool,
+}
+
+impl SecurityHeadersConfig {
+    /// Returns the `Strict-Transport-Security` value, if HSTS is enabled.
+    #[must_use]
+    pub fn hsts(&self) -> Option<HeaderValue> {
+        if self.hsts_max_age.is_zero() {
+            return None;
+        }
+        let mut value = format!("max-age={}", self.hsts_max_age.as_secs());
+        if self.hsts_include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        HeaderValue::from_str(&value).ok()
+    }
+}
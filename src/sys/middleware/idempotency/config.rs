@@ -0,0 +1,28 @@
+use super::models::IdempotencyConfig;
+use crate::sys::config::ConfigReader;
+use std::time::Duration;
+
+impl IdempotencyConfig {
+    /// Creates an `IdempotencyConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let ttl = config.duration("IDEMPOTENCY_TTL", Duration::from_secs(24 * 3600));
+        let lock_timeout = config.duration("IDEMPOTENCY_LOCK_TIMEOUT", Duration::from_secs(60));
+        if lock_timeout >= ttl {
+            config.invalid(
+                "IDEMPOTENCY_LOCK_TIMEOUT",
+                "must be shorter than IDEMPOTENCY_TTL",
+            );
+        }
+
+        Self {
+            enabled: config.bool("IDEMPOTENCY_ENABLED", true),
+            ttl,
+            lock_timeout,
+            max_response_size: config.byte_size("IDEMPOTENCY_MAX_RESPONSE_SIZE", 1024 * 1024),
+            database: config
+                .optional("IDEMPOTENCY_DATABASE")
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+}
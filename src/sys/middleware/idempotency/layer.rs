@@ -0,0 +1,191 @@
+use super::models::{Begin, StoredResponse};
+use crate::{AppError, sys::config::state::AppState};
+use axum::{
+    body::{Body, HttpBody, to_bytes},
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use http_body_util::LengthLimitError;
+use sha2::{Digest, Sha256};
+use std::{error::Error as _, sync::Arc};
+use tracing::{debug, warn};
+
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Set on responses replayed from a stored result.
+const REPLAYED: &str = "idempotent-replayed";
+
+/// Longest accepted `Idempotency-Key`.
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Response headers that describe one transmission and are not replayed.
+const SKIPPED_HEADERS: [&str; 6] = [
+    "x-request-id",
+    "date",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "retry-after",
+];
+
+/// Middleware that makes POST and PATCH requests carrying an
+/// `Idempotency-Key` header safe to retry.
+///
+/// The first response is stored and replayed for repeats of the same
+/// request. Repeats fail with 409 while the first request is still running
+/// and with 422 if the key is reused for a different request. Server errors
+/// are not stored, so the request can be retried.
+pub async fn idempotency(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = state.config.load().idempotency.clone();
+    let is_unsafe = matches!(*request.method(), Method::POST | Method::PATCH);
+    let key = request
+        .headers()
+        .get(IDEMPOTENCY_KEY)
+        .map(|v| v.to_str().map(str::to_string));
+    let (true, true, Some(key)) = (config.enabled, is_unsafe, key) else {
+        return next.run(request).await;
+    };
+    let key = match key {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key,
+        _ => {
+            return AppError::BadRequest(format!(
+                "Idempotency-Key must be 1 to {MAX_KEY_LENGTH} visible ASCII characters"
+            ))
+            .into_response();
+        }
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) if e.source().is_some_and(|s| s.is::<LengthLimitError>()) => {
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+        }
+        Err(e) => {
+            return AppError::BadRequest(format!("Failed to read the request body: {e}"))
+                .into_response();
+        }
+    };
+
+    // Keys are scoped to the caller so clients cannot collide with each other
+    let caller = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .map_or(&[][..], HeaderValue::as_bytes);
+    let id = hash(&[caller, key.as_bytes()]);
+    let fingerprint = hash(&[
+        parts.method.as_str().as_bytes(),
+        parts.uri.to_string().as_bytes(),
+        &body,
+    ]);
+
+    let store = &state.idempotency;
+    match store
+        .begin(&id, &fingerprint, config.ttl, config.lock_timeout)
+        .await
+    {
+        Ok(Begin::Started) => {}
+        Ok(Begin::InProgress) => {
+            return AppError::Conflict(
+                "A request with this Idempotency-Key is still being processed".to_string(),
+            )
+            .into_response();
+        }
+        Ok(Begin::Mismatch) => {
+            return AppError::UnprocessableEntity(
+                "This Idempotency-Key was already used for a different request".to_string(),
+            )
+            .into_response();
+        }
+        Ok(Begin::Completed(stored)) => {
+            debug!(path = %parts.uri.path(), "Replaying stored response for Idempotency-Key");
+            return replay(stored);
+        }
+        Err(e) => {
+            warn!(error = %e, "Idempotency store failed");
+            return AppError::ServiceUnavailable(
+                "Idempotency-Key could not be checked, retry later".to_string(),
+            )
+            .into_response();
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    let storable = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= config.max_response_size);
+    if response.status().is_server_error() || !storable {
+        if let Err(e) = store.release(&id).await {
+            warn!(error = %e, "Failed to release Idempotency-Key");
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response for Idempotency-Key");
+            if let Err(e) = store.release(&id).await {
+                warn!(error = %e, "Failed to release Idempotency-Key");
+            }
+            return AppError::ServerError("Failed to produce the response".to_string())
+                .into_response();
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        headers: parts
+            .headers
+            .iter()
+            .filter(|(name, _)| {
+                !SKIPPED_HEADERS.contains(&name.as_str())
+                    && !name.as_str().starts_with("ratelimit-")
+            })
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: STANDARD.encode(&body),
+    };
+    if let Err(e) = store.complete(&id, stored, config.ttl).await {
+        warn!(error = %e, "Failed to store response for Idempotency-Key");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let body = STANDARD.decode(&stored.body).unwrap_or_default();
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+
+    let headers = response.headers_mut();
+    for (name, value) in stored.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            headers.append(name, value);
+        }
+    }
+    headers.insert(REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// Hex SHA-256 of the parts, each prefixed by its length so that different
+/// splits of the same bytes hash differently.
+fn hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hex::encode(hasher.finalize())
+}
@@ -0,0 +1,7 @@
+mod config;
+mod layer;
+mod models;
+mod store;
+pub use layer::idempotency;
+pub use models::{Begin, IdempotencyConfig, IdempotencyRecord, StoredResponse};
+pub use store::IdempotencyStore;
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyConfig {
+    pub enabled: bool,
+    /// How long a key and its stored response are kept.
+    pub ttl: Duration,
+    /// How long a request may hold a key before a retry can take it over,
+    /// for example after the instance processing it crashed.
+    pub lock_timeout: Duration,
+    /// Responses larger than this are not stored and the key is released.
+    pub max_response_size: u64,
+    /// Named database for stored responses, the primary one when `None`.
+    pub database: Option<String>,
+}
+
+/// A response stored for replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Base64 encoded body.
+    pub body: String,
+}
+
+/// A row of the `idempotency` table. Times are milliseconds since the Unix
+/// epoch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Hash of the method, URI and body of the first request.
+    pub fingerprint: String,
+    pub completed: bool,
+    pub response: Option<StoredResponse>,
+    pub locked_at: i64,
+    pub expires_at: i64,
+}
+
+/// The state of a key when a request using it arrives.
+#[derive(Debug)]
+pub enum Begin {
+    /// The key is new (or was taken over), the request should run.
+    Started,
+    /// Another request with the key is still being processed.
+    InProgress,
+    /// The key was used for the same request, which already completed.
+    Completed(StoredResponse),
+    /// The key was used for a different request.
+    Mismatch,
+}
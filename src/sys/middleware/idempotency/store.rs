@@ -0,0 +1,175 @@
+use super::models::{Begin, IdempotencyRecord, StoredResponse};
+use crate::{
+    dbs::{
+        error::{DatabaseError, is_duplicate},
+        models::DbConnection,
+    },
+    sys::server::Shutdown,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Attempts before giving up when concurrent requests race on a key.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Keys and stored responses in the `idempotency` table.
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    db: DbConnection,
+}
+
+impl IdempotencyStore {
+    /// Creates the store and starts a task that deletes expired keys until
+    /// shutdown.
+    #[must_use]
+    pub fn new(db: DbConnection, sweep_interval: Duration, shutdown: Shutdown) -> Self {
+        let sweeper = db.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sweep_interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    () = shutdown.triggered() => break,
+                }
+                if let Err(e) = sweeper
+                    .query("DELETE idempotency WHERE expires_at < $now")
+                    .bind(("now", now_millis()))
+                    .await
+                {
+                    warn!(error = %e, "Failed to delete expired idempotency keys");
+                }
+            }
+        });
+
+        Self { db }
+    }
+
+    /// Claims `id` for a request with `fingerprint`, or reports why it
+    /// cannot run.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the table cannot be queried, or if the key
+    /// cannot be created for any reason other than already existing.
+    pub async fn begin(
+        &self,
+        id: &str,
+        fingerprint: &str,
+        ttl: Duration,
+        lock_timeout: Duration,
+    ) -> Result<Begin, DatabaseError> {
+        for _ in 0..MAX_ATTEMPTS {
+            let now = now_millis();
+            let record = IdempotencyRecord {
+                fingerprint: fingerprint.to_string(),
+                completed: false,
+                response: None,
+                locked_at: now,
+                expires_at: now + millis(ttl),
+            };
+            // Creating fails if the key exists, so only one request claims it
+            let created = self
+                .db
+                .query("CREATE type::thing('idempotency', $id) CONTENT $record RETURN NONE")
+                .bind(("id", id.to_string()))
+                .bind(("record", record))
+                .await?
+                .check();
+            match created {
+                Ok(_) => return Ok(Begin::Started),
+                Err(e) if is_duplicate(&e) => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            let existing: Option<IdempotencyRecord> = self
+                .db
+                .query("SELECT * FROM type::thing('idempotency', $id)")
+                .bind(("id", id.to_string()))
+                .await?
+                .take(0)?;
+            let Some(existing) = existing else {
+                continue;
+            };
+
+            if existing.expires_at <= now {
+                self.release(id).await?;
+                continue;
+            }
+            if existing.fingerprint != fingerprint {
+                return Ok(Begin::Mismatch);
+            }
+            if let (true, Some(response)) = (existing.completed, existing.response) {
+                return Ok(Begin::Completed(response));
+            }
+            if now - existing.locked_at < millis(lock_timeout) {
+                return Ok(Begin::InProgress);
+            }
+
+            // The request holding the key never finished, take it over
+            let claimed: Option<IdempotencyRecord> = self
+                .db
+                .query(
+                    "UPDATE type::thing('idempotency', $id) SET locked_at = $now \
+                     WHERE completed = false AND locked_at = $locked_at",
+                )
+                .bind(("id", id.to_string()))
+                .bind(("now", now))
+                .bind(("locked_at", existing.locked_at))
+                .await?
+                .take(0)?;
+            if claimed.is_some() {
+                return Ok(Begin::Started);
+            }
+        }
+
+        Ok(Begin::InProgress)
+    }
+
+    /// Stores the response of the request holding `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the response cannot be stored.
+    pub async fn complete(
+        &self,
+        id: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "UPDATE type::thing('idempotency', $id) \
+                 SET completed = true, response = $response, expires_at = $expires_at",
+            )
+            .bind(("id", id.to_string()))
+            .bind(("response", response))
+            .bind(("expires_at", now_millis() + millis(ttl)))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Deletes `id` so the request can be retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the key cannot be deleted.
+    pub async fn release(&self, id: &str) -> Result<(), DatabaseError> {
+        self.db
+            .query("DELETE type::thing('idempotency', $id)")
+            .bind(("id", id.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, millis)
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,42 @@
+use super::models::LoadShedConfig;
+use crate::sys::{config::ConfigReader, middleware::route_pattern::RoutePattern};
+use std::time::Duration;
+
+impl LoadShedConfig {
+    /// Creates a `LoadShedConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let max_in_flight: usize = config.parsed("LOAD_SHED_MAX_IN_FLIGHT", 512);
+        if max_in_flight == 0 {
+            config.invalid("LOAD_SHED_MAX_IN_FLIGHT", "must be greater than zero");
+        }
+
+        let route_limits = config
+            .list("LOAD_SHED_ROUTE_LIMITS", "")
+            .iter()
+            .filter_map(|entry| {
+                let limit = RoutePattern::parse_entry(entry, |v| {
+                    v.parse::<usize>().ok().filter(|&l| l > 0)
+                });
+                if limit.is_none() {
+                    config.invalid(
+                        "LOAD_SHED_ROUTE_LIMITS",
+                        format!("'{entry}' is not an entry such as 'POST /reports=4'"),
+                    );
+                }
+                limit
+            })
+            .collect();
+
+        Self {
+            enabled: config.bool("LOAD_SHED_ENABLED", true),
+            max_in_flight: max_in_flight.max(1),
+            route_limits,
+            max_queue: config.parsed("LOAD_SHED_MAX_QUEUE", 1024),
+            queue_timeout: config.duration("LOAD_SHED_QUEUE_TIMEOUT", Duration::from_secs(2)),
+            latency_threshold: config
+                .duration("LOAD_SHED_LATENCY_THRESHOLD", Duration::from_secs(5)),
+            retry_after: config.duration("LOAD_SHED_RETRY_AFTER", Duration::from_secs(5)),
+            exclude_paths: config.list("LOAD_SHED_EXCLUDE_PATHS", "/health,/metrics"),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{AppError, sys::config::state::AppState};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Middleware that enforces concurrency limits and sheds load with 503 and
+/// `Retry-After` when the service is overloaded.
+pub async fn load_shed(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let shedder = &state.load_shedder;
+    let config = shedder.config();
+    let path = request.uri().path();
+    if !config.enabled || config.is_excluded(path) {
+        return next.run(request).await;
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+    let admission = match shedder.admit(request.method().as_str(), route, path).await {
+        Ok(admission) => admission,
+        Err(reason) => {
+            warn!(
+                path = %path,
+                reason = reason.as_str(),
+                in_flight = shedder.in_flight(),
+                queued = shedder.queued(),
+                "Shedding request"
+            );
+            let mut response =
+                AppError::ServiceUnavailable(format!("The service is overloaded: {reason}"))
+                    .into_response();
+            let retry_after = config.retry_after.as_secs().max(1);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            return response;
+        }
+    };
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    shedder.record_latency(start.elapsed());
+    drop(admission);
+
+    response
+}
@@ -0,0 +1,7 @@
+mod config;
+mod layer;
+mod models;
+mod shedder;
+pub use layer::load_shed;
+pub use models::{LoadShedConfig, ShedReason};
+pub use shedder::{Admission, LoadShedder};
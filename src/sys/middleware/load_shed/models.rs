@@ -0,0 +1,62 @@
+use crate::sys::middleware::route_pattern::{self, RoutePattern};
+use std::{fmt, time::Duration};
+
+#[derive(Debug, Clone)]
+pub struct LoadShedConfig {
+    pub enabled: bool,
+    /// Requests processed at once across all routes.
+    pub max_in_flight: usize,
+    /// Lower concurrency limits for specific routes.
+    pub route_limits: Vec<(RoutePattern, usize)>,
+    /// Requests allowed to wait for a free slot; more are rejected at once.
+    pub max_queue: usize,
+    /// Longest a request waits for a free slot before it is rejected.
+    pub queue_timeout: Duration,
+    /// Average latency above which new requests are increasingly shed.
+    /// Disabled when zero.
+    pub latency_threshold: Duration,
+    /// Value of `Retry-After` on rejected requests.
+    pub retry_after: Duration,
+    /// Paths that are never limited. Entries ending in `*` match by prefix.
+    pub exclude_paths: Vec<String>,
+}
+
+impl LoadShedConfig {
+    /// Returns true if requests to `path` are never limited.
+    #[must_use]
+    pub fn is_excluded(&self, path: &str) -> bool {
+        route_pattern::is_excluded(&self.exclude_paths, path)
+    }
+}
+
+/// Why a request was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShedReason {
+    /// Too many requests were already waiting for a slot.
+    QueueFull,
+    /// No slot became free within the queue timeout.
+    QueueTimeout,
+    /// Measured latency is above the threshold.
+    Latency,
+}
+
+impl ShedReason {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::QueueFull => "queue_full",
+            Self::QueueTimeout => "queue_timeout",
+            Self::Latency => "latency",
+        }
+    }
+}
+
+impl fmt::Display for ShedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => write!(f, "too many requests are waiting"),
+            Self::QueueTimeout => write!(f, "no capacity became available in time"),
+            Self::Latency => write!(f, "response times are above the threshold"),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use super::models::{LoadShedConfig, ShedReason};
+use crate::sys::{
+    health::models::{ComponentHealth, HealthCheck, HealthStatus},
+    metrics::{Gauge, MetricsRegistry},
+    middleware::route_pattern::RoutePattern,
+};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::{Instant, timeout_at},
+};
+
+/// Weight of the latest sample in the latency moving average.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// How long the component stays degraded after shedding a request.
+const DEGRADED_WINDOW: Duration = Duration::from_secs(30);
+
+/// Admits requests while capacity is available and sheds them when the
+/// service is overloaded.
+///
+/// Limits are fixed when the shedder is created; changes need a restart.
+#[derive(Clone)]
+pub struct LoadShedder {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    config: LoadShedConfig,
+    global: Arc<Semaphore>,
+    routes: Vec<(RoutePattern, Arc<Semaphore>)>,
+    queued: AtomicUsize,
+    /// Exponential moving average of request latency in microseconds.
+    latency_us: AtomicU64,
+    /// Milliseconds after `started` at which a request was last shed.
+    last_shed_ms: AtomicU64,
+    started: Instant,
+    metrics: Arc<MetricsRegistry>,
+    queued_gauge: Gauge,
+    latency_gauge: Gauge,
+}
+
+/// Slots held by an admitted request, released when dropped.
+pub struct Admission {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+impl LoadShedder {
+    #[must_use]
+    pub fn new(config: LoadShedConfig, metrics: Arc<MetricsRegistry>) -> Self {
+        let routes = config
+            .route_limits
+            .iter()
+            .map(|(route, limit)| (route.clone(), Arc::new(Semaphore::new(*limit))))
+            .collect();
+        let queued_gauge = metrics.gauge(
+            "load_shed_queued",
+            "Requests waiting for a concurrency slot",
+            &[],
+        );
+        let latency_gauge = metrics.gauge(
+            "load_shed_latency_ms",
+            "Moving average of request latency in milliseconds",
+            &[],
+        );
+
+        Self {
+            inner: Arc::new(Inner {
+                global: Arc::new(Semaphore::new(config.max_in_flight)),
+                routes,
+                config,
+                queued: AtomicUsize::new(0),
+                latency_us: AtomicU64::new(0),
+                last_shed_ms: AtomicU64::new(u64::MAX),
+                started: Instant::now(),
+                metrics,
+                queued_gauge,
+                latency_gauge,
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &LoadShedConfig {
+        &self.inner.config
+    }
+
+    /// Waits for a slot for a request, or returns why it must be rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `ShedReason` if the request should be rejected.
+    pub async fn admit(
+        &self,
+        method: &str,
+        route: Option<&str>,
+        path: &str,
+    ) -> Result<Admission, ShedReason> {
+        let inner = &self.inner;
+        if self.should_shed_for_latency() {
+            return Err(self.shed(ShedReason::Latency));
+        }
+
+        let deadline = Instant::now() + inner.config.queue_timeout;
+        let route_limit = inner
+            .routes
+            .iter()
+            .find(|(pattern, _)| pattern.matches(method, route, path))
+            .map(|(_, semaphore)| semaphore.clone());
+
+        let mut permits = Vec::with_capacity(2);
+        for semaphore in route_limit.into_iter().chain([inner.global.clone()]) {
+            permits.push(self.acquire(semaphore, deadline).await?);
+        }
+        Ok(Admission { _permits: permits })
+    }
+
+    async fn acquire(
+        &self,
+        semaphore: Arc<Semaphore>,
+        deadline: Instant,
+    ) -> Result<OwnedSemaphorePermit, ShedReason> {
+        let inner = &self.inner;
+        if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        if inner.queued.fetch_add(1, Ordering::Relaxed) >= inner.config.max_queue {
+            inner.queued.fetch_sub(1, Ordering::Relaxed);
+            return Err(self.shed(ShedReason::QueueFull));
+        }
+        inner.queued_gauge.inc();
+        let permit = timeout_at(deadline, semaphore.acquire_owned()).await;
+        inner.queued.fetch_sub(1, Ordering::Relaxed);
+        inner.queued_gauge.dec();
+
+        match permit {
+            Ok(Ok(permit)) => Ok(permit),
+            // The semaphore is never closed, so only the timeout can fail
+            _ => Err(self.shed(ShedReason::QueueTimeout)),
+        }
+    }
+
+    /// Sheds a share of new requests that grows with how far the average
+    /// latency exceeds the threshold. Requests are always admitted when
+    /// none are in flight, so the average can recover.
+    fn should_shed_for_latency(&self) -> bool {
+        let threshold = self.inner.config.latency_threshold;
+        if threshold.is_zero() || self.in_flight() == 0 {
+            return false;
+        }
+        let latency = self.latency();
+        if latency <= threshold {
+            return false;
+        }
+        let overshoot = (latency.as_secs_f64() - threshold.as_secs_f64()) / threshold.as_secs_f64();
+        rand::random::<f64>() < overshoot.min(1.0)
+    }
+
+    fn shed(&self, reason: ShedReason) -> ShedReason {
+        let inner = &self.inner;
+        let elapsed = u64::try_from(inner.started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        inner.last_shed_ms.store(elapsed, Ordering::Relaxed);
+        inner
+            .metrics
+            .counter(
+                "load_shed_rejected_total",
+                "Requests rejected because the service is overloaded, by reason",
+                &[("reason", reason.as_str())],
+            )
+            .inc();
+        reason
+    }
+
+    /// Records the latency of a completed request.
+    pub fn record_latency(&self, latency: Duration) {
+        let sample = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let update = |current: u64| {
+            #[allow(
+                clippy::cast_precision_loss,
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss
+            )]
+            let next = if current == 0 {
+                sample
+            } else {
+                (LATENCY_SMOOTHING * sample as f64 + (1.0 - LATENCY_SMOOTHING) * current as f64)
+                    as u64
+            };
+            Some(next)
+        };
+        if let Ok(previous) =
+            self.inner
+                .latency_us
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, update)
+        {
+            let current = update(previous).unwrap_or(previous);
+            self.inner
+                .latency_gauge
+                .set(i64::try_from(current / 1000).unwrap_or(i64::MAX));
+        }
+    }
+
+    /// The moving average of request latency.
+    #[must_use]
+    pub fn latency(&self) -> Duration {
+        Duration::from_micros(self.inner.latency_us.load(Ordering::Relaxed))
+    }
+
+    /// Requests currently holding a global slot.
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.inner.config.max_in_flight - self.inner.global.available_permits()
+    }
+
+    /// Requests currently waiting for a slot.
+    #[must_use]
+    pub fn queued(&self) -> usize {
+        self.inner.queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if a request was shed within the degraded window.
+    #[must_use]
+    pub fn recently_shed(&self) -> bool {
+        let last = self.inner.last_shed_ms.load(Ordering::Relaxed);
+        let elapsed = u64::try_from(self.inner.started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        last != u64::MAX
+            && elapsed.saturating_sub(last)
+                < u64::try_from(DEGRADED_WINDOW.as_millis()).unwrap_or(u64::MAX)
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for LoadShedder {
+    /// Reports degraded while requests are being shed or responses are slow.
+    async fn check(&self) -> ComponentHealth {
+        let threshold = self.inner.config.latency_threshold;
+        let slow = !threshold.is_zero() && self.latency() > threshold;
+        let status = if self.recently_shed() || slow {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+
+        ComponentHealth {
+            name: "Load".to_string(),
+            status,
+            message: Some(format!(
+                "In flight: {}/{}, queued: {}, average latency: {}ms{}",
+                self.in_flight(),
+                self.inner.config.max_in_flight,
+                self.queued(),
+                self.latency().as_millis(),
+                if self.recently_shed() {
+                    ", shedding requests"
+                } else {
+                    ""
+                }
+            )),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod access_log;
+pub mod catch_panic;
+pub mod conditional;
+pub mod http;
+pub mod idempotency;
+pub mod load_shed;
+pub mod rate_limit;
+pub mod route_pattern;
@@ -0,0 +1,40 @@
+use super::models::IpNet;
+use axum::{extract::ConnectInfo, http::Request};
+use std::net::{IpAddr, SocketAddr};
+
+/// Returns the IP address of the client that sent `request`.
+///
+/// `X-Forwarded-For` is only honoured when the peer is a trusted proxy (or a
+/// Unix socket, which only local proxies can reach). Entries are read from
+/// right to left and the first address that is not a trusted proxy is the
+/// client, so a client cannot spoof its address by sending the header itself.
+#[must_use]
+pub fn client_ip<B>(request: &Request<B>, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_canonical());
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+
+    if peer.is_some_and(|ip| !is_trusted(ip)) {
+        return peer;
+    }
+
+    let forwarded = request
+        .headers()
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|entry| entry.trim().parse::<IpAddr>().ok())
+        .map(|ip| ip.to_canonical())
+        .collect::<Vec<_>>();
+
+    forwarded
+        .iter()
+        .rev()
+        .find(|&&ip| !is_trusted(ip))
+        .or_else(|| forwarded.first())
+        .copied()
+        .or(peer)
+}
@@ -0,0 +1,89 @@
+use super::models::{IpNet, Quota, RateLimitConfig, RateLimitKey, RateLimitStoreKind, RouteQuota};
+use crate::sys::{
+    config::ConfigReader,
+    env::{self, EnvEnum},
+};
+use std::time::Duration;
+
+impl RateLimitConfig {
+    /// Creates a `RateLimitConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config.bool("RATE_LIMIT_ENABLED", false);
+
+        let default_quota = config.with(
+            "RATE_LIMIT_DEFAULT",
+            Quota {
+                limit: 100,
+                period: Duration::from_secs(60),
+            },
+            "a quota such as 100/1m",
+            Quota::parse,
+        );
+
+        let routes = config
+            .list("RATE_LIMIT_ROUTES", "")
+            .iter()
+            .filter_map(|entry| {
+                let route = RouteQuota::parse(entry);
+                if route.is_none() {
+                    config.invalid(
+                        "RATE_LIMIT_ROUTES",
+                        format!("'{entry}' is not an entry such as 'POST /login=5/1m'"),
+                    );
+                }
+                route
+            })
+            .collect();
+
+        let keys = config
+            .list("RATE_LIMIT_KEYS", "principal,ip")
+            .iter()
+            .filter_map(|name| {
+                let key = env::parse_enum::<RateLimitKey>(name);
+                if key.is_none() {
+                    config.invalid(
+                        "RATE_LIMIT_KEYS",
+                        format!(
+                            "unknown key '{name}', expected one of: {}",
+                            RateLimitKey::names().join(", ")
+                        ),
+                    );
+                }
+                key
+            })
+            .collect();
+
+        let trusted_proxies = config
+            .list("RATE_LIMIT_TRUSTED_PROXIES", "")
+            .iter()
+            .filter_map(|entry| {
+                let net = IpNet::parse(entry);
+                if net.is_none() {
+                    config.invalid(
+                        "RATE_LIMIT_TRUSTED_PROXIES",
+                        format!("'{entry}' is not an IP address or CIDR network"),
+                    );
+                }
+                net
+            })
+            .collect();
+
+        let store = config.enumeration("RATE_LIMIT_STORE", RateLimitStoreKind::Memory);
+        let database = config
+            .optional("RATE_LIMIT_DATABASE")
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty());
+
+        Self {
+            enabled,
+            default_quota,
+            routes,
+            keys,
+            api_key_header: config.string("RATE_LIMIT_API_KEY_HEADER", "x-api-key"),
+            trusted_proxies,
+            exclude_paths: config.list("RATE_LIMIT_EXCLUDE_PATHS", "/health,/metrics"),
+            store,
+            database,
+        }
+    }
+}
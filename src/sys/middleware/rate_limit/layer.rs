@@ -0,0 +1,138 @@
+use super::{
+    client_ip::client_ip,
+    models::{Decision, Quota, RateLimitConfig, RateLimitKey, VerifiedApiKey},
+};
+use crate::{
+    AppError,
+    sys::{config::state::AppState, middleware::access_log::UserId},
+};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Middleware that rejects clients exceeding their quota with 429.
+///
+/// Every response carries `RateLimit-Limit`, `RateLimit-Remaining`,
+/// `RateLimit-Reset` and `RateLimit-Policy`; rejections add `Retry-After`.
+/// If the store fails, the request is let through.
+pub async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = state.config.load();
+    let config = &config.rate_limit;
+    let path = request.uri().path();
+    if !config.enabled || config.is_excluded(path) {
+        return next.run(request).await;
+    }
+
+    let Some(client) = client_key(&request, config) else {
+        debug!(path = %path, "No rate limit key for request");
+        return next.run(request).await;
+    };
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+    let (quota, bucket) = config.quota_for(request.method().as_str(), route, path);
+    let key = format!("{bucket}:{client}");
+
+    let decision = match state.rate_limiter.check(&key, &quota).await {
+        Ok(decision) => decision,
+        Err(e) => {
+            warn!(error = %e, "Rate limit store failed, allowing request");
+            return next.run(request).await;
+        }
+    };
+
+    if !decision.allowed {
+        state
+            .metrics
+            .counter(
+                "rate_limit_rejected_total",
+                "Requests rejected by the rate limiter by quota",
+                &[("quota", &bucket)],
+            )
+            .inc();
+        let retry_after = decision.retry_after.unwrap_or_default();
+        let mut response = AppError::TooManyRequests(format!(
+            "Rate limit of {} requests per {}s exceeded, retry in {}s",
+            quota.limit,
+            quota.period.as_secs_f64(),
+            ceil_secs(retry_after)
+        ))
+        .into_response();
+        set_headers(response.headers_mut(), &quota, &decision);
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(ceil_secs(retry_after)),
+        );
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    set_headers(response.headers_mut(), &quota, &decision);
+    response
+}
+
+/// Returns the key identifying the client, from the first configured key
+/// kind present on the request.
+fn client_key(request: &Request, config: &RateLimitConfig) -> Option<String> {
+    config.keys.iter().find_map(|key| match key {
+        RateLimitKey::Ip => ip_key(request, config),
+        RateLimitKey::Principal => {
+            let user = request.extensions().get::<UserId>()?;
+            Some(format!("{}:{}", key.as_str(), user.0))
+        }
+        RateLimitKey::ApiKey => match request.extensions().get::<VerifiedApiKey>() {
+            // Keys are stored, so never keep the API key itself
+            Some(api_key) => Some(format!(
+                "{}:{}",
+                key.as_str(),
+                hex::encode(Sha256::digest(api_key.0.as_bytes()))
+            )),
+            // Anyone can send a new unverified key with every request
+            None if request
+                .headers()
+                .contains_key(config.api_key_header.as_str()) =>
+            {
+                ip_key(request, config)
+            }
+            None => None,
+        },
+    })
+}
+
+fn ip_key(request: &Request, config: &RateLimitConfig) -> Option<String> {
+    let ip = client_ip(request, &config.trusted_proxies)?;
+    Some(format!("{}:{ip}", RateLimitKey::Ip.as_str()))
+}
+
+fn set_headers(headers: &mut HeaderMap, quota: &Quota, decision: &Decision) {
+    let policy = format!("{};w={}", quota.limit, ceil_secs(quota.period));
+    for (name, value) in [
+        ("ratelimit-limit", HeaderValue::from(decision.limit)),
+        ("ratelimit-remaining", HeaderValue::from(decision.remaining)),
+        (
+            "ratelimit-reset",
+            HeaderValue::from(ceil_secs(decision.reset)),
+        ),
+    ] {
+        headers.insert(HeaderName::from_static(name), value);
+    }
+    if let Ok(policy) = HeaderValue::from_str(&policy) {
+        headers.insert(HeaderName::from_static("ratelimit-policy"), policy);
+    }
+}
+
+/// Whole seconds, rounded up so clients never retry too early.
+fn ceil_secs(duration: std::time::Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
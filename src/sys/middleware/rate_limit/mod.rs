@@ -0,0 +1,14 @@
+mod client_ip;
+mod config;
+mod layer;
+mod models;
+mod store;
+mod surreal;
+pub use client_ip::client_ip;
+pub use layer::rate_limit;
+pub use models::{
+    Decision, IpNet, Quota, RateLimitConfig, RateLimitKey, RateLimitStoreKind, RouteQuota,
+    VerifiedApiKey,
+};
+pub use store::{MemoryStore, RateLimitStore, gcra};
+pub use surreal::SurrealStore;
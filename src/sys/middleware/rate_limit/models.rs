@@ -0,0 +1,214 @@
+use crate::sys::{
+    env::{self, EnvEnum},
+    middleware::route_pattern::{self, RoutePattern},
+};
+use std::{fmt, time::Duration};
+
+/// What a client's requests are counted against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKey {
+    /// The client IP, taken from `X-Forwarded-For` behind trusted proxies.
+    Ip,
+    /// The authenticated user, set by authentication layers as `UserId`.
+    Principal,
+    /// The API key of the request once an authentication layer has
+    /// verified it and set `VerifiedApiKey`, hashed before it is used as a
+    /// key. Requests with an unverified API key header are keyed by IP.
+    ApiKey,
+}
+
+/// The API key a request was authenticated with, inserted into request
+/// extensions by authentication layers once the key is verified.
+#[derive(Debug, Clone)]
+pub struct VerifiedApiKey(pub String);
+
+impl EnvEnum for RateLimitKey {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("ip", Self::Ip),
+        ("principal", Self::Principal),
+        ("user", Self::Principal),
+        ("api_key", Self::ApiKey),
+    ];
+}
+
+impl RateLimitKey {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ip => "ip",
+            Self::Principal => "principal",
+            Self::ApiKey => "api_key",
+        }
+    }
+}
+
+/// Where rate limit state is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStoreKind {
+    /// Per-instance state, lost on restart.
+    Memory,
+    /// Shared state in SurrealDB, for deployments with several instances.
+    SurrealDb,
+}
+
+impl EnvEnum for RateLimitStoreKind {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("memory", Self::Memory),
+        ("surrealdb", Self::SurrealDb),
+        ("database", Self::SurrealDb),
+    ];
+}
+
+/// Allows `limit` requests per `period`, refilled continuously.
+///
+/// Equivalent to a token bucket holding `limit` tokens that refills one token
+/// every `period / limit`; enforced with GCRA so only one timestamp is stored
+/// per key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    /// Parses a quota such as `100/1m` or `10/s`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let (limit, period) = value.trim().split_once('/')?;
+        let limit: u32 = limit.trim().parse().ok().filter(|&l| l > 0)?;
+
+        let period = period.trim();
+        let period = if period.starts_with(|c: char| c.is_ascii_digit()) {
+            env::parse_duration(period)?
+        } else {
+            env::parse_duration(&format!("1{period}"))?
+        };
+
+        // At least one microsecond between requests keeps the arithmetic exact
+        (period.as_micros() >= u128::from(limit)).then_some(Self { limit, period })
+    }
+
+    /// Time between requests when they arrive at the sustained rate.
+    #[must_use]
+    pub fn emission_interval(&self) -> Duration {
+        self.period / self.limit
+    }
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}s", self.limit, self.period.as_secs_f64())
+    }
+}
+
+/// A quota applied to requests matching a route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteQuota {
+    pub route: RoutePattern,
+    pub quota: Quota,
+}
+
+impl RouteQuota {
+    /// Parses an entry such as `POST /login=5/1m` or `/api/*=1000/1m`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        RoutePattern::parse_entry(value, Quota::parse).map(|(route, quota)| Self { route, quota })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Quota for requests that match no route quota.
+    pub default_quota: Quota,
+    pub routes: Vec<RouteQuota>,
+    /// Keys tried in order; the first one present on a request is used.
+    pub keys: Vec<RateLimitKey>,
+    pub api_key_header: String,
+    pub trusted_proxies: Vec<IpNet>,
+    /// Paths that are never limited. Entries ending in `*` match by prefix.
+    pub exclude_paths: Vec<String>,
+    pub store: RateLimitStoreKind,
+    /// Named database for the SurrealDB store, the primary one when `None`.
+    pub database: Option<String>,
+}
+
+impl RateLimitConfig {
+    /// Returns the quota and its bucket name for a request.
+    #[must_use]
+    pub fn quota_for(&self, method: &str, route: Option<&str>, path: &str) -> (Quota, String) {
+        self.routes
+            .iter()
+            .find(|r| r.route.matches(method, route, path))
+            .map_or_else(
+                || (self.default_quota, "default".to_string()),
+                |r| (r.quota, r.route.to_string()),
+            )
+    }
+
+    /// Returns true if requests to `path` are never limited.
+    #[must_use]
+    pub fn is_excluded(&self, path: &str) -> bool {
+        route_pattern::is_excluded(&self.exclude_paths, path)
+    }
+}
+
+/// An IPv4 or IPv6 network in CIDR notation, or a single address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNet {
+    addr: std::net::IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    /// Parses `10.0.0.0/8`, `::1` or `192.168.1.10`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let (addr, prefix) = match value.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value.trim(), None),
+        };
+        let addr: std::net::IpAddr = addr.parse().ok()?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse().ok().filter(|&p| p <= max)?,
+            None => max,
+        };
+        Some(Self { addr, prefix })
+    }
+
+    /// Returns true if `ip` is inside this network.
+    #[must_use]
+    pub fn contains(&self, ip: std::net::IpAddr) -> bool {
+        use std::net::IpAddr;
+
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The outcome of counting one request against a quota.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    /// Requests that could still be made right now.
+    pub remaining: u32,
+    /// Time until the quota is fully replenished.
+    pub reset: Duration,
+    /// Time until the next request would be allowed, when rejected.
+    pub retry_after: Option<Duration>,
+}
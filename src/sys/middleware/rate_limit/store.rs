@@ -0,0 +1,109 @@
+use super::models::{Decision, Quota};
+use crate::dbs::error::DatabaseError;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Number of checks between sweeps of expired in-memory entries.
+const SWEEP_EVERY: u64 = 4096;
+
+/// Keeps per-key rate limit state.
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Counts one request for `key` against `quota`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError` if the state could not be read or written.
+    async fn check(&self, key: &str, quota: &Quota) -> Result<Decision, DatabaseError>;
+}
+
+/// Applies GCRA for a request arriving at `now`, given the key's stored
+/// theoretical arrival time (both in microseconds since the Unix epoch).
+///
+/// Returns the decision and, when the request is allowed, the arrival time
+/// to store.
+#[must_use]
+pub fn gcra(stored_tat: Option<u64>, now: u64, quota: &Quota) -> (Decision, Option<u64>) {
+    let period = micros(quota.period);
+    let interval = micros(quota.emission_interval()).max(1);
+
+    let tat = stored_tat.unwrap_or(now).max(now);
+    let new_tat = tat + interval;
+    let allow_at = new_tat.saturating_sub(period);
+
+    if now < allow_at {
+        let decision = Decision {
+            allowed: false,
+            limit: quota.limit,
+            remaining: 0,
+            reset: Duration::from_micros(tat - now),
+            retry_after: Some(Duration::from_micros(allow_at - now)),
+        };
+        return (decision, None);
+    }
+
+    let remaining = (period - (new_tat - now)) / interval;
+    let decision = Decision {
+        allowed: true,
+        limit: quota.limit,
+        remaining: u32::try_from(remaining).unwrap_or(u32::MAX),
+        reset: Duration::from_micros(new_tat - now),
+        retry_after: None,
+    };
+    (decision, Some(new_tat))
+}
+
+/// Current time in microseconds since the Unix epoch.
+#[must_use]
+pub fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, micros)
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Rate limit state kept in this process.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    /// Theoretical arrival time per key.
+    arrivals: HashMap<String, u64>,
+    checks: u64,
+}
+
+impl MemoryStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn check(&self, key: &str, quota: &Quota) -> Result<Decision, DatabaseError> {
+        let now = now_micros();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // Keys whose arrival time has passed are indistinguishable from new ones
+        state.checks += 1;
+        if state.checks.is_multiple_of(SWEEP_EVERY) {
+            state.arrivals.retain(|_, tat| *tat > now);
+        }
+
+        let (decision, new_tat) = gcra(state.arrivals.get(key).copied(), now, quota);
+        if let Some(new_tat) = new_tat {
+            state.arrivals.insert(key.to_string(), new_tat);
+        }
+        Ok(decision)
+    }
+}
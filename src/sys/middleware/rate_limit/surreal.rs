@@ -0,0 +1,105 @@
+use super::{
+    models::{Decision, Quota},
+    store::{RateLimitStore, gcra, now_micros},
+};
+use crate::{
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::server::Shutdown,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::warn;
+
+/// Attempts before giving up when other instances update the same key.
+const MAX_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Deserialize)]
+struct ArrivalRow {
+    tat: u64,
+}
+
+/// Rate limit state shared by every instance through the `rate_limit` table.
+///
+/// Updates use compare-and-set on the stored arrival time, so concurrent
+/// requests from several instances are counted exactly once.
+pub struct SurrealStore {
+    db: DbConnection,
+}
+
+impl SurrealStore {
+    /// Creates the store and starts a task that deletes expired entries
+    /// until shutdown.
+    #[must_use]
+    pub fn new(db: DbConnection, sweep_interval: Duration, shutdown: Shutdown) -> Self {
+        let sweeper = db.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sweep_interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    () = shutdown.triggered() => break,
+                }
+                if let Err(e) = sweeper
+                    .query("DELETE rate_limit WHERE tat < $now")
+                    .bind(("now", now_micros()))
+                    .await
+                {
+                    warn!(error = %e, "Failed to delete expired rate limit entries");
+                }
+            }
+        });
+
+        Self { db }
+    }
+
+    async fn try_check(&self, key: &str, quota: &Quota) -> Result<Option<Decision>, DatabaseError> {
+        let now = now_micros();
+        let mut response = self
+            .db
+            .query("SELECT tat FROM type::thing('rate_limit', $key)")
+            .bind(("key", key.to_string()))
+            .await?;
+        let stored = response.take::<Option<ArrivalRow>>(0)?.map(|row| row.tat);
+
+        let (decision, new_tat) = gcra(stored, now, quota);
+        let Some(new_tat) = new_tat else {
+            return Ok(Some(decision));
+        };
+
+        // Only write if no other instance changed the entry since it was read
+        let query = if stored.is_some() {
+            "UPDATE type::thing('rate_limit', $key) SET tat = $new WHERE tat = $old RETURN tat"
+        } else {
+            "CREATE type::thing('rate_limit', $key) SET tat = $new RETURN tat"
+        };
+        let mut response = self
+            .db
+            .query(query)
+            .bind(("key", key.to_string()))
+            .bind(("new", new_tat))
+            .bind(("old", stored))
+            .await?;
+        let written: Option<ArrivalRow> = match response.take(0) {
+            Ok(row) => row,
+            // Another instance created the entry first
+            Err(_) if stored.is_none() => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(written.map(|_| decision))
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for SurrealStore {
+    async fn check(&self, key: &str, quota: &Quota) -> Result<Decision, DatabaseError> {
+        for _ in 0..MAX_ATTEMPTS {
+            if let Some(decision) = self.try_check(key, quota).await? {
+                return Ok(decision);
+            }
+        }
+        Err(DatabaseError::QueryError(format!(
+            "Rate limit entry '{key}' is under heavy contention"
+        )))
+    }
+}
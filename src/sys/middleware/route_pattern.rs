@@ -0,0 +1,77 @@
+use std::fmt;
+
+/// Selects requests by method and route, for per-route settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePattern {
+    /// Only requests with this method match, any method when `None`.
+    pub method: Option<String>,
+    /// A route template such as `/users/{id}` or a path. Patterns ending in
+    /// `*` match by prefix.
+    pub pattern: String,
+}
+
+impl RoutePattern {
+    /// Parses `/api/*` or `POST /login`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+        let (method, pattern) = match (parts.next()?, parts.next(), parts.next()) {
+            (pattern, None, None) => (None, pattern),
+            (method, Some(pattern), None) => (Some(method.to_uppercase()), pattern),
+            _ => return None,
+        };
+        pattern.starts_with('/').then(|| Self {
+            method,
+            pattern: pattern.to_string(),
+        })
+    }
+
+    /// Parses a `PATTERN=VALUE` entry such as `POST /login=5/1m`, using
+    /// `parse_value` for the part after the last `=`.
+    #[must_use]
+    pub fn parse_entry<T>(
+        value: &str,
+        parse_value: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<(Self, T)> {
+        let (route, value) = value.rsplit_once('=')?;
+        Some((Self::parse(route)?, parse_value(value.trim())?))
+    }
+
+    /// Returns true if a request with `method` to `route` (the matched
+    /// template) or `path` matches.
+    #[must_use]
+    pub fn matches(&self, method: &str, route: Option<&str>, path: &str) -> bool {
+        if self.method.as_deref().is_some_and(|m| m != method) {
+            return false;
+        }
+        let matches = |candidate: &str| pattern_matches(&self.pattern, candidate);
+        route.is_some_and(matches) || matches(path)
+    }
+}
+
+/// Returns true if `path` equals `pattern`, or starts with it when it ends
+/// in `*`.
+#[must_use]
+pub fn pattern_matches(pattern: &str, path: &str) -> bool {
+    pattern
+        .strip_suffix('*')
+        .map_or(pattern == path, |prefix| path.starts_with(prefix))
+}
+
+/// Returns true if `path` matches any of `patterns`, as used for the
+/// excluded paths of middleware.
+#[must_use]
+pub fn is_excluded(patterns: &[String], path: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| pattern_matches(pattern, path))
+}
+
+impl fmt::Display for RoutePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.method {
+            Some(method) => write!(f, "{method} {}", self.pattern),
+            None => write!(f, "{}", self.pattern),
+        }
+    }
+}
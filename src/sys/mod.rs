@@ -1,5 +1,18 @@
+pub mod admin;
+pub mod audit;
+pub mod cache;
 pub mod config;
 pub mod env;
+pub mod events;
 pub mod health;
 pub mod init;
+pub mod jobs;
 pub mod log;
+pub mod metrics;
+pub mod middleware;
+pub mod outbox;
+pub mod scheduler;
+pub mod server;
+pub mod tenancy;
+pub mod tls;
+pub mod webhooks;
@@ -0,0 +1,69 @@
+use super::models::{OutboxConfig, SinkKind};
+use crate::sys::{
+    config::ConfigReader,
+    env::{self, EnvEnum},
+};
+use std::{path::PathBuf, time::Duration};
+
+impl OutboxConfig {
+    /// Creates an `OutboxConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config.bool("OUTBOX_ENABLED", true);
+
+        let mut sinks = Vec::new();
+        for name in config.list("OUTBOX_SINKS", "local") {
+            match env::parse_enum::<SinkKind>(&name) {
+                Some(sink) if !sinks.contains(&sink) => sinks.push(sink),
+                Some(_) => {}
+                None => config.invalid(
+                    "OUTBOX_SINKS",
+                    format!("'{name}' is not one of {}", SinkKind::names().join(", ")),
+                ),
+            }
+        }
+        if enabled && sinks.is_empty() {
+            config.invalid("OUTBOX_SINKS", "must name at least one sink");
+        }
+
+        let file = config
+            .optional("OUTBOX_FILE")
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from);
+        if sinks.contains(&SinkKind::File) && file.is_none() {
+            config.invalid("OUTBOX_FILE", "must be set when OUTBOX_SINKS includes file");
+        }
+
+        let batch_size = config.parsed("OUTBOX_BATCH_SIZE", 100);
+        if batch_size == 0 {
+            config.invalid("OUTBOX_BATCH_SIZE", "must be greater than zero");
+        }
+        let concurrency = config.parsed("OUTBOX_CONCURRENCY", 4);
+        if concurrency == 0 {
+            config.invalid("OUTBOX_CONCURRENCY", "must be greater than zero");
+        }
+        let max_attempts = config.parsed("OUTBOX_MAX_ATTEMPTS", 10);
+        if max_attempts == 0 {
+            config.invalid("OUTBOX_MAX_ATTEMPTS", "must be greater than zero");
+        }
+        let lease = config.duration("OUTBOX_LEASE", Duration::from_secs(30));
+        if lease < Duration::from_secs(1) {
+            config.invalid("OUTBOX_LEASE", "must be at least 1s");
+        }
+
+        Self {
+            enabled,
+            sinks,
+            file,
+            webhook_tenant: config.string("OUTBOX_WEBHOOK_TENANT", "default"),
+            poll_interval: config.duration("OUTBOX_POLL_INTERVAL", Duration::from_secs(1)),
+            batch_size,
+            concurrency,
+            lease,
+            backoff_base: config.duration("OUTBOX_BACKOFF_BASE", Duration::from_secs(1)),
+            backoff_max: config.duration("OUTBOX_BACKOFF_MAX", Duration::from_secs(300)),
+            max_attempts,
+            retention: config.duration("OUTBOX_RETENTION", Duration::from_secs(7 * 86_400)),
+            stuck_threshold: config.duration("OUTBOX_STUCK_THRESHOLD", Duration::from_secs(300)),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod config;
+mod models;
+mod relay;
+mod routes;
+mod sink;
+mod store;
+mod transaction;
+pub use models::{NewEvent, OutboxConfig, OutboxEvent, OutboxStats, SinkKind};
+pub use relay::Outbox;
+pub use routes::{list_dead_events, retry_dead_event};
+pub use sink::{FileSink, LocalSink, OutboxSink, SinkResult, StdoutSink, WebhookSink};
+pub(crate) use store::record_events;
+pub use transaction::OutboxTransaction;
@@ -0,0 +1,154 @@
+use crate::{
+    dbs::error::DatabaseError,
+    sys::{env::EnvEnum, events::Event},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{path::PathBuf, time::Duration};
+
+#[derive(Debug, Clone)]
+pub struct OutboxConfig {
+    /// Relay events from this instance.
+    pub enabled: bool,
+    /// Where relayed events are published.
+    pub sinks: Vec<SinkKind>,
+    /// File the `file` sink appends events to, one JSON object per line.
+    pub file: Option<PathBuf>,
+    /// Tenant the `webhooks` sink publishes events without a tenant to.
+    pub webhook_tenant: String,
+    /// How often the relay looks for events when it is not woken by a commit.
+    pub poll_interval: Duration,
+    /// Aggregates relayed per pass, and events relayed per aggregate.
+    pub batch_size: u32,
+    /// Aggregates relayed at the same time.
+    pub concurrency: usize,
+    /// How long an aggregate is held by a relay without progress. A crashed
+    /// instance releases its aggregates by letting the lease expire.
+    pub lease: Duration,
+    /// First delay before a failed event is retried; doubles on every
+    /// further failure.
+    pub backoff_base: Duration,
+    /// Longest delay between retries.
+    pub backoff_max: Duration,
+    /// Attempts before a failing event is set aside as dead, letting the
+    /// later events of its aggregate through.
+    pub max_attempts: u32,
+    /// How long published events are kept.
+    pub retention: Duration,
+    /// Age at which an unpublished event is reported as stuck.
+    pub stuck_threshold: Duration,
+}
+
+/// A destination for relayed events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkKind {
+    /// Outbound webhooks of the event's tenant.
+    Webhooks,
+    /// Subscribers to `OutboxEvent` on this instance's event bus.
+    Local,
+    Stdout,
+    File,
+}
+
+impl EnvEnum for SinkKind {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("webhooks", Self::Webhooks),
+        ("local", Self::Local),
+        ("stdout", Self::Stdout),
+        ("file", Self::File),
+    ];
+}
+
+/// An event to record with a transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct NewEvent {
+    /// Kind of entity the event is about, such as `user`.
+    pub aggregate_type: String,
+    /// Entity the event is about. Events of one aggregate are published in
+    /// the order they were committed.
+    pub aggregate_id: String,
+    /// Event name, such as `user.created`.
+    pub event: String,
+    pub payload: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl NewEvent {
+    /// Creates an event about the aggregate `aggregate_type`/`aggregate_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the payload cannot be
+    /// serialized.
+    pub fn new<T: Serialize + ?Sized>(
+        aggregate_type: impl Into<String>,
+        aggregate_id: impl Into<String>,
+        event: impl Into<String>,
+        payload: &T,
+    ) -> Result<Self, DatabaseError> {
+        let payload = serde_json::to_value(payload)
+            .map_err(|e| DatabaseError::QueryError(format!("Payload cannot be stored: {e}")))?;
+        Ok(Self {
+            aggregate_type: aggregate_type.into(),
+            aggregate_id: aggregate_id.into(),
+            event: event.into(),
+            payload,
+            tenant: None,
+        })
+    }
+
+    /// Marks the event as belonging to `tenant`.
+    #[must_use]
+    pub fn for_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+}
+
+/// A recorded event, as stored in the `outbox` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    /// Stable across redeliveries, so consumers can skip duplicates.
+    pub id: String,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    /// Position of the event among the events of its aggregate, from 1.
+    pub sequence: u64,
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub payload: Value,
+    pub created_at: i64,
+    /// Failed attempts to publish the event.
+    #[serde(default)]
+    pub attempts: u32,
+    /// The event and the ones after it in its aggregate are held until this
+    /// time after a failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<i64>,
+    /// When the event was set aside after failing its last attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_at: Option<i64>,
+}
+
+impl Event for OutboxEvent {
+    const NAME: &'static str = "outbox";
+}
+
+/// Counts of unpublished events.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OutboxStats {
+    pub pending: u64,
+    /// Unpublished events that failed at least once.
+    pub failing: u64,
+    /// Events set aside after failing every attempt.
+    pub dead: u64,
+    /// Age of the oldest unpublished event.
+    pub oldest_pending: Duration,
+}
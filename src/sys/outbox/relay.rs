@@ -0,0 +1,369 @@
+use super::{
+    models::{OutboxConfig, OutboxEvent, OutboxStats},
+    sink::OutboxSink,
+    store::{OutboxStore, Stream},
+    transaction::OutboxTransaction,
+};
+use crate::{
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::{
+        health::models::{ComponentHealth, HealthCheck, HealthStatus},
+        metrics::MetricsRegistry,
+        server::Shutdown,
+    },
+};
+use futures::StreamExt;
+use rand::Rng;
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+use tokio::{
+    sync::Notify,
+    time::{Instant, sleep},
+};
+use tracing::{Instrument, debug, error, info, info_span, warn};
+
+/// How often published events past their retention are deleted.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Records events in the same transaction as the data they describe and
+/// relays them to sinks.
+///
+/// Every instance may run the relay; a lease per aggregate makes sure only
+/// one of them publishes an aggregate's events at a time, in the order they
+/// were committed. An event that fails holds back the later events of its
+/// aggregate until it is published, while other aggregates carry on. After
+/// `OUTBOX_MAX_ATTEMPTS` failures it is set aside as dead so the aggregate
+/// moves on, and can be retried from the admin API.
+#[derive(Clone)]
+pub struct Outbox {
+    inner: Arc<OutboxInner>,
+}
+
+struct OutboxInner {
+    store: OutboxStore,
+    config: OutboxConfig,
+    sinks: RwLock<Vec<Arc<dyn OutboxSink>>>,
+    /// Wakes the relay when events are committed by this instance.
+    notify: Notify,
+    shutdown: Shutdown,
+    metrics: Arc<MetricsRegistry>,
+}
+
+impl Outbox {
+    #[must_use]
+    pub fn new(
+        db: DbConnection,
+        config: OutboxConfig,
+        shutdown: Shutdown,
+        metrics: Arc<MetricsRegistry>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(OutboxInner {
+                store: OutboxStore::new(db),
+                config,
+                sinks: RwLock::new(Vec::new()),
+                notify: Notify::new(),
+                shutdown,
+                metrics,
+            }),
+        }
+    }
+
+    /// Adds a sink every event is published to.
+    pub fn add_sink(&self, sink: impl OutboxSink + 'static) {
+        info!(sink = sink.name(), "Outbox sink added");
+        self.inner
+            .sinks
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::new(sink));
+    }
+
+    /// Starts a transaction on the primary database, which holds the outbox.
+    #[must_use]
+    pub fn transaction(&self) -> OutboxTransaction {
+        OutboxTransaction::new(self.clone())
+    }
+
+    /// Starts relaying events until shutdown.
+    pub fn start(&self) {
+        tokio::spawn(self.clone().run().instrument(info_span!("outbox")));
+        info!("Outbox relay started");
+    }
+
+    /// Counts unpublished events.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the outbox cannot be queried.
+    pub async fn stats(&self) -> Result<OutboxStats, DatabaseError> {
+        self.inner.store.stats(now_millis()).await
+    }
+
+    /// Returns events set aside after failing every attempt, most recent
+    /// first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the outbox cannot be queried.
+    pub async fn dead_events(
+        &self,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<OutboxEvent>, DatabaseError> {
+        self.inner.store.dead(start, limit).await
+    }
+
+    /// Returns a dead event to the relay with fresh attempts. It is
+    /// published after the events of its aggregate that went ahead of it.
+    /// Returns false if no dead event has that id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the event cannot be updated.
+    pub async fn retry_dead(&self, id: &str) -> Result<bool, DatabaseError> {
+        let retried = self.inner.store.retry_dead(id).await?;
+        if retried {
+            self.notify();
+        }
+        Ok(retried)
+    }
+
+    pub(super) fn db(&self) -> &DbConnection {
+        self.inner.store.db()
+    }
+
+    /// Wakes the relay to publish newly committed events.
+    pub(super) fn notify(&self) {
+        self.inner.notify.notify_one();
+    }
+
+    async fn run(self) {
+        let config = &self.inner.config;
+        let shutdown = &self.inner.shutdown;
+        let mut last_sweep: Option<Instant> = None;
+        while !shutdown.is_triggered() {
+            if last_sweep.is_none_or(|at| at.elapsed() >= SWEEP_INTERVAL) {
+                last_sweep = Some(Instant::now());
+                let cutoff = now_millis().saturating_sub(millis(config.retention));
+                if let Err(e) = self.inner.store.sweep(cutoff).await {
+                    warn!(error = %e, "Failed to delete published outbox events");
+                }
+            }
+
+            let busy = match self.relay().await {
+                Ok(busy) => busy,
+                Err(e) => {
+                    warn!(error = %e, "Failed to relay outbox events");
+                    false
+                }
+            };
+            if !busy {
+                tokio::select! {
+                    () = self.inner.notify.notified() => {}
+                    () = sleep(config.poll_interval) => {}
+                    () = shutdown.triggered() => {}
+                }
+            }
+        }
+        info!("Outbox relay stopped");
+    }
+
+    /// Relays the events of a batch of aggregates. Returns whether any
+    /// events were published, so more may be waiting.
+    async fn relay(&self) -> Result<bool, DatabaseError> {
+        let config = &self.inner.config;
+        let streams = self
+            .inner
+            .store
+            .pending_streams(now_millis(), config.batch_size)
+            .await?;
+        let published = futures::stream::iter(streams)
+            .map(|stream| async move {
+                match self.relay_stream(&stream).await {
+                    Ok(published) => published,
+                    Err(e) => {
+                        warn!(
+                            aggregate_type = %stream.aggregate_type,
+                            aggregate_id = %stream.aggregate_id,
+                            error = %e,
+                            "Failed to relay outbox events"
+                        );
+                        0
+                    }
+                }
+            })
+            .buffer_unordered(config.concurrency)
+            .fold(0, |total, published| async move { total + published })
+            .await;
+        Ok(published > 0)
+    }
+
+    /// Publishes the events of one aggregate in order while holding its
+    /// lease, stopping at the first failure. Returns the number of events
+    /// published, none if another relay holds the lease.
+    async fn relay_stream(&self, stream: &Stream) -> Result<usize, DatabaseError> {
+        let store = &self.inner.store;
+        let lease_ttl = millis(self.inner.config.lease);
+        let lease = hex::encode(rand::rng().random::<[u8; 16]>());
+        let now = now_millis();
+        if !store
+            .claim(stream, &lease, now, now.saturating_add(lease_ttl))
+            .await?
+        {
+            return Ok(0);
+        }
+
+        let result = self.publish_events(stream, &lease).await;
+        store.release(stream, &lease).await?;
+        result
+    }
+
+    async fn publish_events(&self, stream: &Stream, lease: &str) -> Result<usize, DatabaseError> {
+        let store = &self.inner.store;
+        let lease_ttl = millis(self.inner.config.lease);
+        let events = store
+            .pending_events(stream, self.inner.config.batch_size)
+            .await?;
+        let mut published = 0;
+        for event in events {
+            let now = now_millis();
+            if self.inner.shutdown.is_triggered() || event.retry_at.is_some_and(|at| at > now) {
+                break;
+            }
+            // Another relay may only take over once the lease has expired
+            if !store
+                .claim(stream, lease, now, now.saturating_add(lease_ttl))
+                .await?
+            {
+                warn!(
+                    aggregate_type = %stream.aggregate_type,
+                    aggregate_id = %stream.aggregate_id,
+                    "Lost the outbox lease, leaving the aggregate to another relay"
+                );
+                break;
+            }
+
+            match self.publish(&event).await {
+                Ok(()) => {
+                    store.mark_published(&event.id, now_millis()).await?;
+                    published += 1;
+                    self.record("published");
+                    debug!(id = %event.id, event = %event.event, sequence = event.sequence, "Relayed outbox event");
+                }
+                Err(e) if event.attempts + 1 >= self.inner.config.max_attempts => {
+                    error!(
+                        id = %event.id,
+                        event = %event.event,
+                        attempt = event.attempts + 1,
+                        error = %e,
+                        "Outbox event failed its last attempt, setting it aside as dead"
+                    );
+                    store.mark_dead(&event, &e, now_millis()).await?;
+                    self.record("dead");
+                }
+                Err(e) => {
+                    let delay = self.backoff(event.attempts + 1);
+                    warn!(
+                        id = %event.id,
+                        event = %event.event,
+                        attempt = event.attempts + 1,
+                        retry_in_ms = delay.as_millis(),
+                        error = %e,
+                        "Failed to publish outbox event"
+                    );
+                    store
+                        .mark_failed(&event, &e, now_millis().saturating_add(millis(delay)))
+                        .await?;
+                    self.record("failed");
+                    break;
+                }
+            }
+        }
+        Ok(published)
+    }
+
+    /// Publishes an event to every sink, failing if any sink fails.
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), String> {
+        let sinks = self
+            .inner
+            .sinks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        for sink in sinks {
+            sink.publish(event)
+                .await
+                .map_err(|e| format!("Sink '{}' failed: {e}", sink.name()))?;
+        }
+        Ok(())
+    }
+
+    /// Delay before retrying after the given failed attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let config = &self.inner.config;
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1).min(31));
+        config
+            .backoff_base
+            .saturating_mul(factor)
+            .min(config.backoff_max)
+    }
+
+    fn record(&self, outcome: &str) {
+        self.inner
+            .metrics
+            .counter(
+                "outbox_events_total",
+                "Outbox events relayed, by outcome",
+                &[("outcome", outcome)],
+            )
+            .inc();
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for Outbox {
+    /// Reports degraded while events fail or wait too long.
+    async fn check(&self) -> ComponentHealth {
+        let (status, message) = match self.stats().await {
+            Ok(stats) => {
+                let waiting = stats.oldest_pending > self.inner.config.stuck_threshold;
+                let status = if stats.failing > 0 || waiting {
+                    HealthStatus::Degraded
+                } else {
+                    HealthStatus::Healthy
+                };
+                (
+                    status,
+                    format!(
+                        "Pending: {}, failing: {}, dead: {}, oldest pending: {}s",
+                        stats.pending,
+                        stats.failing,
+                        stats.dead,
+                        stats.oldest_pending.as_secs()
+                    ),
+                )
+            }
+            Err(e) => {
+                warn!(error = %e, "Outbox health check failed");
+                (HealthStatus::Unhealthy, format!("Query error: {e}"))
+            }
+        };
+
+        ComponentHealth {
+            name: "Outbox".to_string(),
+            status,
+            message: Some(message),
+        }
+    }
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,43 @@
+use crate::{AppError, dbs::error::DatabaseError, sys::config::state::AppState};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Most events returned by one request.
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct DeadEventsQuery {
+    #[serde(default)]
+    pub start: u64,
+    pub limit: Option<u64>,
+}
+
+/// Lists outbox events set aside after failing every attempt, most recent
+/// first.
+pub async fn list_dead_events(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DeadEventsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query.limit.unwrap_or(100).min(MAX_LIMIT);
+    let events = state.outbox.dead_events(query.start, limit).await?;
+    Ok(Json(json!({ "events": events })))
+}
+
+/// Returns a dead outbox event to the relay with fresh attempts.
+pub async fn retry_dead_event(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    if state.outbox.retry_dead(&id).await? {
+        Ok((StatusCode::ACCEPTED, Json(json!({ "id": id }))))
+    } else {
+        Err(DatabaseError::NotFound(format!("Dead outbox event '{id}' not found")).into())
+    }
+}
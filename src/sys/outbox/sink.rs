@@ -0,0 +1,162 @@
+use super::models::OutboxEvent;
+use crate::sys::{events::EventBus, webhooks::Webhooks};
+use std::{error::Error, path::PathBuf};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::Mutex,
+};
+use tracing::debug;
+
+/// Outcome of publishing an event to a sink. Errors are retried with backoff.
+pub type SinkResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// A destination the relay publishes events to.
+///
+/// Events are delivered at least once: an event is published again if any
+/// sink fails or the relay stops before recording it as published, so
+/// sinks should tolerate duplicates, for example by the event id. Sinks
+/// that hand events on without confirmation, like `LocalSink`, are only
+/// at most once past that point.
+#[async_trait::async_trait]
+pub trait OutboxSink: Send + Sync {
+    /// Name used in logs and metrics.
+    fn name(&self) -> &str;
+
+    async fn publish(&self, event: &OutboxEvent) -> SinkResult;
+}
+
+/// Publishes events as outbound webhooks of their tenant.
+///
+/// Deliveries are queued in commit order but retried independently, so
+/// receivers may see the events of an aggregate out of order and should
+/// order them by the payload where it matters.
+pub struct WebhookSink {
+    webhooks: Webhooks,
+    /// Tenant for events that have none.
+    default_tenant: String,
+}
+
+impl WebhookSink {
+    #[must_use]
+    pub fn new(webhooks: Webhooks, default_tenant: impl Into<String>) -> Self {
+        Self {
+            webhooks,
+            default_tenant: default_tenant.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl OutboxSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> SinkResult {
+        let tenant = event.tenant.as_deref().unwrap_or(&self.default_tenant);
+        self.webhooks
+            .publish_with_id(tenant, &event.id, &event.event, &event.payload)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Publishes events as `OutboxEvent` on the event bus of this process.
+///
+/// Delivery is at most once: an event reaches the subscribers present when
+/// it is relayed and is dropped for subscribers that lag or fail, or when
+/// there are none. Use it for notifications, not for work that must happen.
+pub struct LocalSink {
+    events: EventBus,
+}
+
+impl LocalSink {
+    #[must_use]
+    pub fn new(events: EventBus) -> Self {
+        Self { events }
+    }
+}
+
+#[async_trait::async_trait]
+impl OutboxSink for LocalSink {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> SinkResult {
+        if self.events.publish(event.clone()) == 0 {
+            debug!(id = %event.id, event = %event.event, "No local subscribers for outbox event");
+        }
+        Ok(())
+    }
+}
+
+/// Writes events to standard output, one JSON object per line.
+#[derive(Default)]
+pub struct StdoutSink {
+    lock: Mutex<()>,
+}
+
+impl StdoutSink {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl OutboxSink for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> SinkResult {
+        let _guard = self.lock.lock().await;
+        write_line(&mut tokio::io::stdout(), event).await
+    }
+}
+
+/// Appends events to a file, one JSON object per line. The file is opened
+/// for every event, so it can be rotated while the relay runs.
+pub struct FileSink {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSink {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl OutboxSink for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> SinkResult {
+        let _guard = self.lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        write_line(&mut file, event).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(writer: &mut W, event: &OutboxEvent) -> SinkResult {
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    writer.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use super::models::{OutboxEvent, OutboxStats};
+use crate::dbs::{error::DatabaseError, models::DbConnection};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Fields selected for every event: its own fields plus the record key as `id`.
+const FIELDS: &str = "*, meta::id(id) AS id";
+
+/// Returns a statement that records the events `events` evaluates to, for
+/// use inside a transaction.
+///
+/// Each event takes the next sequence number of its aggregate from a
+/// counter in `outbox_stream`. Concurrent transactions raising events for
+/// the same aggregate conflict on the counter, so sequence numbers follow
+/// commit order.
+pub(crate) fn record_events(events: &str) -> String {
+    format!(
+        "FOR $outbox_event IN {events} {{
+             LET $outbox_sequence = (UPSERT type::thing('outbox_stream', [
+                 $outbox_event.aggregate_type, $outbox_event.aggregate_id
+             ]) SET sequence += 1 RETURN VALUE sequence)[0];
+             CREATE outbox CONTENT {{
+                 aggregate_type: $outbox_event.aggregate_type,
+                 aggregate_id: $outbox_event.aggregate_id,
+                 sequence: $outbox_sequence,
+                 event: $outbox_event.event,
+                 tenant: $outbox_event.tenant,
+                 payload: $outbox_event.payload,
+                 created_at: $outbox_event.created_at,
+                 attempts: 0
+             }} RETURN NONE;
+         }}"
+    )
+}
+
+/// An aggregate with unpublished events.
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct Stream {
+    pub(super) aggregate_type: String,
+    pub(super) aggregate_id: String,
+}
+
+#[derive(Deserialize)]
+struct PendingRow {
+    pending: u64,
+    oldest: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CountRow {
+    count: u64,
+}
+
+/// Queries on the `outbox` table and the relay leases in `outbox_lease`.
+#[derive(Clone)]
+pub(super) struct OutboxStore {
+    db: DbConnection,
+}
+
+impl OutboxStore {
+    pub(super) fn new(db: DbConnection) -> Self {
+        Self { db }
+    }
+
+    pub(super) fn db(&self) -> &DbConnection {
+        &self.db
+    }
+
+    /// Returns aggregates with unpublished events that are not held back by
+    /// a failure or leased by another relay, oldest first. Dead events are
+    /// left out.
+    pub(super) async fn pending_streams(
+        &self,
+        now: i64,
+        limit: u32,
+    ) -> Result<Vec<Stream>, DatabaseError> {
+        Ok(self
+            .db
+            .query(
+                "SELECT aggregate_type, aggregate_id, math::min(created_at) AS oldest FROM outbox \
+                 WHERE published_at IS NONE AND dead_at IS NONE \
+                 AND (retry_at IS NONE OR retry_at <= $now) \
+                 AND (type::thing('outbox_lease', [aggregate_type, aggregate_id]).until ?? 0) < $now \
+                 GROUP BY aggregate_type, aggregate_id ORDER BY oldest LIMIT $limit",
+            )
+            .bind(("now", now))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?)
+    }
+
+    /// Takes or extends the lease of an aggregate unless another relay
+    /// holds it. Returns whether the lease is held.
+    pub(super) async fn claim(
+        &self,
+        stream: &Stream,
+        lease: &str,
+        now: i64,
+        until: i64,
+    ) -> Result<bool, DatabaseError> {
+        let claimed: Option<String> = self
+            .db
+            .query(
+                "UPSERT type::thing('outbox_lease', [$type, $aggregate]) \
+                 SET lease = $lease, until = $until \
+                 WHERE until IS NONE OR until < $now OR lease = $lease RETURN VALUE lease",
+            )
+            .bind(("type", stream.aggregate_type.clone()))
+            .bind(("aggregate", stream.aggregate_id.clone()))
+            .bind(("lease", lease.to_string()))
+            .bind(("now", now))
+            .bind(("until", until))
+            .await?
+            .take(0)?;
+        Ok(claimed.is_some())
+    }
+
+    /// Gives up the lease of an aggregate if it is still held.
+    pub(super) async fn release(&self, stream: &Stream, lease: &str) -> Result<(), DatabaseError> {
+        self.db
+            .query("DELETE type::thing('outbox_lease', [$type, $aggregate]) WHERE lease = $lease")
+            .bind(("type", stream.aggregate_type.clone()))
+            .bind(("aggregate", stream.aggregate_id.clone()))
+            .bind(("lease", lease.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Returns the first unpublished events of an aggregate in order,
+    /// skipping dead ones.
+    pub(super) async fn pending_events(
+        &self,
+        stream: &Stream,
+        limit: u32,
+    ) -> Result<Vec<OutboxEvent>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM outbox \
+                 WHERE aggregate_type = $type AND aggregate_id = $aggregate \
+                 AND published_at IS NONE AND dead_at IS NONE \
+                 ORDER BY sequence LIMIT $limit"
+            ))
+            .bind(("type", stream.aggregate_type.clone()))
+            .bind(("aggregate", stream.aggregate_id.clone()))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?)
+    }
+
+    pub(super) async fn mark_published(&self, id: &str, now: i64) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "UPDATE type::thing('outbox', $id) \
+                 SET published_at = $now, retry_at = NONE",
+            )
+            .bind(("id", id.to_string()))
+            .bind(("now", now))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Records a failed attempt to publish `event` and holds back the
+    /// aggregate's unpublished events until `retry_at`.
+    pub(super) async fn mark_failed(
+        &self,
+        event: &OutboxEvent,
+        error: &str,
+        retry_at: i64,
+    ) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "BEGIN TRANSACTION;
+                 UPDATE type::thing('outbox', $id) SET attempts += 1, last_error = $error;
+                 UPDATE outbox SET retry_at = $retry_at \
+                 WHERE aggregate_type = $type AND aggregate_id = $aggregate \
+                 AND published_at IS NONE AND dead_at IS NONE;
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("id", event.id.clone()))
+            .bind(("error", error.to_string()))
+            .bind(("retry_at", retry_at))
+            .bind(("type", event.aggregate_type.clone()))
+            .bind(("aggregate", event.aggregate_id.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Records the last failed attempt to publish `event` and sets it aside,
+    /// releasing the aggregate's later events.
+    pub(super) async fn mark_dead(
+        &self,
+        event: &OutboxEvent,
+        error: &str,
+        now: i64,
+    ) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "BEGIN TRANSACTION;
+                 UPDATE type::thing('outbox', $id) \
+                 SET attempts += 1, last_error = $error, dead_at = $now, retry_at = NONE;
+                 UPDATE outbox SET retry_at = NONE \
+                 WHERE aggregate_type = $type AND aggregate_id = $aggregate \
+                 AND published_at IS NONE AND dead_at IS NONE;
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("id", event.id.clone()))
+            .bind(("error", error.to_string()))
+            .bind(("now", now))
+            .bind(("type", event.aggregate_type.clone()))
+            .bind(("aggregate", event.aggregate_id.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Returns dead events, most recent first.
+    pub(super) async fn dead(
+        &self,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<OutboxEvent>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM outbox WHERE dead_at IS NOT NONE \
+                 ORDER BY dead_at DESC LIMIT $limit START $start"
+            ))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+
+    /// Returns a dead event to the relay with fresh attempts. Returns false
+    /// if no dead event has that id.
+    pub(super) async fn retry_dead(&self, id: &str) -> Result<bool, DatabaseError> {
+        let retried: Option<String> = self
+            .db
+            .query(
+                "UPDATE type::thing('outbox', $id) \
+                 SET dead_at = NONE, attempts = 0, retry_at = NONE \
+                 WHERE dead_at IS NOT NONE RETURN VALUE meta::id(id)",
+            )
+            .bind(("id", id.to_string()))
+            .await?
+            .take(0)?;
+        Ok(retried.is_some())
+    }
+
+    /// Deletes events published before `cutoff`.
+    pub(super) async fn sweep(&self, cutoff: i64) -> Result<(), DatabaseError> {
+        self.db
+            .query("DELETE outbox WHERE published_at IS NOT NONE AND published_at < $cutoff")
+            .bind(("cutoff", cutoff))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub(super) async fn stats(&self, now: i64) -> Result<OutboxStats, DatabaseError> {
+        let mut response = self
+            .db
+            .query(
+                "SELECT count() AS pending, math::min(created_at) AS oldest FROM outbox \
+                 WHERE published_at IS NONE AND dead_at IS NONE GROUP ALL;
+                 SELECT count() AS count FROM outbox \
+                 WHERE published_at IS NONE AND dead_at IS NONE AND attempts > 0 GROUP ALL;
+                 SELECT count() AS count FROM outbox WHERE dead_at IS NOT NONE GROUP ALL;",
+            )
+            .await?;
+        let pending: Option<PendingRow> = response.take(0)?;
+        let failing: Option<CountRow> = response.take(1)?;
+        let dead: Option<CountRow> = response.take(2)?;
+
+        let (pending, oldest) = pending.map_or((0, None), |row| (row.pending, row.oldest));
+        let age = oldest.map_or(0, |oldest| now.saturating_sub(oldest));
+        Ok(OutboxStats {
+            pending,
+            failing: failing.map_or(0, |row| row.count),
+            dead: dead.map_or(0, |row| row.count),
+            oldest_pending: Duration::from_millis(u64::try_from(age).unwrap_or(0)),
+        })
+    }
+}
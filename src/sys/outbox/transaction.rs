@@ -0,0 +1,142 @@
+use super::{models::NewEvent, relay::Outbox, store::record_events};
+use crate::dbs::error::{DatabaseError, transaction_failure};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use surrealdb::Response;
+use tracing::debug;
+
+/// Commits retried after a conflict with a concurrent transaction.
+const CONFLICT_RETRIES: u32 = 3;
+
+/// Business writes and the events they raise, committed in one SurrealDB
+/// transaction so that either both are stored or neither is.
+///
+/// Statements run in the order they are added, and their results are at
+/// the same indexes in the returned response. Parameters named
+/// `outbox_*` are reserved.
+pub struct OutboxTransaction {
+    outbox: Outbox,
+    statements: Vec<String>,
+    bindings: Map<String, Value>,
+    events: Vec<NewEvent>,
+    error: Option<DatabaseError>,
+}
+
+impl OutboxTransaction {
+    pub(super) fn new(outbox: Outbox) -> Self {
+        Self {
+            outbox,
+            statements: Vec::new(),
+            bindings: Map::new(),
+            events: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Adds a SurrealQL statement.
+    #[must_use]
+    pub fn statement(mut self, statement: impl Into<String>) -> Self {
+        self.statements.push(statement.into());
+        self
+    }
+
+    /// Binds `$name` for every statement.
+    #[must_use]
+    pub fn bind<T: Serialize>(mut self, name: impl Into<String>, value: T) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value) => {
+                self.bindings.insert(name.into(), value);
+            }
+            Err(e) => {
+                self.error.get_or_insert_with(|| {
+                    DatabaseError::QueryError(format!("Parameter cannot be bound: {e}"))
+                });
+            }
+        }
+        self
+    }
+
+    /// Records `event` with the statements.
+    #[must_use]
+    pub fn event(mut self, event: NewEvent) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Runs the statements and records the events atomically. A commit that
+    /// conflicts with a concurrent transaction is retried a few times.
+    ///
+    /// # Errors
+    ///
+    /// - `DatabaseError::QueryError` if a statement fails, in which case
+    ///   nothing is written
+    /// - `DatabaseError::Conflict` if the transaction keeps conflicting
+    pub async fn commit(self) -> Result<Response, DatabaseError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let events: Vec<Value> = self
+            .events
+            .iter()
+            .map(|event| {
+                let mut event = serde_json::to_value(event).unwrap_or_default();
+                if let Some(fields) = event.as_object_mut() {
+                    fields.insert("created_at".to_string(), now.into());
+                }
+                event
+            })
+            .collect();
+
+        let mut query = String::from("BEGIN TRANSACTION;\n");
+        for statement in &self.statements {
+            query.push_str(statement.trim().trim_end_matches(';'));
+            query.push_str(";\n");
+        }
+        if !events.is_empty() {
+            query.push_str(&record_events("$outbox_events"));
+            query.push_str(";\n");
+        }
+        query.push_str("COMMIT TRANSACTION;");
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut request = self.outbox.db().query(query.as_str());
+            for (name, value) in &self.bindings {
+                request = request.bind((name.clone(), value.clone()));
+            }
+            let result = match request.bind(("outbox_events", events.clone())).await {
+                Ok(mut response) => match transaction_failure(&mut response) {
+                    Some(e) => Err(e),
+                    None => Ok(response),
+                },
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(response) => {
+                    if !events.is_empty() {
+                        self.outbox.notify();
+                    }
+                    return Ok(response);
+                }
+                Err(e) if is_conflict(&e) && attempt <= CONFLICT_RETRIES => {
+                    debug!(attempt, "Outbox transaction conflicted, retrying");
+                }
+                Err(e) if is_conflict(&e) => {
+                    return Err(DatabaseError::Conflict(format!(
+                        "Transaction kept conflicting with concurrent writes: {e}"
+                    )));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Whether a transaction failed only because a concurrent one committed
+/// first, so that running it again can succeed.
+fn is_conflict(error: &surrealdb::Error) -> bool {
+    error.to_string().contains("can be retried")
+}
@@ -0,0 +1,28 @@
+use super::models::SchedulerConfig;
+use crate::sys::config::ConfigReader;
+use std::time::Duration;
+
+impl SchedulerConfig {
+    /// Creates a `SchedulerConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let lock_ttl = config.duration("SCHEDULER_LOCK_TTL", Duration::from_secs(60));
+        if lock_ttl < Duration::from_secs(1) {
+            config.invalid("SCHEDULER_LOCK_TTL", "must be at least 1s");
+        }
+
+        Self {
+            enabled: config.bool("SCHEDULER_ENABLED", true),
+            lock_ttl,
+            history_retention: config.duration(
+                "SCHEDULER_HISTORY_RETENTION",
+                Duration::from_secs(7 * 86_400),
+            ),
+            shutdown_timeout: config
+                .duration("SCHEDULER_SHUTDOWN_TIMEOUT", Duration::from_secs(30)),
+            database: config
+                .optional("SCHEDULER_DATABASE")
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+mod config;
+mod models;
+mod routes;
+mod runner;
+mod store;
+mod task;
+pub use models::{RunStatus, SchedulerConfig, TaskRun};
+pub use routes::{get_scheduled_tasks, get_task_runs};
+pub use runner::{Scheduler, TaskInfo};
+pub use task::{TaskContext, TaskResult};
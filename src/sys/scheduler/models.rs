@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    /// Run scheduled tasks in this instance.
+    pub enabled: bool,
+    /// How long a task lock is held without a heartbeat. Running tasks extend
+    /// it; a crashed instance releases it by letting it expire.
+    pub lock_ttl: Duration,
+    /// How long run history is kept.
+    pub history_retention: Duration,
+    /// How long running tasks get to finish after shutdown starts before they
+    /// are aborted.
+    pub shutdown_timeout: Duration,
+    /// Named database for locks and history, the primary one when `None`.
+    pub database: Option<String>,
+}
+
+/// Outcome of a task run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Stopped by a shutdown before it finished.
+    Cancelled,
+}
+
+impl RunStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// One run of a scheduled task, as stored in the `scheduler_run` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub id: String,
+    pub task: String,
+    /// Instance that ran the task.
+    pub instance: String,
+    /// The schedule occurrence this run is for, in milliseconds since the
+    /// Unix epoch.
+    pub scheduled_at: i64,
+    pub started_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+    pub status: RunStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
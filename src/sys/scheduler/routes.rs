@@ -0,0 +1,41 @@
+use crate::{AppError, sys::config::state::AppState};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Most runs returned by one history request.
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct RunsQuery {
+    #[serde(default)]
+    pub start: u64,
+    pub limit: Option<u64>,
+}
+
+/// Lists the tasks registered on this instance with their next run.
+pub async fn get_scheduled_tasks(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(json!({
+        "instance": state.scheduler.instance(),
+        "tasks": state.scheduler.tasks(),
+    }))
+}
+
+/// Returns the run history of one task across all instances, newest first.
+pub async fn get_task_runs(
+    State(state): State<Arc<AppState>>,
+    Path(task): Path<String>,
+    Query(query): Query<RunsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query.limit.unwrap_or(100).min(MAX_LIMIT);
+    let runs = state.scheduler.runs(&task, query.start, limit).await?;
+    Ok(Json(json!({
+        "task": task,
+        "runs": runs,
+    })))
+}
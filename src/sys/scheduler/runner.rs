@@ -0,0 +1,385 @@
+use super::{
+    models::{RunStatus, SchedulerConfig, TaskRun},
+    store::SchedulerStore,
+    task::{TaskContext, TaskFn, TaskResult, task_fn},
+};
+use crate::{
+    AppError,
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::{config::state::AppState, metrics::MetricsRegistry, server::Shutdown},
+};
+use chrono::{DateTime, Timelike, Utc};
+use croner::Cron;
+use rand::Rng;
+use serde::Serialize;
+use std::{
+    future::Future,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::Duration,
+};
+use tokio::{
+    sync::Notify,
+    task::{JoinError, JoinSet},
+    time::{Instant, interval_at, sleep},
+};
+use tracing::{Instrument, debug, error, info, info_span, warn};
+
+/// Longest the scheduler sleeps without re-checking its tasks.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// A registered task as listed by the admin endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub name: String,
+    pub schedule: String,
+    /// Next occurrence in milliseconds since the Unix epoch, `None` if the
+    /// schedule has no further occurrences.
+    pub next_run: Option<i64>,
+}
+
+struct ScheduledTask {
+    name: String,
+    expression: String,
+    schedule: Cron,
+    run: TaskFn,
+    next: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl ScheduledTask {
+    fn next_after(&self, time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // Occurrences must be whole seconds for every instance to compute the
+        // same ones, and the lock to tell them apart
+        let time = time.with_nanosecond(0).unwrap_or(*time);
+        self.schedule
+            .find_next_occurrence(&time, false)
+            .inspect_err(|e| error!(task = %self.name, error = %e, "Task has no next occurrence"))
+            .ok()
+    }
+}
+
+/// Runs registered tasks on cron schedules.
+///
+/// Every instance runs the same schedule, and a lock in SurrealDB makes sure
+/// each occurrence of a task runs on only one of them. Occurrences missed
+/// while no instance was running are skipped.
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+struct SchedulerInner {
+    store: SchedulerStore,
+    config: SchedulerConfig,
+    /// Identifies this instance as a lock holder.
+    instance: String,
+    tasks: RwLock<Vec<Arc<ScheduledTask>>>,
+    /// Wakes the scheduler when a task is registered.
+    notify: Notify,
+    shutdown: Shutdown,
+    runs: Mutex<JoinSet<()>>,
+    metrics: Arc<MetricsRegistry>,
+}
+
+impl Scheduler {
+    #[must_use]
+    pub fn new(
+        db: DbConnection,
+        config: SchedulerConfig,
+        shutdown: Shutdown,
+        metrics: Arc<MetricsRegistry>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                store: SchedulerStore::new(db),
+                config,
+                instance: hex::encode(rand::rng().random::<[u8; 8]>()),
+                tasks: RwLock::new(Vec::new()),
+                notify: Notify::new(),
+                shutdown,
+                runs: Mutex::new(JoinSet::new()),
+                metrics,
+            }),
+        }
+    }
+
+    /// Runs `run` on `schedule`, a cron expression with five fields or six
+    /// with leading seconds, evaluated in UTC. Task names identify locks and
+    /// history, so they must be unique and stable across instances.
+    ///
+    /// Tasks can be registered before or after the scheduler starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::ServerError` if the expression is invalid or a task
+    /// with the same name is already registered.
+    pub fn register<F, Fut>(&self, name: &str, schedule: &str, run: F) -> Result<(), AppError>
+    where
+        F: Fn(TaskContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        let cron = Cron::from_str(schedule).map_err(|e| {
+            AppError::ServerError(format!(
+                "Invalid schedule '{schedule}' for task '{name}': {e}"
+            ))
+        })?;
+        let task = ScheduledTask {
+            name: name.to_string(),
+            expression: schedule.to_string(),
+            schedule: cron,
+            run: task_fn(run),
+            next: Mutex::new(None),
+        };
+        *task.next.lock().unwrap_or_else(PoisonError::into_inner) = task.next_after(&Utc::now());
+
+        let mut tasks = self
+            .inner
+            .tasks
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if tasks.iter().any(|t| t.name == name) {
+            return Err(AppError::ServerError(format!(
+                "Task '{name}' is already scheduled"
+            )));
+        }
+        tasks.push(Arc::new(task));
+        drop(tasks);
+
+        info!(task = name, schedule, "Scheduled task");
+        self.inner.notify.notify_one();
+        Ok(())
+    }
+
+    /// Starts running tasks until shutdown.
+    pub fn start(&self, state: &Arc<AppState>) {
+        tokio::spawn(self.clone().run(state.clone()));
+        info!(instance = %self.inner.instance, "Scheduler started");
+    }
+
+    /// Waits for runs in progress to finish or be cancelled. Call once the
+    /// shutdown has been triggered.
+    pub async fn stopped(&self) {
+        let mut runs = std::mem::take(
+            &mut *self
+                .inner
+                .runs
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        if !runs.is_empty() {
+            info!(runs = runs.len(), "Waiting for scheduled tasks to stop");
+        }
+        while runs.join_next().await.is_some() {}
+    }
+
+    /// Lists the registered tasks.
+    #[must_use]
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.inner
+            .tasks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|task| TaskInfo {
+                name: task.name.clone(),
+                schedule: task.expression.clone(),
+                next_run: task
+                    .next
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .map(|next| next.timestamp_millis()),
+            })
+            .collect()
+    }
+
+    /// Returns the runs of a task on any instance, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the history cannot be queried.
+    pub async fn runs(
+        &self,
+        task: &str,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<TaskRun>, DatabaseError> {
+        self.inner.store.runs(task, start, limit).await
+    }
+
+    #[must_use]
+    pub fn instance(&self) -> &str {
+        &self.inner.instance
+    }
+
+    async fn run(self, state: Arc<AppState>) {
+        let shutdown = self.inner.shutdown.clone();
+        while !shutdown.is_triggered() {
+            let now = Utc::now();
+            let mut wake = now + MAX_SLEEP;
+            let tasks = self
+                .inner
+                .tasks
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone();
+            for task in tasks {
+                let mut next = task.next.lock().unwrap_or_else(PoisonError::into_inner);
+                if let Some(due) = *next
+                    && due <= now
+                {
+                    *next = task.next_after(&now);
+                    self.spawn_run(&state, task.clone(), due);
+                }
+                if let Some(next) = *next {
+                    wake = wake.min(next);
+                }
+            }
+
+            let sleep_for = (wake - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                () = sleep(sleep_for) => {}
+                () = self.inner.notify.notified() => {}
+                () = shutdown.triggered() => {}
+            }
+        }
+        info!("Scheduler stopped starting tasks");
+    }
+
+    fn spawn_run(&self, state: &Arc<AppState>, task: Arc<ScheduledTask>, due: DateTime<Utc>) {
+        let span = info_span!("scheduled_task", task = %task.name, scheduled_at = %due);
+        let run = self
+            .clone()
+            .execute(state.clone(), task, due.timestamp_millis())
+            .instrument(span);
+
+        let mut runs = self
+            .inner
+            .runs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Drop finished runs so the set only tracks those in progress
+        while runs.try_join_next().is_some() {}
+        runs.spawn(run);
+    }
+
+    /// Runs one occurrence of a task if this instance wins its lock.
+    async fn execute(self, state: Arc<AppState>, task: Arc<ScheduledTask>, scheduled_at: i64) {
+        let inner = &self.inner;
+        let ttl = inner.config.lock_ttl;
+        match inner
+            .store
+            .acquire(&task.name, &inner.instance, scheduled_at, now_millis(), ttl)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!("Task is running or has run on another instance");
+                return;
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to lock task, skipping this run");
+                return;
+            }
+        }
+
+        let started_at = now_millis();
+        let run_id = inner
+            .store
+            .start_run(&task.name, &inner.instance, scheduled_at, started_at)
+            .await
+            .inspect_err(|e| warn!(error = %e, "Failed to record task run"))
+            .ok();
+        debug!("Task started");
+
+        let context = TaskContext {
+            state,
+            task: task.name.clone(),
+            scheduled_at,
+            shutdown: inner.shutdown.clone(),
+        };
+        // Spawned so a panicking task fails like any other
+        let mut handle = tokio::spawn((task.run)(context).in_current_span());
+
+        let period = ttl / 3;
+        let mut heartbeat = interval_at(Instant::now() + period, period);
+        let abort = async {
+            inner.shutdown.triggered().await;
+            sleep(inner.config.shutdown_timeout).await;
+        };
+        tokio::pin!(abort);
+
+        let (status, error) = loop {
+            tokio::select! {
+                result = &mut handle => break self.outcome(result),
+                _ = heartbeat.tick() => {
+                    match inner.store.extend(&task.name, &inner.instance, now_millis(), ttl).await {
+                        Ok(true) => {}
+                        Ok(false) => warn!("Lost the task lock, another instance may run it"),
+                        Err(e) => warn!(error = %e, "Failed to extend the task lock"),
+                    }
+                }
+                () = &mut abort => {
+                    handle.abort();
+                    break (
+                        RunStatus::Cancelled,
+                        Some("Aborted after the shutdown timeout".to_string()),
+                    );
+                }
+            }
+        };
+
+        let finished_at = now_millis();
+        match status {
+            RunStatus::Failed => {
+                error!(error = error.as_deref().unwrap_or_default(), "Task failed");
+            }
+            RunStatus::Cancelled => warn!("Task cancelled by shutdown"),
+            _ => debug!(duration_ms = finished_at - started_at, "Task finished"),
+        }
+
+        if let Some(run_id) = run_id
+            && let Err(e) = inner
+                .store
+                .finish_run(&run_id, finished_at, status, error)
+                .await
+        {
+            warn!(error = %e, "Failed to record task outcome");
+        }
+        if let Err(e) = inner.store.release(&task.name, &inner.instance).await {
+            warn!(error = %e, "Failed to release task lock");
+        }
+        let retention =
+            i64::try_from(inner.config.history_retention.as_millis()).unwrap_or(i64::MAX);
+        if let Err(e) = inner
+            .store
+            .prune(&task.name, finished_at.saturating_sub(retention))
+            .await
+        {
+            warn!(error = %e, "Failed to prune task history");
+        }
+
+        inner
+            .metrics
+            .counter(
+                "scheduler_runs_total",
+                "Scheduled task runs by task and outcome",
+                &[("task", &task.name), ("status", status.as_str())],
+            )
+            .inc();
+    }
+
+    fn outcome(&self, result: Result<TaskResult, JoinError>) -> (RunStatus, Option<String>) {
+        let cancelled = self.inner.shutdown.is_triggered();
+        match result {
+            Ok(Ok(())) => (RunStatus::Succeeded, None),
+            Ok(Err(e)) if cancelled => (RunStatus::Cancelled, Some(e.to_string())),
+            Ok(Err(e)) => (RunStatus::Failed, Some(e.to_string())),
+            Err(e) if e.is_panic() => (RunStatus::Failed, Some("Task panicked".to_string())),
+            Err(e) => (RunStatus::Cancelled, Some(e.to_string())),
+        }
+    }
+}
+
+fn now_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
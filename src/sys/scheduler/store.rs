@@ -0,0 +1,183 @@
+use super::models::{RunStatus, TaskRun};
+use crate::dbs::{error::DatabaseError, models::DbConnection};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Fields selected for every run: its own fields plus the record key as `id`.
+const FIELDS: &str = "*, meta::id(id) AS id";
+
+#[derive(Debug, Deserialize)]
+struct IdRow {
+    id: String,
+}
+
+/// Task locks in `scheduler_lock` and run history in `scheduler_run`.
+#[derive(Clone)]
+pub(super) struct SchedulerStore {
+    db: DbConnection,
+}
+
+impl SchedulerStore {
+    pub(super) fn new(db: DbConnection) -> Self {
+        Self { db }
+    }
+
+    /// Takes the lock of `task` for the occurrence at `scheduled_at`.
+    ///
+    /// Fails while another instance holds an unexpired lock, and once any
+    /// instance has taken it for this or a later occurrence, so each
+    /// occurrence runs at most once.
+    pub(super) async fn acquire(
+        &self,
+        task: &str,
+        instance: &str,
+        scheduled_at: i64,
+        now: i64,
+        ttl: Duration,
+    ) -> Result<bool, DatabaseError> {
+        let locked: Option<IdRow> = self
+            .db
+            .query(
+                "UPSERT type::thing('scheduler_lock', $task) \
+                 SET holder = $holder, until = $until, scheduled_at = $at \
+                 WHERE (scheduled_at IS NONE OR scheduled_at < $at) \
+                 AND (until IS NONE OR until < $now) \
+                 RETURN meta::id(id) AS id",
+            )
+            .bind(("task", task.to_string()))
+            .bind(("holder", instance.to_string()))
+            .bind(("until", now.saturating_add(millis(ttl))))
+            .bind(("at", scheduled_at))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+        Ok(locked.is_some())
+    }
+
+    /// Extends a held lock. Returns false if the lock was lost.
+    pub(super) async fn extend(
+        &self,
+        task: &str,
+        instance: &str,
+        now: i64,
+        ttl: Duration,
+    ) -> Result<bool, DatabaseError> {
+        let extended: Option<IdRow> = self
+            .db
+            .query(
+                "UPDATE type::thing('scheduler_lock', $task) SET until = $until \
+                 WHERE holder = $holder AND until >= $now RETURN meta::id(id) AS id",
+            )
+            .bind(("task", task.to_string()))
+            .bind(("holder", instance.to_string()))
+            .bind(("until", now.saturating_add(millis(ttl))))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+        Ok(extended.is_some())
+    }
+
+    /// Releases a held lock, keeping the occurrence it was taken for.
+    pub(super) async fn release(&self, task: &str, instance: &str) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "UPDATE type::thing('scheduler_lock', $task) SET until = NONE \
+                 WHERE holder = $holder",
+            )
+            .bind(("task", task.to_string()))
+            .bind(("holder", instance.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Records the start of a run and returns its id.
+    pub(super) async fn start_run(
+        &self,
+        task: &str,
+        instance: &str,
+        scheduled_at: i64,
+        started_at: i64,
+    ) -> Result<String, DatabaseError> {
+        let created: Option<IdRow> = self
+            .db
+            .query("CREATE scheduler_run CONTENT $content RETURN meta::id(id) AS id")
+            .bind((
+                "content",
+                json!({
+                    "task": task,
+                    "instance": instance,
+                    "scheduled_at": scheduled_at,
+                    "started_at": started_at,
+                    "status": RunStatus::Running,
+                }),
+            ))
+            .await?
+            .take(0)?;
+        created
+            .map(|row| row.id)
+            .ok_or_else(|| DatabaseError::QueryError("Failed to record task run".to_string()))
+    }
+
+    /// Records how a run ended.
+    pub(super) async fn finish_run(
+        &self,
+        id: &str,
+        finished_at: i64,
+        status: RunStatus,
+        error: Option<String>,
+    ) -> Result<(), DatabaseError> {
+        let query = if error.is_some() {
+            "UPDATE type::thing('scheduler_run', $id) \
+             SET finished_at = $finished_at, status = $status, error = $error"
+        } else {
+            "UPDATE type::thing('scheduler_run', $id) \
+             SET finished_at = $finished_at, status = $status"
+        };
+        self.db
+            .query(query)
+            .bind(("id", id.to_string()))
+            .bind(("finished_at", finished_at))
+            .bind(("status", status))
+            .bind(("error", error))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Deletes the runs of `task` started before `cutoff`.
+    pub(super) async fn prune(&self, task: &str, cutoff: i64) -> Result<(), DatabaseError> {
+        self.db
+            .query("DELETE scheduler_run WHERE task = $task AND started_at < $cutoff")
+            .bind(("task", task.to_string()))
+            .bind(("cutoff", cutoff))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Returns runs of `task`, newest first.
+    pub(super) async fn runs(
+        &self,
+        task: &str,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<TaskRun>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM scheduler_run WHERE task = $task \
+                 ORDER BY started_at DESC LIMIT $limit START $start"
+            ))
+            .bind(("task", task.to_string()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
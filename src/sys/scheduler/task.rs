@@ -0,0 +1,41 @@
+use crate::sys::{config::state::AppState, server::Shutdown};
+use futures::future::BoxFuture;
+use std::{error::Error, future::Future, sync::Arc};
+
+/// Outcome of a task run. Errors are recorded in the run history; the task
+/// still runs at its next occurrence.
+pub type TaskResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// What a running task knows about itself and the application.
+#[derive(Clone)]
+pub struct TaskContext {
+    pub state: Arc<AppState>,
+    pub task: String,
+    /// The schedule occurrence being run, in milliseconds since the Unix epoch.
+    pub scheduled_at: i64,
+    pub(super) shutdown: Shutdown,
+}
+
+impl TaskContext {
+    /// Whether the application is shutting down. Long tasks should check
+    /// this and stop early; they are aborted once the shutdown timeout passes.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.shutdown.is_triggered()
+    }
+
+    /// Completes once the application starts shutting down.
+    pub async fn cancelled(&self) {
+        self.shutdown.triggered().await;
+    }
+}
+
+pub(super) type TaskFn = Arc<dyn Fn(TaskContext) -> BoxFuture<'static, TaskResult> + Send + Sync>;
+
+pub(super) fn task_fn<F, Fut>(run: F) -> TaskFn
+where
+    F: Fn(TaskContext) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = TaskResult> + Send + 'static,
+{
+    Arc::new(move |context| Box::pin(run(context)))
+}
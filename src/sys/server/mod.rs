@@ -0,0 +1,7 @@
+pub mod models;
+pub mod serve;
+pub mod shutdown;
+
+pub use models::{Listeners, ServerListener};
+pub use serve::{serve, serve_all};
+pub use shutdown::{Shutdown, spawn_signal_listener};
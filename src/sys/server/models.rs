@@ -0,0 +1,34 @@
+use crate::sys::tls::TlsListener;
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+/// A bound listener the application serves requests on.
+pub enum ServerListener {
+    Plain(TcpListener),
+    Tls(TlsListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl ServerListener {
+    /// Returns the URL scheme served by this listener.
+    #[must_use]
+    pub const fn scheme(&self) -> &'static str {
+        match self {
+            Self::Plain(_) => "http",
+            Self::Tls(_) => "https",
+            #[cfg(unix)]
+            Self::Unix(_) => "unix",
+        }
+    }
+}
+
+/// Every listener bound at startup.
+pub struct Listeners {
+    /// Listeners serving the public routes.
+    pub public: Vec<ServerListener>,
+    /// Listener serving `/health`, `/metrics` and `/admin/*`, when those are
+    /// kept off the public listeners.
+    pub admin: Option<ServerListener>,
+}
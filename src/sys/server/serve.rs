@@ -0,0 +1,88 @@
+use super::{
+    models::{Listeners, ServerListener},
+    shutdown::Shutdown,
+};
+use crate::AppError;
+use axum::{Router, serve::ListenerExt};
+use futures::future::try_join_all;
+use std::net::SocketAddr;
+use tokio::net::TcpStream;
+use tokio_rustls::server::TlsStream;
+use tracing::error;
+
+/// Serves `router` on `listener` until `shutdown` is triggered and open
+/// requests have finished, exposing TCP peer addresses to handlers as
+/// `ConnectInfo<SocketAddr>`.
+///
+/// # Errors
+///
+/// Returns `AppError::ServerError` if the server fails.
+pub async fn serve(
+    listener: ServerListener,
+    router: Router,
+    shutdown: Shutdown,
+) -> Result<(), AppError> {
+    let signal = async move { shutdown.triggered().await };
+    let result = match listener {
+        ServerListener::Plain(listener) => {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(signal)
+            .await
+        }
+        // Tapping the listener lets `SocketAddr` act as its connect info
+        ServerListener::Tls(listener) => {
+            axum::serve(
+                listener.tap_io(ignore_io),
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(signal)
+            .await
+        }
+        #[cfg(unix)]
+        ServerListener::Unix(listener) => {
+            axum::serve(listener, router.into_make_service())
+                .with_graceful_shutdown(signal)
+                .await
+        }
+    };
+
+    result.map_err(|e| {
+        error!(error = %e, "The server encountered an unrecoverable error");
+        AppError::ServerError(e.to_string())
+    })
+}
+
+/// Serves every listener concurrently until one of them fails or `shutdown`
+/// is triggered.
+///
+/// `admin` routes are served on the admin listener when there is one, and
+/// alongside `public` routes otherwise.
+///
+/// # Errors
+///
+/// Returns `AppError::ServerError` if any server fails.
+pub async fn serve_all(
+    listeners: Listeners,
+    public: Router,
+    admin: Router,
+    shutdown: &Shutdown,
+) -> Result<(), AppError> {
+    let (public, admin) = match listeners.admin {
+        Some(listener) => (public, Some((listener, admin))),
+        None => (public.merge(admin), None),
+    };
+
+    let servers = listeners
+        .public
+        .into_iter()
+        .map(|listener| (listener, public.clone()))
+        .chain(admin)
+        .map(|(listener, router)| serve(listener, router, shutdown.clone()));
+
+    try_join_all(servers).await.map(|_| ())
+}
+
+const fn ignore_io(_: &mut TlsStream<TcpStream>) {}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// Process-wide shutdown signal.
+///
+/// Servers stop accepting connections once it is triggered, and background
+/// tasks use it to stop starting new work and cancel what is running.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Starts the shutdown. Later calls do nothing.
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    #[must_use]
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Completes once the shutdown has been triggered.
+    pub async fn triggered(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives in `self`, so the channel cannot close
+        let _ = receiver.wait_for(|triggered| *triggered).await;
+    }
+}
+
+/// Triggers `shutdown` on Ctrl+C, or on SIGTERM on Unix.
+pub fn spawn_signal_listener(shutdown: Shutdown) {
+    tokio::spawn(async move {
+        let interrupt = async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                error!(error = %e, "Failed to listen for Ctrl+C");
+                std::future::pending::<()>().await;
+            }
+        };
+
+        #[cfg(unix)]
+        let terminate = async {
+            use tokio::signal::unix::{SignalKind, signal};
+            match signal(SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to listen for SIGTERM");
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            () = interrupt => {}
+            () = terminate => {}
+        }
+        info!("Shutdown signal received, draining connections");
+        shutdown.trigger();
+    });
+}
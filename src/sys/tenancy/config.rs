@@ -0,0 +1,109 @@
+use super::models::{TenancyConfig, TenancyMode, TenantSource};
+use crate::sys::{
+    config::ConfigReader,
+    env::{self, EnvEnum},
+};
+use std::{path::PathBuf, time::Duration};
+
+impl TenancyConfig {
+    /// Creates a `TenancyConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config.bool("TENANCY_ENABLED", false);
+        let mode = config.enumeration("TENANCY_MODE", TenancyMode::Database);
+
+        let prefix = config.string("TENANCY_PREFIX", "tenant_");
+        if !prefix
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+        {
+            config.invalid(
+                "TENANCY_PREFIX",
+                "may only contain lowercase letters, digits and underscores",
+            );
+        }
+
+        let mut sources = Vec::new();
+        for name in config.list("TENANCY_SOURCES", "header") {
+            match env::parse_enum::<TenantSource>(&name) {
+                Some(source) if !sources.contains(&source) => sources.push(source),
+                Some(_) => {}
+                None => config.invalid(
+                    "TENANCY_SOURCES",
+                    format!(
+                        "'{name}' is not one of {}",
+                        TenantSource::names().join(", ")
+                    ),
+                ),
+            }
+        }
+        if enabled && sources.is_empty() {
+            config.invalid("TENANCY_SOURCES", "must name at least one source");
+        }
+
+        let base_domain = config
+            .optional("TENANCY_BASE_DOMAIN")
+            .map(|domain| domain.trim().trim_matches('.').to_lowercase())
+            .filter(|domain| !domain.is_empty());
+        if enabled && sources.contains(&TenantSource::Subdomain) && base_domain.is_none() {
+            config.invalid(
+                "TENANCY_BASE_DOMAIN",
+                "must be set when TENANCY_SOURCES includes subdomain",
+            );
+        }
+
+        let jwt_secret = config.optional_secret("TENANCY_JWT_SECRET");
+        if enabled && sources.contains(&TenantSource::Jwt) && jwt_secret.is_none() {
+            config.invalid(
+                "TENANCY_JWT_SECRET",
+                "must be set when TENANCY_SOURCES includes jwt",
+            );
+        }
+
+        let root_username = config
+            .optional("TENANCY_ROOT_USERNAME")
+            .filter(|name| !name.trim().is_empty());
+        let root_password = config.optional_secret("TENANCY_ROOT_PASSWORD");
+        if enabled && mode == TenancyMode::Namespace {
+            if root_username.is_none() {
+                config.invalid(
+                    "TENANCY_ROOT_USERNAME",
+                    "must be set when TENANCY_MODE is namespace",
+                );
+            }
+            if root_password.is_none() {
+                config.invalid(
+                    "TENANCY_ROOT_PASSWORD",
+                    "must be set when TENANCY_MODE is namespace",
+                );
+            }
+        }
+
+        let max_connections = config.parsed("TENANCY_MAX_CONNECTIONS", 100);
+        if max_connections == 0 {
+            config.invalid("TENANCY_MAX_CONNECTIONS", "must be greater than zero");
+        }
+
+        Self {
+            enabled,
+            mode,
+            prefix,
+            sources,
+            header: config
+                .string("TENANCY_HEADER", "x-tenant-id")
+                .to_lowercase(),
+            base_domain,
+            jwt_secret,
+            jwt_claim: config.string("TENANCY_JWT_CLAIM", "tenant"),
+            root_username,
+            root_password,
+            migrations_dir: PathBuf::from(config.string("TENANCY_MIGRATIONS_DIR", "migrations")),
+            cache_ttl: config.duration("TENANCY_CACHE_TTL", Duration::from_secs(30)),
+            max_connections: max_connections.max(1),
+            idle_timeout: config.duration("TENANCY_IDLE_TIMEOUT", Duration::from_secs(600)),
+            database: config
+                .optional("TENANCY_DATABASE")
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+}
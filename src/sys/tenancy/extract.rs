@@ -0,0 +1,62 @@
+use super::resolve::resolve_tenant;
+use crate::{
+    AppError,
+    dbs::{models::DbConnection, repository::Repository},
+    sys::config::state::AppState,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::{Serialize, de::DeserializeOwned};
+use std::sync::Arc;
+use tracing::debug;
+
+/// The tenant a request is for, with the connection to its database.
+///
+/// Resolved from the sources in `TENANCY_SOURCES`. Rejects the request with
+/// 400 if it names no tenant, 404 if the tenant does not exist and 403 if
+/// it is suspended.
+#[derive(Clone)]
+pub struct Tenant {
+    pub id: String,
+    pub db: DbConnection,
+}
+
+impl Tenant {
+    /// Returns a repository of `table` in the tenant's database. It cannot
+    /// record outbox events, which are only relayed from the primary
+    /// database.
+    #[must_use]
+    pub fn repository<T>(&self, table: impl Into<String>) -> Repository<T>
+    where
+        T: Serialize + DeserializeOwned + Send + Sync,
+    {
+        Repository::new(self.db.clone(), table).for_tenant(&self.id)
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for Tenant {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(tenant) = parts.extensions.get::<Self>() {
+            return Ok(tenant.clone());
+        }
+
+        let config = state.config.load();
+        if !config.tenancy.enabled {
+            return Err(AppError::ServerError(
+                "Multi-tenancy is disabled".to_string(),
+            ));
+        }
+        let id = resolve_tenant(&config.tenancy, parts)?
+            .ok_or_else(|| AppError::BadRequest("The request names no tenant".to_string()))?;
+        let db = state.tenants.connection(&id).await?;
+        debug!(tenant = %id, "Resolved tenant");
+
+        let tenant = Self { id, db };
+        parts.extensions.insert(tenant.clone());
+        Ok(tenant)
+    }
+}
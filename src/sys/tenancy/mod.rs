@@ -0,0 +1,18 @@
+mod config;
+mod extract;
+mod models;
+mod resolve;
+mod routes;
+mod store;
+mod tenants;
+pub use extract::Tenant;
+pub use models::{
+    NewTenant, TenancyConfig, TenancyMode, TenantMigration, TenantProvisioned, TenantRecord,
+    TenantSource, TenantStatus, validate_tenant_id,
+};
+pub use resolve::resolve_tenant;
+pub use routes::{
+    get_tenant, get_tenant_health, list_migrations, list_tenants, migrate_tenant, migrate_tenants,
+    provision_tenant, resume_tenant, suspend_tenant,
+};
+pub use tenants::Tenants;
@@ -0,0 +1,174 @@
+use crate::sys::{
+    env::{EnvEnum, Secret},
+    events::Event,
+};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+
+/// Longest tenant id, so that derived database names stay short.
+pub(super) const MAX_ID_LENGTH: usize = 63;
+
+#[derive(Debug, Clone)]
+pub struct TenancyConfig {
+    /// Serve requests for multiple tenants.
+    pub enabled: bool,
+    /// How the data of tenants is separated.
+    pub mode: TenancyMode,
+    /// Prepended to tenant ids to name their database or namespace.
+    pub prefix: String,
+    /// Where the tenant of a request is read from. Every source that names
+    /// a tenant must name the same one, and with `jwt` a token must name it.
+    /// Without `jwt`, clients choose their tenant unchecked.
+    pub sources: Vec<TenantSource>,
+    /// Header naming the tenant for the `header` source.
+    pub header: String,
+    /// Domain under which every tenant has a subdomain, such as
+    /// `example.com` for `acme.example.com`.
+    pub base_domain: Option<String>,
+    /// HS256 key that bearer tokens of the `jwt` source are signed with.
+    pub jwt_secret: Option<Secret>,
+    /// Claim of the bearer token naming the tenant.
+    pub jwt_claim: String,
+    /// Root user that defines tenant namespaces, and the `DB_*` user in
+    /// each of them, in `namespace` mode.
+    pub root_username: Option<String>,
+    pub root_password: Option<Secret>,
+    /// Directory of `*.surql` migrations applied to every tenant.
+    pub migrations_dir: PathBuf,
+    /// How long the status of a tenant is trusted before it is read again,
+    /// so that suspensions made on other instances take effect.
+    pub cache_ttl: Duration,
+    /// Most tenant connections kept open. The least recently used one is
+    /// closed to open another.
+    pub max_connections: usize,
+    /// How long a tenant connection is kept open without being used.
+    pub idle_timeout: Duration,
+    /// Named database holding the tenant registry, the primary one when
+    /// `None`.
+    pub database: Option<String>,
+}
+
+/// How the data of tenants is separated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenancyMode {
+    /// A database per tenant in the namespace of the primary database.
+    Database,
+    /// A namespace per tenant, holding a database named like the primary
+    /// one. Needs root credentials to define namespaces.
+    Namespace,
+}
+
+impl EnvEnum for TenancyMode {
+    const VARIANTS: &'static [(&'static str, Self)] =
+        &[("database", Self::Database), ("namespace", Self::Namespace)];
+}
+
+/// A part of the request the tenant is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantSource {
+    /// The configured header, trusted as the client sent it.
+    Header,
+    /// The first label of the host under the base domain.
+    Subdomain,
+    /// A claim of a verified `Authorization: Bearer` token.
+    Jwt,
+}
+
+impl EnvEnum for TenantSource {
+    const VARIANTS: &'static [(&'static str, Self)] = &[
+        ("header", Self::Header),
+        ("subdomain", Self::Subdomain),
+        ("jwt", Self::Jwt),
+    ];
+}
+
+impl TenantSource {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Header => "header",
+            Self::Subdomain => "subdomain",
+            Self::Jwt => "jwt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantStatus {
+    /// Created, but its migrations have not all been applied yet.
+    Provisioning,
+    Active,
+    /// Requests for the tenant are rejected; its data is kept.
+    Suspended,
+}
+
+/// A tenant, as stored in the `tenant` table of the registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantRecord {
+    pub id: String,
+    pub name: String,
+    pub status: TenantStatus,
+    /// Where the tenant's data is, fixed when it is provisioned.
+    pub namespace: String,
+    pub database: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Why provisioning last failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// A tenant to provision.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewTenant {
+    /// Lowercase letters, digits and inner hyphens, as in a DNS label.
+    pub id: String,
+    /// Display name, the id when not given.
+    pub name: Option<String>,
+}
+
+/// Published on the event bus once a tenant is provisioned.
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantProvisioned {
+    pub tenant: TenantRecord,
+    /// Versions of the migrations applied to it.
+    pub migrations: Vec<String>,
+}
+
+impl Event for TenantProvisioned {
+    const NAME: &'static str = "tenant.provisioned";
+}
+
+/// The outcome of migrating one tenant.
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantMigration {
+    pub tenant: String,
+    /// Versions of the migrations applied.
+    pub applied: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Checks that `id` can name a tenant, a subdomain and a database.
+///
+/// # Errors
+///
+/// Returns why the id is not valid.
+pub fn validate_tenant_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > MAX_ID_LENGTH {
+        return Err(format!(
+            "Tenant id must be 1 to {MAX_ID_LENGTH} characters long"
+        ));
+    }
+    if !id
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    {
+        return Err("Tenant id may only contain lowercase letters, digits and hyphens".to_string());
+    }
+    if id.starts_with('-') || id.ends_with('-') {
+        return Err("Tenant id must not start or end with a hyphen".to_string());
+    }
+    Ok(())
+}
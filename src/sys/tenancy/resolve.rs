@@ -0,0 +1,118 @@
+use super::models::{TenancyConfig, TenantSource, validate_tenant_id};
+use crate::AppError;
+use axum::http::{header, request::Parts};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde_json::{Map, Value};
+
+/// Returns the tenant named by the configured sources of a request, or
+/// `None` if no source names one. With the `jwt` source, the tenant must be
+/// named by a verified bearer token; the header and subdomain alone are
+/// trusted as the client sent them.
+///
+/// # Errors
+///
+/// - `AppError::Unauthorized` if a bearer token fails verification, or the
+///   `jwt` source is configured and no token names the tenant
+/// - `AppError::Forbidden` if sources name different tenants, so a header
+///   cannot select another tenant than the one a token was issued for
+/// - `AppError::BadRequest` if the tenant id is not valid
+pub fn resolve_tenant(config: &TenancyConfig, parts: &Parts) -> Result<Option<String>, AppError> {
+    let mut resolved: Option<(TenantSource, String)> = None;
+    let mut verified = false;
+    for &source in &config.sources {
+        let found = match source {
+            TenantSource::Header => from_header(config, parts),
+            TenantSource::Subdomain => from_subdomain(config, parts),
+            TenantSource::Jwt => from_jwt(config, parts)?,
+        };
+        let Some(found) = found else { continue };
+        verified |= source == TenantSource::Jwt;
+        match &resolved {
+            Some((first, tenant)) if *tenant != found => {
+                return Err(AppError::Forbidden(format!(
+                    "The tenant from the {} does not match the tenant from the {}",
+                    source.name(),
+                    first.name()
+                )));
+            }
+            Some(_) => {}
+            None => resolved = Some((source, found)),
+        }
+    }
+
+    let Some((source, tenant)) = resolved else {
+        return Ok(None);
+    };
+    if !verified && config.sources.contains(&TenantSource::Jwt) {
+        return Err(AppError::Unauthorized(format!(
+            "The tenant from the {} must be named by a bearer token",
+            source.name()
+        )));
+    }
+    validate_tenant_id(&tenant).map_err(AppError::BadRequest)?;
+    Ok(Some(tenant))
+}
+
+fn from_header(config: &TenancyConfig, parts: &Parts) -> Option<String> {
+    parts
+        .headers
+        .get(config.header.as_str())
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads `acme` from `acme.example.com`. The base domain itself and
+/// deeper subdomains name no tenant.
+fn from_subdomain(config: &TenancyConfig, parts: &Parts) -> Option<String> {
+    let base = config.base_domain.as_deref()?;
+    // HTTP/2 requests carry the host in the URI instead of a header
+    let host = parts
+        .headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| parts.uri.host())?;
+    let host = host.rsplit_once(':').map_or(host, |(name, port)| {
+        if port.bytes().all(|b| b.is_ascii_digit()) {
+            name
+        } else {
+            host
+        }
+    });
+    let host = host.to_lowercase();
+    let label = host.strip_suffix(base)?.strip_suffix('.')?;
+    (!label.is_empty() && !label.contains('.')).then(|| label.to_string())
+}
+
+/// Reads the tenant claim of an HS256 `Authorization: Bearer` token. The
+/// token must not be expired.
+fn from_jwt(config: &TenancyConfig, parts: &Parts) -> Result<Option<String>, AppError> {
+    let Some(secret) = &config.jwt_secret else {
+        return Ok(None);
+    };
+    let Some(token) = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return Ok(None);
+    };
+
+    let claims = jsonwebtoken::decode::<Map<String, Value>>(
+        token.trim(),
+        &DecodingKey::from_secret(secret.expose().as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|e| AppError::Unauthorized(format!("Invalid bearer token: {e}")))?
+    .claims;
+
+    match claims.get(&config.jwt_claim) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(tenant)) => Ok(Some(tenant.trim().to_lowercase())),
+        Some(_) => Err(AppError::Unauthorized(format!(
+            "The '{}' claim of the bearer token is not a string",
+            config.jwt_claim
+        ))),
+    }
+}
@@ -0,0 +1,148 @@
+use super::models::NewTenant;
+use crate::{
+    AppError,
+    dbs::error::DatabaseError,
+    sys::{config::state::AppState, health::models::HealthStatus},
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Most tenants returned by one request.
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct TenantsQuery {
+    #[serde(default)]
+    pub start: u64,
+    pub limit: Option<u64>,
+}
+
+fn require_enabled(state: &AppState) -> Result<(), AppError> {
+    if state.tenants.is_enabled() {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("Multi-tenancy is disabled".to_string()))
+    }
+}
+
+/// Lists tenants by id.
+pub async fn list_tenants(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TenantsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    let limit = query.limit.unwrap_or(100).min(MAX_LIMIT);
+    let tenants = state.tenants.list(query.start, limit).await?;
+    Ok(Json(json!({ "tenants": tenants })))
+}
+
+/// Registers a tenant, creates its database and applies every migration.
+pub async fn provision_tenant(
+    State(state): State<Arc<AppState>>,
+    Json(input): Json<NewTenant>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    let (tenant, applied) = state.tenants.provision(input).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "tenant": tenant,
+            "applied": applied,
+        })),
+    ))
+}
+
+/// Returns a tenant with the migrations applied to it.
+pub async fn get_tenant(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    let tenant = state
+        .tenants
+        .get(&id)
+        .await?
+        .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+    let migrations = state.tenants.applied(&id).await?;
+    Ok(Json(json!({
+        "tenant": tenant,
+        "migrations": migrations,
+    })))
+}
+
+/// Applies the migrations a tenant is missing.
+pub async fn migrate_tenant(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    let (tenant, applied) = state.tenants.migrate(&id).await?;
+    Ok(Json(json!({
+        "tenant": tenant,
+        "applied": applied,
+    })))
+}
+
+/// Lists the migrations every tenant is migrated to, in order.
+pub async fn list_migrations(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    Ok(Json(
+        json!({ "migrations": state.tenants.migrations().all() }),
+    ))
+}
+
+/// Applies missing migrations to every tenant. Responds 207 if any tenant
+/// failed.
+pub async fn migrate_tenants(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    let tenants = state.tenants.migrate_all().await?;
+    let status = if tenants.iter().any(|tenant| tenant.error.is_some()) {
+        StatusCode::MULTI_STATUS
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(json!({ "tenants": tenants }))))
+}
+
+/// Rejects further requests for a tenant, keeping its data.
+pub async fn suspend_tenant(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    Ok(Json(state.tenants.suspend(&id).await?))
+}
+
+/// Serves a suspended tenant again.
+pub async fn resume_tenant(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    Ok(Json(state.tenants.resume(&id).await?))
+}
+
+/// Checks the database of a tenant. Responds 503 if it is unhealthy.
+pub async fn get_tenant_health(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    require_enabled(&state)?;
+    let health = state.tenants.check_tenant(&id).await?;
+    let status = match health.status {
+        HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+        HealthStatus::Healthy | HealthStatus::Degraded => StatusCode::OK,
+    };
+    Ok((status, Json(health)))
+}
@@ -0,0 +1,97 @@
+use super::models::{TenantRecord, TenantStatus};
+use crate::dbs::{
+    error::{DatabaseError, is_duplicate},
+    models::DbConnection,
+};
+
+/// Fields selected for every record: its own fields plus the key as `id`.
+const FIELDS: &str = "*, meta::id(id) AS id";
+
+/// The registry of tenants in SurrealDB.
+#[derive(Clone)]
+pub(super) struct TenantStore {
+    db: DbConnection,
+}
+
+impl TenantStore {
+    pub(super) fn new(db: DbConnection) -> Self {
+        Self { db }
+    }
+
+    /// Creates a tenant. Fails with `DatabaseError::Conflict` if its id is
+    /// taken.
+    pub(super) async fn create(
+        &self,
+        tenant: &TenantRecord,
+    ) -> Result<TenantRecord, DatabaseError> {
+        let mut content = serde_json::to_value(tenant)
+            .map_err(|e| DatabaseError::QueryError(format!("Tenant cannot be stored: {e}")))?;
+        if let Some(fields) = content.as_object_mut() {
+            fields.remove("id");
+        }
+        let created: Option<TenantRecord> = self
+            .db
+            .query(format!(
+                "CREATE type::thing('tenant', $id) CONTENT $content RETURN {FIELDS}"
+            ))
+            .bind(("id", tenant.id.clone()))
+            .bind(("content", content))
+            .await?
+            .take(0)
+            .map_err(|e| {
+                if is_duplicate(&e) {
+                    DatabaseError::Conflict(format!("Tenant '{}' already exists", tenant.id))
+                } else {
+                    e.into()
+                }
+            })?;
+        created.ok_or_else(|| DatabaseError::QueryError("Failed to create tenant".to_string()))
+    }
+
+    pub(super) async fn get(&self, id: &str) -> Result<Option<TenantRecord>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!("SELECT {FIELDS} FROM type::thing('tenant', $id)"))
+            .bind(("id", id.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    pub(super) async fn list(
+        &self,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<TenantRecord>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM tenant ORDER BY id LIMIT $limit START $start"
+            ))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+
+    /// Sets the status of a tenant and why provisioning last failed.
+    /// Returns `None` if there is no such tenant.
+    pub(super) async fn set_status(
+        &self,
+        id: &str,
+        status: TenantStatus,
+        error: Option<String>,
+    ) -> Result<Option<TenantRecord>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "UPDATE type::thing('tenant', $id) \
+                 SET status = $status, last_error = $error, updated_at = $now RETURN {FIELDS}"
+            ))
+            .bind(("id", id.to_string()))
+            .bind(("status", status))
+            .bind(("error", error))
+            .bind(("now", chrono::Utc::now().timestamp_millis()))
+            .await?
+            .take(0)?)
+    }
+}
@@ -0,0 +1,641 @@
+use super::{
+    models::{
+        NewTenant, TenancyConfig, TenancyMode, TenantMigration, TenantProvisioned, TenantRecord,
+        TenantStatus, validate_tenant_id,
+    },
+    store::TenantStore,
+};
+use crate::{
+    AppError,
+    dbs::{
+        connector::{connect, connect_root},
+        error::DatabaseError,
+        migrations::{AppliedMigration, Migrations},
+        models::{Database, DbConfig, DbConnection},
+    },
+    sys::{
+        events::EventBus,
+        health::models::{ComponentHealth, HealthCheck, HealthStatus},
+        metrics::MetricsRegistry,
+    },
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+};
+use tokio::{
+    sync::OnceCell,
+    time::{Instant, timeout},
+};
+use tracing::{info, warn};
+
+/// Tenants listed per query when migrating all of them.
+const PAGE_SIZE: u64 = 100;
+
+/// The tenants of this service, their registry and their connections.
+///
+/// Each tenant's data lives in a database or namespace of its own, fixed
+/// when it is provisioned. Connections are opened on first use and closed
+/// once idle for `TENANCY_IDLE_TIMEOUT`, or when `TENANCY_MAX_CONNECTIONS`
+/// is reached and another tenant needs one; each is a separate client
+/// session, since clones of a connection share the namespace and database
+/// they use. Sessions sign in as the `DB_*` user, in namespace mode as the
+/// one defined in the tenant's namespace.
+#[derive(Clone)]
+pub struct Tenants {
+    inner: Arc<TenantsInner>,
+}
+
+struct TenantsInner {
+    store: TenantStore,
+    config: TenancyConfig,
+    /// Settings tenant connections are derived from.
+    base: DbConfig,
+    migrations: Migrations,
+    connections: Mutex<HashMap<String, TenantConnection>>,
+    /// Tenants as last read from the registry, with when they were read.
+    records: Mutex<HashMap<String, (TenantRecord, Instant)>>,
+    events: EventBus,
+    metrics: Arc<MetricsRegistry>,
+}
+
+/// A tenant's connection, opened once for all callers.
+struct TenantConnection {
+    cell: Arc<OnceCell<DbConnection>>,
+    last_used: Instant,
+}
+
+impl Tenants {
+    /// Creates the tenants registered in `db`, connecting to them with the
+    /// endpoint and credentials of `base`.
+    #[must_use]
+    pub fn new(
+        db: DbConnection,
+        config: TenancyConfig,
+        base: DbConfig,
+        migrations: Migrations,
+        events: EventBus,
+        metrics: Arc<MetricsRegistry>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(TenantsInner {
+                store: TenantStore::new(db),
+                config,
+                base,
+                migrations,
+                connections: Mutex::new(HashMap::new()),
+                records: Mutex::new(HashMap::new()),
+                events,
+                metrics,
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.inner.config.enabled
+    }
+
+    /// Returns the migrations applied to every tenant.
+    #[must_use]
+    pub fn migrations(&self) -> &Migrations {
+        &self.inner.migrations
+    }
+
+    /// Returns the connection of an active tenant, connecting on first use.
+    ///
+    /// # Errors
+    ///
+    /// - `AppError::Database` if the tenant does not exist, or its database
+    ///   cannot be reached
+    /// - `AppError::Forbidden` if the tenant is suspended
+    /// - `AppError::ServiceUnavailable` if the tenant is still provisioning
+    pub async fn connection(&self, id: &str) -> Result<DbConnection, AppError> {
+        let tenant = self
+            .cached(id)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        match tenant.status {
+            TenantStatus::Active => Ok(self.connect(&tenant).await?),
+            TenantStatus::Suspended => {
+                Err(AppError::Forbidden(format!("Tenant '{id}' is suspended")))
+            }
+            TenantStatus::Provisioning => Err(AppError::ServiceUnavailable(format!(
+                "Tenant '{id}' is not provisioned yet"
+            ))),
+        }
+    }
+
+    /// Returns a tenant from the registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the query fails.
+    pub async fn get(&self, id: &str) -> Result<Option<TenantRecord>, DatabaseError> {
+        self.inner.store.get(id).await
+    }
+
+    /// Lists tenants by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the query fails.
+    pub async fn list(&self, start: u64, limit: u64) -> Result<Vec<TenantRecord>, DatabaseError> {
+        self.inner.store.list(start, limit).await
+    }
+
+    /// Registers a tenant, creates its database, applies every migration
+    /// to it and activates it. A tenant whose migrations fail stays
+    /// provisioning, with the error, until [`Tenants::migrate`] succeeds.
+    ///
+    /// # Errors
+    ///
+    /// - `AppError::UnprocessableEntity` if the id is not valid
+    /// - `AppError::Database` if the id is taken, or creating the database
+    ///   or a migration fails
+    pub async fn provision(
+        &self,
+        tenant: NewTenant,
+    ) -> Result<(TenantRecord, Vec<String>), AppError> {
+        validate_tenant_id(&tenant.id).map_err(AppError::UnprocessableEntity)?;
+        let (namespace, database) = self.location(&tenant.id);
+        let now = chrono::Utc::now().timestamp_millis();
+        let record = self
+            .inner
+            .store
+            .create(&TenantRecord {
+                name: tenant
+                    .name
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or_else(|| tenant.id.clone()),
+                id: tenant.id,
+                status: TenantStatus::Provisioning,
+                namespace,
+                database,
+                created_at: now,
+                updated_at: now,
+                last_error: None,
+            })
+            .await?;
+        info!(
+            tenant = %record.id,
+            namespace = %record.namespace,
+            database = %record.database,
+            "Provisioning tenant"
+        );
+        self.migrate_record(record).await
+    }
+
+    /// Applies the migrations a tenant is missing. Activates a tenant that
+    /// is still provisioning.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the tenant does not exist, or its
+    /// database cannot be reached, or a migration fails.
+    pub async fn migrate(&self, id: &str) -> Result<(TenantRecord, Vec<String>), AppError> {
+        let record = self
+            .inner
+            .store
+            .get(id)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        self.migrate_record(record).await
+    }
+
+    /// Migrates every tenant, one at a time. A tenant that fails does not
+    /// stop the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the tenants cannot be listed.
+    pub async fn migrate_all(&self) -> Result<Vec<TenantMigration>, DatabaseError> {
+        let mut outcomes = Vec::new();
+        let mut start = 0;
+        loop {
+            let page = self.inner.store.list(start, PAGE_SIZE).await?;
+            let count = page.len();
+            for record in page {
+                let tenant = record.id.clone();
+                outcomes.push(match self.migrate_record(record).await {
+                    Ok((_, applied)) => TenantMigration {
+                        tenant,
+                        applied,
+                        error: None,
+                    },
+                    Err(e) => TenantMigration {
+                        tenant,
+                        applied: Vec::new(),
+                        error: Some(e.to_string()),
+                    },
+                });
+            }
+            if (count as u64) < PAGE_SIZE {
+                return Ok(outcomes);
+            }
+            start += PAGE_SIZE;
+        }
+    }
+
+    /// Returns the migrations applied to a tenant.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the tenant does not exist, or its
+    /// database cannot be reached.
+    pub async fn applied(&self, id: &str) -> Result<Vec<AppliedMigration>, AppError> {
+        let record = self
+            .inner
+            .store
+            .get(id)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        let db = self.connect(&record).await?;
+        Ok(Migrations::applied(&db).await?)
+    }
+
+    /// Rejects further requests for a tenant, keeping its data. Other
+    /// instances notice within `TENANCY_CACHE_TTL`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the tenant does not exist.
+    pub async fn suspend(&self, id: &str) -> Result<TenantRecord, AppError> {
+        let record = self
+            .inner
+            .store
+            .set_status(id, TenantStatus::Suspended, None)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        self.forget(id);
+        info!(tenant = %id, "Suspended tenant");
+        Ok(record)
+    }
+
+    /// Serves a suspended tenant again.
+    ///
+    /// # Errors
+    ///
+    /// - `AppError::Database` if the tenant does not exist
+    /// - `AppError::Conflict` if the tenant has not finished provisioning
+    pub async fn resume(&self, id: &str) -> Result<TenantRecord, AppError> {
+        let record = self
+            .inner
+            .store
+            .get(id)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        if record.status == TenantStatus::Provisioning {
+            return Err(AppError::Conflict(format!(
+                "Tenant '{id}' has not finished provisioning; migrate it instead"
+            )));
+        }
+        let record = self
+            .inner
+            .store
+            .set_status(id, TenantStatus::Active, None)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        self.forget(id);
+        info!(tenant = %id, "Resumed tenant");
+        Ok(record)
+    }
+
+    /// Checks the database of one tenant, connecting to it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the tenant does not exist.
+    pub async fn check_tenant(&self, id: &str) -> Result<ComponentHealth, AppError> {
+        let record = self
+            .inner
+            .store
+            .get(id)
+            .await?
+            .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+        let name = format!("Tenant ({id})");
+        Ok(match self.connect(&record).await {
+            Ok(db) => self.database(name, db).check().await,
+            Err(e) => ComponentHealth {
+                name,
+                status: HealthStatus::Unhealthy,
+                message: Some(e.to_string()),
+            },
+        })
+    }
+
+    /// Creates the tenant's database if needed and applies the migrations
+    /// it is missing, recording the outcome on a provisioning tenant.
+    async fn migrate_record(
+        &self,
+        record: TenantRecord,
+    ) -> Result<(TenantRecord, Vec<String>), AppError> {
+        let id = record.id.clone();
+        let result = async {
+            self.define(&record).await?;
+            let db = self.connect(&record).await?;
+            self.inner.migrations.apply(&db).await
+        }
+        .await;
+
+        match (result, record.status) {
+            (Ok(applied), TenantStatus::Provisioning) => {
+                let record = self
+                    .inner
+                    .store
+                    .set_status(&id, TenantStatus::Active, None)
+                    .await?
+                    .ok_or_else(|| DatabaseError::NotFound(format!("Tenant '{id}' not found")))?;
+                self.forget_record(&id);
+                info!(tenant = %id, migrations = applied.len(), "Provisioned tenant");
+                self.inner.events.publish(TenantProvisioned {
+                    tenant: record.clone(),
+                    migrations: applied.clone(),
+                });
+                Ok((record, applied))
+            }
+            (Ok(applied), _) => Ok((record, applied)),
+            (Err(e), status) => {
+                warn!(tenant = %id, error = %e, "Failed to migrate tenant");
+                if status == TenantStatus::Provisioning {
+                    self.inner
+                        .store
+                        .set_status(&id, TenantStatus::Provisioning, Some(e.to_string()))
+                        .await?;
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Returns a tenant, read again from the registry once the cached
+    /// copy is older than `TENANCY_CACHE_TTL`.
+    async fn cached(&self, id: &str) -> Result<Option<TenantRecord>, DatabaseError> {
+        if let Some((record, read_at)) = lock(&self.inner.records).get(id)
+            && read_at.elapsed() < self.inner.config.cache_ttl
+        {
+            return Ok(Some(record.clone()));
+        }
+
+        let record = self.inner.store.get(id).await?;
+        match &record {
+            Some(record) => {
+                lock(&self.inner.records).insert(id.to_string(), (record.clone(), Instant::now()));
+                if record.status == TenantStatus::Suspended {
+                    self.disconnect(id);
+                }
+            }
+            None => {
+                lock(&self.inner.records).remove(id);
+                self.disconnect(id);
+            }
+        }
+        Ok(record)
+    }
+
+    /// Returns the connection of a tenant, opening it once for all callers.
+    async fn connect(&self, tenant: &TenantRecord) -> Result<DbConnection, DatabaseError> {
+        let cell = {
+            let mut connections = lock(&self.inner.connections);
+            let connection =
+                connections
+                    .entry(tenant.id.clone())
+                    .or_insert_with(|| TenantConnection {
+                        cell: Arc::default(),
+                        last_used: Instant::now(),
+                    });
+            connection.last_used = Instant::now();
+            connection.cell.clone()
+        };
+        let db = cell.get_or_try_init(|| self.open(tenant)).await.cloned();
+        self.evict(Some(&tenant.id));
+        self.count_connections();
+        db
+    }
+
+    async fn open(&self, tenant: &TenantRecord) -> Result<DbConnection, DatabaseError> {
+        let config = self.db_config(tenant);
+        let db = match self.with_timeout(tenant, connect(&config)).await {
+            // Tenants provisioned before their namespace had a user of its own
+            Err(DatabaseError::AuthenticationError(_))
+                if self.inner.config.mode == TenancyMode::Namespace =>
+            {
+                self.define(tenant).await?;
+                self.with_timeout(tenant, connect(&config)).await?
+            }
+            result => result?,
+        };
+        info!(
+            tenant = %tenant.id,
+            namespace = %tenant.namespace,
+            database = %tenant.database,
+            "Connected to tenant database"
+        );
+        Ok(db)
+    }
+
+    async fn with_timeout(
+        &self,
+        tenant: &TenantRecord,
+        connecting: impl Future<Output = Result<DbConnection, DatabaseError>>,
+    ) -> Result<DbConnection, DatabaseError> {
+        let connection_timeout = self.inner.base.connection_timeout;
+        timeout(connection_timeout, connecting).await.map_err(|_| {
+            DatabaseError::ConnectionError(format!(
+                "Connecting to tenant '{}' timed out after {}ms",
+                tenant.id,
+                connection_timeout.as_millis()
+            ))
+        })?
+    }
+
+    /// Defines the tenant's database if it does not exist. In namespace
+    /// mode, signs in as root to define its namespace too, and the `DB_*`
+    /// user in it, updating its password.
+    async fn define(&self, tenant: &TenantRecord) -> Result<(), DatabaseError> {
+        let config = self.db_config(tenant);
+        let mut statements = Vec::new();
+        let db = match self.inner.config.mode {
+            TenancyMode::Database => self.with_timeout(tenant, connect(&config)).await?,
+            TenancyMode::Namespace => {
+                let mut root = config.clone();
+                if let (Some(username), Some(password)) = (
+                    &self.inner.config.root_username,
+                    &self.inner.config.root_password,
+                ) {
+                    root.username.clone_from(username);
+                    root.password = password.clone();
+                }
+                statements.push(format!(
+                    "DEFINE NAMESPACE IF NOT EXISTS {}",
+                    ident(&tenant.namespace)
+                ));
+                statements.push(format!(
+                    "DEFINE USER OVERWRITE {} ON NAMESPACE PASSWORD {} ROLES EDITOR",
+                    ident(&config.username),
+                    strand(config.password.expose())
+                ));
+                self.with_timeout(tenant, connect_root(&root)).await?
+            }
+        };
+        statements.push(format!(
+            "DEFINE DATABASE IF NOT EXISTS {}",
+            ident(&tenant.database)
+        ));
+        db.query(statements.join(";\n")).await?.check()?;
+        Ok(())
+    }
+
+    /// Returns the namespace and database a new tenant's data is kept in.
+    fn location(&self, id: &str) -> (String, String) {
+        let name = format!("{}{}", self.inner.config.prefix, id.replace('-', "_"));
+        match self.inner.config.mode {
+            TenancyMode::Database => (self.inner.base.namespace.clone(), name),
+            TenancyMode::Namespace => (name, self.inner.base.database.clone()),
+        }
+    }
+
+    fn db_config(&self, tenant: &TenantRecord) -> DbConfig {
+        let mut config = self.inner.base.clone();
+        config.namespace.clone_from(&tenant.namespace);
+        config.database.clone_from(&tenant.database);
+        config
+    }
+
+    fn database(&self, name: String, db: DbConnection) -> Database {
+        Database {
+            name,
+            db,
+            health_check_timeout: self.inner.base.health_check_timeout,
+        }
+    }
+
+    /// Drops everything cached about a tenant.
+    fn forget(&self, id: &str) {
+        self.forget_record(id);
+        self.disconnect(id);
+    }
+
+    fn forget_record(&self, id: &str) {
+        lock(&self.inner.records).remove(id);
+    }
+
+    /// Closes connections idle for longer than `TENANCY_IDLE_TIMEOUT`, and
+    /// the least recently used ones beyond `TENANCY_MAX_CONNECTIONS`, other
+    /// than `keep`. Requests still holding a connection finish with it.
+    fn evict(&self, keep: Option<&str>) {
+        let config = &self.inner.config;
+        let mut closed = Vec::new();
+        {
+            let mut connections = lock(&self.inner.connections);
+            connections.retain(|id, connection| {
+                let idle = Some(id.as_str()) != keep
+                    && connection.last_used.elapsed() >= config.idle_timeout;
+                if idle {
+                    closed.push(id.clone());
+                }
+                !idle
+            });
+            while connections.len() > config.max_connections {
+                let Some(id) = connections
+                    .iter()
+                    .filter(|(id, _)| Some(id.as_str()) != keep)
+                    .min_by_key(|(_, connection)| connection.last_used)
+                    .map(|(id, _)| id.clone())
+                else {
+                    break;
+                };
+                connections.remove(&id);
+                closed.push(id);
+            }
+        }
+        for id in &closed {
+            info!(tenant = %id, "Closed unused tenant connection");
+        }
+    }
+
+    fn disconnect(&self, id: &str) {
+        if lock(&self.inner.connections).remove(id).is_some() {
+            info!(tenant = %id, "Closed tenant connection");
+            self.count_connections();
+        }
+    }
+
+    fn count_connections(&self) {
+        let open = lock(&self.inner.connections)
+            .values()
+            .filter(|connection| connection.cell.initialized())
+            .count();
+        self.inner
+            .metrics
+            .gauge(
+                "tenant_connections",
+                "Open connections to tenant databases",
+                &[],
+            )
+            .set(i64::try_from(open).unwrap_or(i64::MAX));
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for Tenants {
+    /// Reports degraded while any open tenant connection fails, since the
+    /// other tenants are still served.
+    async fn check(&self) -> ComponentHealth {
+        // Health checks run on a timer, so idle connections close without traffic
+        self.evict(None);
+        self.count_connections();
+        let connections: Vec<(String, DbConnection)> = lock(&self.inner.connections)
+            .iter()
+            .filter_map(|(id, connection)| connection.cell.get().map(|db| (id.clone(), db.clone())))
+            .collect();
+        let total = connections.len();
+        let checks = connections
+            .into_iter()
+            .map(|(id, db)| async move { (id.clone(), self.database(id, db).check().await) });
+        let mut failing: Vec<String> = futures::future::join_all(checks)
+            .await
+            .into_iter()
+            .filter(|(_, health)| health.status != HealthStatus::Healthy)
+            .map(|(id, _)| id)
+            .collect();
+        failing.sort();
+
+        let (status, message) = if failing.is_empty() {
+            (
+                HealthStatus::Healthy,
+                format!("Open connections: {total}, failing: 0"),
+            )
+        } else {
+            (
+                HealthStatus::Degraded,
+                format!(
+                    "Open connections: {total}, failing: {} ({})",
+                    failing.len(),
+                    failing.join(", ")
+                ),
+            )
+        };
+
+        ComponentHealth {
+            name: "Tenants".to_string(),
+            status,
+            message: Some(message),
+        }
+    }
+}
+
+/// Quotes a SurrealQL identifier.
+fn ident(name: &str) -> String {
+    format!("`{}`", name.replace('`', "\\`"))
+}
+
+/// Quotes a SurrealQL string, for statements that take no parameters.
+fn strand(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
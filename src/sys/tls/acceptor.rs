@@ -0,0 +1,120 @@
+use super::config::{ClientAuth, TlsConfig};
+use arc_swap::ArcSwap;
+use std::{path::Path, sync::Arc, time::SystemTime};
+use tokio_rustls::rustls::{
+    RootCertStore, ServerConfig,
+    crypto::{CryptoProvider, ring},
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    server::WebPkiClientVerifier,
+};
+use tracing::{error, info};
+
+/// Builds the rustls server configuration from the PEM files in `config`.
+///
+/// ALPN advertises HTTP/2 and HTTP/1.1. When a client CA bundle is set,
+/// client certificates are verified against it.
+///
+/// # Errors
+///
+/// Returns a description of the problem if a file cannot be read or parsed,
+/// or if the certificate and key do not match.
+pub fn build_server_config(config: &TlsConfig) -> Result<Arc<ServerConfig>, String> {
+    let provider = Arc::new(ring::default_provider());
+
+    let certs = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|e| format!("Failed to read '{}': {e}", config.cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(format!(
+            "No certificates found in '{}'",
+            config.cert_path.display()
+        ));
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key_path)
+        .map_err(|e| format!("Failed to read '{}': {e}", config.key_path.display()))?;
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?;
+
+    let builder = match &config.client_ca_path {
+        Some(ca_path) => {
+            let verifier = client_verifier(ca_path, config.client_auth, provider)?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("Invalid certificate or key: {e}"))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(server_config))
+}
+
+fn client_verifier(
+    ca_path: &Path,
+    client_auth: ClientAuth,
+    provider: Arc<CryptoProvider>,
+) -> Result<Arc<dyn tokio_rustls::rustls::server::danger::ClientCertVerifier>, String> {
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(ca_path)
+        .map_err(|e| format!("Failed to read '{}': {e}", ca_path.display()))?
+    {
+        let cert = cert.map_err(|e| format!("Failed to parse '{}': {e}", ca_path.display()))?;
+        roots
+            .add(cert)
+            .map_err(|e| format!("Invalid client CA certificate: {e}"))?;
+    }
+
+    let builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+    let builder = match client_auth {
+        ClientAuth::Required => builder,
+        ClientAuth::Optional => builder.allow_unauthenticated(),
+    };
+    builder
+        .build()
+        .map_err(|e| format!("Invalid client CA bundle: {e}"))
+}
+
+/// Spawns a task that rebuilds the server configuration whenever one of the
+/// certificate files changes on disk. A failed rebuild keeps the current
+/// certificates in place.
+pub fn spawn_cert_watcher(config: TlsConfig, current: Arc<ArcSwap<ServerConfig>>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.reload_interval);
+        let mut last_modified = files_modified_at(&config);
+
+        loop {
+            ticker.tick().await;
+            let modified = files_modified_at(&config);
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            match build_server_config(&config) {
+                Ok(server_config) => {
+                    current.store(server_config);
+                    info!("Reloaded TLS certificates");
+                }
+                Err(e) => {
+                    error!(error = %e, "Failed to reload TLS certificates, keeping current ones")
+                }
+            }
+        }
+    });
+}
+
+fn files_modified_at(config: &TlsConfig) -> Vec<Option<SystemTime>> {
+    [
+        Some(&config.cert_path),
+        Some(&config.key_path),
+        config.client_ca_path.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+    .collect()
+}
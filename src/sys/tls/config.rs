@@ -0,0 +1,72 @@
+use crate::sys::{config::ConfigReader, env::EnvEnum};
+use std::{path::PathBuf, time::Duration};
+
+/// Whether clients must present a certificate signed by the client CA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientAuth {
+    Optional,
+    Required,
+}
+
+impl EnvEnum for ClientAuth {
+    const VARIANTS: &'static [(&'static str, Self)] =
+        &[("optional", Self::Optional), ("required", Self::Required)];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    pub enabled: bool,
+    /// PEM file with the certificate chain, leaf first.
+    pub cert_path: PathBuf,
+    /// PEM file with the private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: PathBuf,
+    /// PEM bundle of CAs trusted to sign client certificates (enables mTLS).
+    pub client_ca_path: Option<PathBuf>,
+    pub client_auth: ClientAuth,
+    /// How often certificate files are checked for changes.
+    pub reload_interval: Duration,
+    pub handshake_timeout: Duration,
+}
+
+impl TlsConfig {
+    /// Creates a `TlsConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let enabled = config.bool("TLS_ENABLED", false);
+
+        let mut path = |key: &str| {
+            let value = if enabled {
+                config.required(key)
+            } else {
+                config.string(key, "")
+            };
+            let path = PathBuf::from(value);
+            if enabled && !path.as_os_str().is_empty() && !path.is_file() {
+                config.invalid(key, format!("'{}' does not exist", path.display()));
+            }
+            path
+        };
+        let cert_path = path("TLS_CERT_PATH");
+        let key_path = path("TLS_KEY_PATH");
+
+        let client_ca_path = config
+            .optional("TLS_CLIENT_CA_PATH")
+            .filter(|p| !p.trim().is_empty())
+            .map(PathBuf::from);
+        if let Some(path) = client_ca_path.as_ref().filter(|p| enabled && !p.is_file()) {
+            config.invalid(
+                "TLS_CLIENT_CA_PATH",
+                format!("'{}' does not exist", path.display()),
+            );
+        }
+
+        Self {
+            enabled,
+            cert_path,
+            key_path,
+            client_ca_path,
+            client_auth: config.enumeration("TLS_CLIENT_AUTH", ClientAuth::Required),
+            reload_interval: config.duration("TLS_RELOAD_INTERVAL", Duration::from_secs(30)),
+            handshake_timeout: config.duration("TLS_HANDSHAKE_TIMEOUT", Duration::from_secs(10)),
+        }
+    }
+}
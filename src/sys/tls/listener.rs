@@ -0,0 +1,95 @@
+use super::{
+    acceptor::{build_server_config, spawn_cert_watcher},
+    config::TlsConfig,
+};
+use crate::AppError;
+use arc_swap::ArcSwap;
+use axum::serve::Listener;
+use std::{io, net::SocketAddr, sync::Arc};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time::{Duration, sleep, timeout},
+};
+use tokio_rustls::{TlsAcceptor, server::TlsStream};
+use tracing::{debug, warn};
+
+/// Number of completed handshakes buffered before the server accepts them.
+const ACCEPT_BACKLOG: usize = 128;
+
+/// A listener that terminates TLS on accepted TCP connections.
+///
+/// Handshakes run in their own tasks so a slow client cannot stall others;
+/// each one uses the latest certificates, so reloads apply to new connections.
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    /// Wraps a bound TCP listener, loading certificates from `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::TlsError` if the certificates cannot be loaded, or
+    /// `AppError::BindError` if the local address cannot be determined.
+    pub fn new(tcp: TcpListener, config: &TlsConfig) -> Result<Self, AppError> {
+        let server_config = build_server_config(config).map_err(AppError::TlsError)?;
+        let current = Arc::new(ArcSwap::new(server_config));
+        spawn_cert_watcher(config.clone(), current.clone());
+
+        let local_addr = tcp.local_addr()?;
+        let (sender, incoming) = mpsc::channel(ACCEPT_BACKLOG);
+        let handshake_timeout = config.handshake_timeout;
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, peer) = tokio::select! {
+                    () = sender.closed() => break,
+                    accepted = tcp.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            warn!(error = %e, "Failed to accept TCP connection");
+                            sleep(Duration::from_millis(50)).await;
+                            continue;
+                        }
+                    },
+                };
+
+                let acceptor = TlsAcceptor::from(current.load_full());
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match timeout(handshake_timeout, acceptor.accept(stream)).await {
+                        Ok(Ok(tls)) => {
+                            let _ = sender.send((tls, peer)).await;
+                        }
+                        Ok(Err(e)) => debug!(peer = %peer, error = %e, "TLS handshake failed"),
+                        Err(_) => debug!(peer = %peer, "TLS handshake timed out"),
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            local_addr,
+            incoming,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(connection) => connection,
+            // The accept loop only stops once this listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
@@ -0,0 +1,6 @@
+pub mod acceptor;
+pub mod config;
+pub mod listener;
+
+pub use config::{ClientAuth, TlsConfig};
+pub use listener::TlsListener;
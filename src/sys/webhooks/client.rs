@@ -0,0 +1,558 @@
+use super::{
+    job::DeliverWebhook,
+    models::{
+        Delivery, DeliveryAttempt, DeliveryStatus, NewSubscription, Subscription, WebhooksConfig,
+    },
+    nonce::NonceStore,
+    signing::{ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER, sign},
+    store::{DeliveryFilter, WebhookStore},
+};
+use crate::{
+    AppError,
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::{
+        jobs::{EnqueueOptions, JobQueue, JobResult},
+        metrics::MetricsRegistry,
+        server::Shutdown,
+    },
+};
+use chrono::Utc;
+use rand::Rng;
+use reqwest::header::{CONTENT_TYPE, USER_AGENT};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Longest response body excerpt kept in the attempt log.
+const MAX_RESPONSE_LOG: usize = 1024;
+
+/// Outbound webhooks: subscriptions per tenant and signed delivery of events
+/// to them.
+///
+/// Each delivery runs as a job, so retries back off and survive restarts.
+/// A subscription whose endpoint keeps failing has its circuit opened, and
+/// its deliveries wait for the cooldown instead of spending attempts.
+#[derive(Clone)]
+pub struct Webhooks {
+    inner: Arc<WebhooksInner>,
+}
+
+struct WebhooksInner {
+    store: WebhookStore,
+    config: WebhooksConfig,
+    http: reqwest::Client,
+    jobs: JobQueue,
+    nonces: NonceStore,
+    metrics: Arc<MetricsRegistry>,
+}
+
+/// Result of one HTTP request to a receiver.
+struct Response {
+    status_code: Option<u16>,
+    body: Option<String>,
+    error: Option<String>,
+}
+
+impl Webhooks {
+    /// Creates the webhook client. Deliveries run on `jobs` workers, and
+    /// expired inbound nonces are deleted until `shutdown`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::ServerError` if the HTTP client cannot be built.
+    pub fn new(
+        db: DbConnection,
+        config: WebhooksConfig,
+        jobs: JobQueue,
+        shutdown: Shutdown,
+        metrics: Arc<MetricsRegistry>,
+    ) -> Result<Self, AppError> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| AppError::ServerError(format!("Failed to build webhook client: {e}")))?;
+        Ok(Self {
+            inner: Arc::new(WebhooksInner {
+                nonces: NonceStore::new(db.clone(), Duration::from_secs(600), shutdown),
+                store: WebhookStore::new(db),
+                config,
+                http,
+                jobs,
+                metrics,
+            }),
+        })
+    }
+
+    /// Ids of inbound webhooks already received.
+    #[must_use]
+    pub fn nonces(&self) -> &NonceStore {
+        &self.inner.nonces
+    }
+
+    /// Queues `event` for every active subscription of `tenant` that matches
+    /// it. Returns the ids of the created deliveries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the payload cannot be
+    /// serialized or the deliveries cannot be stored.
+    pub async fn publish<T: Serialize + ?Sized>(
+        &self,
+        tenant: &str,
+        event: &str,
+        payload: &T,
+    ) -> Result<Vec<String>, DatabaseError> {
+        self.publish_with_id(tenant, &random_id(), event, payload)
+            .await
+    }
+
+    /// Like [`Webhooks::publish`], with the event id sent in every payload
+    /// set by the caller, so that receivers can recognize an event published
+    /// more than once.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the payload cannot be
+    /// serialized or the deliveries cannot be stored.
+    pub async fn publish_with_id<T: Serialize + ?Sized>(
+        &self,
+        tenant: &str,
+        event_id: &str,
+        event: &str,
+        payload: &T,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let payload = serde_json::to_value(payload)
+            .map_err(|e| DatabaseError::QueryError(format!("Payload cannot be stored: {e}")))?;
+        let mut deliveries = Vec::new();
+        for subscription in self.inner.store.subscriptions(tenant).await? {
+            if !subscription.active || !subscription.matches(event) {
+                continue;
+            }
+            let delivery = self
+                .queue(&subscription, event, event_id, &payload, None)
+                .await?;
+            deliveries.push(delivery);
+        }
+        debug!(
+            tenant,
+            event,
+            deliveries = deliveries.len(),
+            "Published webhook event"
+        );
+        Ok(deliveries)
+    }
+
+    /// Sends a delivery again as a new delivery with fresh attempts. Returns
+    /// the new delivery's id, or `None` if the delivery does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::NotFound` if the subscription was deleted, and
+    /// `DatabaseError::QueryError` if the delivery cannot be stored.
+    pub async fn replay(&self, tenant: &str, id: &str) -> Result<Option<String>, DatabaseError> {
+        let Some(original) = self.delivery(tenant, id).await? else {
+            return Ok(None);
+        };
+        let subscription = self
+            .inner
+            .store
+            .subscription(&original.subscription)
+            .await?
+            .ok_or_else(|| {
+                DatabaseError::NotFound(format!(
+                    "Subscription '{}' no longer exists",
+                    original.subscription
+                ))
+            })?;
+        let replayed = self
+            .queue(
+                &subscription,
+                &original.event,
+                &original.event_id,
+                &original.payload,
+                Some(&original.id),
+            )
+            .await?;
+        info!(tenant, delivery = id, replay = %replayed, "Replaying webhook delivery");
+        Ok(Some(replayed))
+    }
+
+    /// Creates a subscription for `tenant`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::UnprocessableEntity` for an invalid URL or empty
+    /// event list, and `AppError::Database` if it cannot be stored.
+    pub async fn subscribe(
+        &self,
+        tenant: &str,
+        subscription: NewSubscription,
+    ) -> Result<Subscription, AppError> {
+        let url = Url::parse(&subscription.url)
+            .map_err(|e| AppError::UnprocessableEntity(format!("Invalid URL: {e}")))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(AppError::UnprocessableEntity(
+                "URL must use http or https".to_string(),
+            ));
+        }
+        let events: Vec<String> = subscription
+            .events
+            .iter()
+            .map(|event| event.trim().to_string())
+            .filter(|event| !event.is_empty())
+            .collect();
+        if events.is_empty() {
+            return Err(AppError::UnprocessableEntity(
+                "At least one event is required".to_string(),
+            ));
+        }
+        let secret = match subscription.secret {
+            Some(secret) if secret.len() < 16 => {
+                return Err(AppError::UnprocessableEntity(
+                    "Secret must be at least 16 characters".to_string(),
+                ));
+            }
+            Some(secret) => secret,
+            None => format!("whsec_{}", hex::encode(rand::rng().random::<[u8; 24]>())),
+        };
+
+        let mut content = json!({
+            "tenant": tenant,
+            "url": url.to_string(),
+            "events": events,
+            "secret": secret,
+            "active": true,
+            "failure_count": 0,
+            "created_at": now_millis(),
+        });
+        if let Some(description) = subscription.description {
+            content["description"] = Value::String(description);
+        }
+        let created = self.inner.store.create_subscription(content).await?;
+        info!(tenant, subscription = %created.id, url = %created.url, "Webhook subscription created");
+        Ok(created)
+    }
+
+    /// Lists the subscriptions of `tenant`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn subscriptions(&self, tenant: &str) -> Result<Vec<Subscription>, DatabaseError> {
+        self.inner.store.subscriptions(tenant).await
+    }
+
+    /// Deletes a subscription of `tenant`. Its pending deliveries fail on
+    /// their next attempt. Returns false if there is no such subscription.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the subscription cannot be
+    /// deleted.
+    pub async fn unsubscribe(&self, tenant: &str, id: &str) -> Result<bool, DatabaseError> {
+        self.inner.store.delete_subscription(tenant, id).await
+    }
+
+    /// Returns a delivery of `tenant`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn delivery(
+        &self,
+        tenant: &str,
+        id: &str,
+    ) -> Result<Option<Delivery>, DatabaseError> {
+        Ok(self
+            .inner
+            .store
+            .delivery(id)
+            .await?
+            .filter(|delivery| delivery.tenant == tenant))
+    }
+
+    /// Lists deliveries of `tenant`, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn deliveries(
+        &self,
+        tenant: &str,
+        filter: &DeliveryFilter,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Delivery>, DatabaseError> {
+        self.inner
+            .store
+            .deliveries(tenant, filter, start, limit)
+            .await
+    }
+
+    /// Returns the attempts made for a delivery, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the table cannot be queried.
+    pub async fn attempts(&self, delivery: &str) -> Result<Vec<DeliveryAttempt>, DatabaseError> {
+        self.inner.store.attempts(delivery).await
+    }
+
+    /// Makes the next attempt of a delivery. Errors make the job queue retry.
+    pub(super) async fn deliver(&self, id: &str) -> JobResult {
+        let inner = &self.inner;
+        let Some(delivery) = inner.store.delivery(id).await? else {
+            return Ok(());
+        };
+        if delivery.status != DeliveryStatus::Pending {
+            return Ok(());
+        }
+        let Some(subscription) = inner
+            .store
+            .subscription(&delivery.subscription)
+            .await?
+            .filter(|subscription| subscription.active)
+        else {
+            let error = Some("Subscription removed".to_string());
+            inner
+                .store
+                .update_delivery(
+                    id,
+                    DeliveryStatus::Failed,
+                    delivery.attempts,
+                    None,
+                    error,
+                    now_millis(),
+                )
+                .await?;
+            return Ok(());
+        };
+
+        // Wait out an open circuit without spending an attempt
+        let now = now_millis();
+        if let Some(open_until) = subscription.circuit_open_until.filter(|until| *until > now) {
+            let delay = Duration::from_millis(u64::try_from(open_until - now).unwrap_or_default());
+            self.enqueue(id, delivery.attempts, delay).await?;
+            debug!(
+                delivery = id,
+                retry_in_ms = delay.as_millis(),
+                "Circuit open, delivery held"
+            );
+            return Ok(());
+        }
+
+        let attempt = delivery.attempts + 1;
+        let started_at = now_millis();
+        let started = Instant::now();
+        let response = self.send(&subscription, &delivery).await;
+        let duration = started.elapsed();
+        let success = response.error.is_none();
+
+        inner
+            .store
+            .record_attempt(json!({
+                "delivery": id,
+                "subscription": subscription.id,
+                "tenant": delivery.tenant,
+                "attempt": attempt,
+                "started_at": started_at,
+                "duration_ms": u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                "success": success,
+                "status_code": response.status_code,
+                "response": response.body,
+                "error": response.error,
+            }))
+            .await?;
+
+        let status = if success {
+            DeliveryStatus::Succeeded
+        } else if attempt >= inner.config.max_attempts {
+            DeliveryStatus::Failed
+        } else {
+            DeliveryStatus::Pending
+        };
+        inner
+            .store
+            .update_delivery(
+                id,
+                status,
+                attempt,
+                response.status_code,
+                response.error.clone(),
+                now_millis(),
+            )
+            .await?;
+        inner
+            .metrics
+            .counter(
+                "webhook_attempts_total",
+                "Webhook delivery attempts by outcome",
+                &[("outcome", if success { "success" } else { "failure" })],
+            )
+            .inc();
+
+        if success {
+            if subscription.failure_count > 0 {
+                inner.store.record_success(&subscription.id).await?;
+                info!(subscription = %subscription.id, "Webhook endpoint recovered, circuit closed");
+            }
+            return Ok(());
+        }
+
+        let error = response.error.unwrap_or_default();
+        let open_until = now_millis().saturating_add(millis(inner.config.circuit_cooldown));
+        let opened = inner
+            .store
+            .record_failure(&subscription.id, inner.config.failure_threshold, open_until)
+            .await?;
+        if opened {
+            warn!(
+                subscription = %subscription.id,
+                url = %subscription.url,
+                "Webhook endpoint keeps failing, circuit opened"
+            );
+            inner
+                .metrics
+                .counter(
+                    "webhook_circuit_opened_total",
+                    "Times a webhook subscription's circuit was opened",
+                    &[],
+                )
+                .inc();
+        }
+
+        if status == DeliveryStatus::Failed {
+            warn!(delivery = id, error = %error, "Webhook delivery failed its last attempt");
+            return Ok(());
+        }
+        Err(error.into())
+    }
+
+    async fn send(&self, subscription: &Subscription, delivery: &Delivery) -> Response {
+        let body = json!({
+            "id": delivery.event_id,
+            "event": delivery.event,
+            "tenant": delivery.tenant,
+            "created_at": delivery.created_at,
+            "data": delivery.payload,
+        })
+        .to_string();
+        let timestamp = Utc::now().timestamp();
+        let signature = sign(
+            subscription.secret.as_bytes(),
+            &delivery.id,
+            timestamp,
+            body.as_bytes(),
+        );
+
+        let result = self
+            .inner
+            .http
+            .post(&subscription.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(
+                USER_AGENT,
+                concat!("axum_backend/", env!("CARGO_PKG_VERSION")),
+            )
+            .header(ID_HEADER, &delivery.id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                let mut body = response.text().await.unwrap_or_default();
+                if body.len() > MAX_RESPONSE_LOG {
+                    let end = body.floor_char_boundary(MAX_RESPONSE_LOG);
+                    body.truncate(end);
+                }
+                Response {
+                    status_code: Some(status.as_u16()),
+                    body: Some(body).filter(|body| !body.is_empty()),
+                    error: (!status.is_success())
+                        .then(|| format!("Receiver responded with {status}")),
+                }
+            }
+            Err(e) => Response {
+                status_code: None,
+                body: None,
+                error: Some(if e.is_timeout() {
+                    "Receiver timed out".to_string()
+                } else {
+                    format!("Request failed: {e}")
+                }),
+            },
+        }
+    }
+
+    async fn queue(
+        &self,
+        subscription: &Subscription,
+        event: &str,
+        event_id: &str,
+        payload: &Value,
+        replay_of: Option<&str>,
+    ) -> Result<String, DatabaseError> {
+        let now = now_millis();
+        let mut content = json!({
+            "tenant": subscription.tenant,
+            "subscription": subscription.id,
+            "event": event,
+            "event_id": event_id,
+            "payload": payload,
+            "status": DeliveryStatus::Pending,
+            "attempts": 0,
+            "created_at": now,
+            "updated_at": now,
+        });
+        if let Some(replay_of) = replay_of {
+            content["replay_of"] = Value::String(replay_of.to_string());
+        }
+        let delivery = self.inner.store.create_delivery(content).await?;
+        self.enqueue(&delivery.id, 0, Duration::ZERO).await?;
+        Ok(delivery.id)
+    }
+
+    /// Queues the job making the remaining attempts of a delivery.
+    async fn enqueue(&self, id: &str, attempts: u32, delay: Duration) -> Result<(), DatabaseError> {
+        let remaining = self
+            .inner
+            .config
+            .max_attempts
+            .saturating_sub(attempts)
+            .max(1);
+        self.inner
+            .jobs
+            .enqueue(
+                &DeliverWebhook {
+                    delivery: id.to_string(),
+                },
+                EnqueueOptions::default()
+                    .delay(delay)
+                    .max_attempts(remaining),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn now_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+fn random_id() -> String {
+    hex::encode(rand::rng().random::<[u8; 16]>())
+}
@@ -0,0 +1,82 @@
+use super::models::{InboundWebhookConfig, SignatureEncoding, WebhooksConfig};
+use crate::sys::{config::ConfigReader, env};
+use axum::http::HeaderName;
+use std::{collections::BTreeMap, time::Duration};
+
+impl WebhooksConfig {
+    /// Creates a `WebhooksConfig` from layered configuration.
+    pub fn from_config(config: &mut ConfigReader) -> Self {
+        let timeout = config.duration("WEBHOOKS_TIMEOUT", Duration::from_secs(10));
+        if timeout.is_zero() {
+            config.invalid("WEBHOOKS_TIMEOUT", "must be greater than zero");
+        }
+        let max_attempts = config.parsed("WEBHOOKS_MAX_ATTEMPTS", 8);
+        if max_attempts == 0 {
+            config.invalid("WEBHOOKS_MAX_ATTEMPTS", "must be greater than zero");
+        }
+        let failure_threshold = config.parsed("WEBHOOKS_FAILURE_THRESHOLD", 5);
+        if failure_threshold == 0 {
+            config.invalid("WEBHOOKS_FAILURE_THRESHOLD", "must be greater than zero");
+        }
+
+        Self {
+            enabled: config.bool("WEBHOOKS_ENABLED", true),
+            timeout,
+            max_attempts,
+            failure_threshold,
+            circuit_cooldown: config
+                .duration("WEBHOOKS_CIRCUIT_COOLDOWN", Duration::from_secs(300)),
+            database: config
+                .optional("WEBHOOKS_DATABASE")
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+}
+
+impl InboundWebhookConfig {
+    /// Creates the configuration of one sender from its
+    /// `INBOUND_WEBHOOK__<NAME>__*` settings.
+    pub fn from_config_group(config: &mut ConfigReader, name: &str) -> Self {
+        let key = |field: &str| env::group_key("INBOUND_WEBHOOK", name, field);
+        let header = |config: &mut ConfigReader, field: &str, default: &str| {
+            let key = key(field);
+            let value = config.string(&key, default).trim().to_lowercase();
+            if !value.is_empty() && HeaderName::try_from(value.as_str()).is_err() {
+                config.invalid(&key, format!("'{value}' is not a valid header name"));
+            }
+            Some(value).filter(|value| !value.is_empty())
+        };
+
+        let signature_header = header(config, "SIGNATURE_HEADER", "webhook-signature");
+        if signature_header.is_none() {
+            config.invalid(&key("SIGNATURE_HEADER"), "must not be empty");
+        }
+        let timestamp_header = header(config, "TIMESTAMP_HEADER", "webhook-timestamp");
+        let id_header = header(config, "ID_HEADER", "webhook-id");
+
+        Self {
+            secret: config.secret(&key("SECRET")),
+            signature_header: signature_header.unwrap_or_default(),
+            signature_prefix: config.string(&key("SIGNATURE_PREFIX"), "v1="),
+            encoding: config.enumeration(&key("ENCODING"), SignatureEncoding::Hex),
+            timestamp_header,
+            tolerance: config.duration(&key("TOLERANCE"), Duration::from_secs(300)),
+            signed_id: config.bool(&key("SIGNED_ID"), true),
+            id_header,
+        }
+    }
+
+    /// Creates the configuration of every sender found in any layer, keyed
+    /// by lowercase name.
+    pub fn named_from_config(config: &mut ConfigReader) -> BTreeMap<String, Self> {
+        config
+            .groups("INBOUND_WEBHOOK")
+            .into_iter()
+            .map(|name| {
+                let webhook = Self::from_config_group(config, &name);
+                (name.to_lowercase(), webhook)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,17 @@
+use crate::sys::jobs::{Job, JobContext, JobResult};
+use serde::{Deserialize, Serialize};
+
+/// Makes the next attempt of a webhook delivery.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliverWebhook {
+    pub delivery: String,
+}
+
+#[async_trait::async_trait]
+impl Job for DeliverWebhook {
+    const KIND: &'static str = "webhook.deliver";
+
+    async fn run(self, context: JobContext) -> JobResult {
+        context.state.webhooks.deliver(&self.delivery).await
+    }
+}
@@ -0,0 +1,23 @@
+mod client;
+mod config;
+mod job;
+mod models;
+mod nonce;
+mod routes;
+mod signing;
+mod store;
+mod verify;
+pub use client::Webhooks;
+pub use job::DeliverWebhook;
+pub use models::{
+    Delivery, DeliveryAttempt, DeliveryStatus, InboundWebhookConfig, NewSubscription,
+    SignatureEncoding, Subscription, WebhooksConfig,
+};
+pub use nonce::NonceStore;
+pub use routes::{
+    create_subscription, delete_subscription, get_delivery, list_deliveries, list_subscriptions,
+    replay_delivery,
+};
+pub use signing::{ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER, sign};
+pub use store::DeliveryFilter;
+pub use verify::{VerifiedWebhook, WebhookSource, release_webhook_nonces};
@@ -0,0 +1,173 @@
+use crate::sys::env::{EnvEnum, Secret};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct WebhooksConfig {
+    /// Deliver webhooks from this instance's job workers.
+    pub enabled: bool,
+    /// Time allowed for a receiver to respond.
+    pub timeout: Duration,
+    /// Attempts before a delivery is marked failed. Retries back off as
+    /// configured for the job queue.
+    pub max_attempts: u32,
+    /// Consecutive failures that open the circuit of a subscription.
+    pub failure_threshold: u32,
+    /// How long an open circuit holds deliveries before trying again.
+    pub circuit_cooldown: Duration,
+    /// Named database for subscriptions and deliveries, the primary one when
+    /// `None`.
+    pub database: Option<String>,
+}
+
+/// A receiver of a tenant's events, as stored in the `webhook_subscription`
+/// table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub id: String,
+    pub tenant: String,
+    pub url: String,
+    /// Event names to deliver: exact names, prefixes like `user.*`, or `*`.
+    pub events: Vec<String>,
+    /// Key for the HMAC-SHA256 signature of each payload.
+    pub secret: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub active: bool,
+    /// Failed attempts since the last success.
+    #[serde(default)]
+    pub failure_count: u32,
+    /// Deliveries are held until this time, in milliseconds since the Unix
+    /// epoch, while the circuit is open.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_open_until: Option<i64>,
+    pub created_at: i64,
+}
+
+impl Subscription {
+    /// Whether `event` passes the subscription's filters.
+    #[must_use]
+    pub fn matches(&self, event: &str) -> bool {
+        self.events.iter().any(|filter| {
+            filter == "*"
+                || filter == event
+                || filter
+                    .strip_suffix('*')
+                    .is_some_and(|prefix| event.starts_with(prefix))
+        })
+    }
+}
+
+/// Fields accepted when creating a subscription.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSubscription {
+    pub url: String,
+    pub events: Vec<String>,
+    /// Generated when not given.
+    pub secret: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for its first attempt or a retry.
+    Pending,
+    Succeeded,
+    /// Out of attempts, or the subscription is gone.
+    Failed,
+}
+
+impl DeliveryStatus {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// One event sent to one subscription, as stored in the `webhook_delivery`
+/// table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub id: String,
+    pub tenant: String,
+    pub subscription: String,
+    pub event: String,
+    /// Shared by the deliveries of one event to every subscription.
+    pub event_id: String,
+    #[serde(default)]
+    pub payload: Value,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_status_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// The delivery this one resends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay_of: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// One HTTP request made for a delivery, as stored in the `webhook_attempt`
+/// table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    pub id: String,
+    pub delivery: String,
+    pub attempt: u32,
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// Start of the response body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How a sender of inbound webhooks signs its requests, configured as
+/// `INBOUND_WEBHOOK__<NAME>__*`.
+///
+/// The defaults match the signatures of outbound webhooks.
+#[derive(Debug, Clone)]
+pub struct InboundWebhookConfig {
+    pub secret: Secret,
+    /// Header carrying the signatures. Several may be given, separated by
+    /// commas or spaces, e.g. while the sender rotates its secret.
+    pub signature_header: String,
+    /// Text before each encoded signature, such as `sha256=`.
+    pub signature_prefix: String,
+    pub encoding: SignatureEncoding,
+    /// Header with the Unix time of signing. When set, the timestamp is
+    /// signed as `{timestamp}.{body}` and old requests are rejected.
+    pub timestamp_header: Option<String>,
+    /// How far the timestamp may be from the current time.
+    pub tolerance: Duration,
+    /// Header with a unique id per request.
+    pub id_header: Option<String>,
+    /// Whether the id is signed in front of the rest, as
+    /// `{id}.{timestamp}.{body}`. Replays are rejected by a signed id, and
+    /// by the signature otherwise, since an unsigned id can be changed.
+    pub signed_id: bool,
+}
+
+/// Encoding of a signature in its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureEncoding {
+    Hex,
+    Base64,
+}
+
+impl EnvEnum for SignatureEncoding {
+    const VARIANTS: &'static [(&'static str, Self)] =
+        &[("hex", Self::Hex), ("base64", Self::Base64)];
+}
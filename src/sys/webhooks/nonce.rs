@@ -0,0 +1,88 @@
+use crate::{
+    dbs::{error::DatabaseError, models::DbConnection},
+    sys::server::Shutdown,
+};
+use chrono::Utc;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::warn;
+
+/// Ids of inbound webhooks already received, kept in the `webhook_nonce`
+/// table until the sender could no longer replay them.
+#[derive(Clone)]
+pub struct NonceStore {
+    db: DbConnection,
+}
+
+impl NonceStore {
+    /// Creates the store and starts a task that deletes expired nonces
+    /// until shutdown.
+    #[must_use]
+    pub fn new(db: DbConnection, sweep_interval: Duration, shutdown: Shutdown) -> Self {
+        let sweeper = db.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sweep_interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    () = shutdown.triggered() => break,
+                }
+                if let Err(e) = sweeper
+                    .query("DELETE webhook_nonce WHERE expires_at <= $now")
+                    .bind(("now", Utc::now().timestamp_millis()))
+                    .await
+                {
+                    warn!(error = %e, "Failed to delete expired webhook nonces");
+                }
+            }
+        });
+
+        Self { db }
+    }
+
+    /// Records `nonce` for `source` for `ttl`. Returns false if it was
+    /// already recorded and has not expired, meaning the request is a replay.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the nonce cannot be stored.
+    pub async fn insert(
+        &self,
+        source: &str,
+        nonce: &str,
+        ttl: Duration,
+    ) -> Result<bool, DatabaseError> {
+        let now = Utc::now().timestamp_millis();
+        let ttl = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+        let stored: Option<Value> = self
+            .db
+            .query(
+                "UPSERT type::thing('webhook_nonce', [$source, $nonce]) \
+                 SET expires_at = $expires_at \
+                 WHERE expires_at IS NONE OR expires_at <= $now \
+                 RETURN expires_at",
+            )
+            .bind(("source", source.to_string()))
+            .bind(("nonce", nonce.to_string()))
+            .bind(("expires_at", now.saturating_add(ttl)))
+            .bind(("now", now))
+            .await?
+            .take(0)?;
+        Ok(stored.is_some())
+    }
+
+    /// Forgets `nonce` for `source`, so the request can be received again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DatabaseError::QueryError` if the nonce cannot be deleted.
+    pub async fn remove(&self, source: &str, nonce: &str) -> Result<(), DatabaseError> {
+        self.db
+            .query("DELETE type::thing('webhook_nonce', [$source, $nonce])")
+            .bind(("source", source.to_string()))
+            .bind(("nonce", nonce.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
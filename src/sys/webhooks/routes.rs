@@ -0,0 +1,130 @@
+use super::{
+    models::{Delivery, NewSubscription, Subscription},
+    store::DeliveryFilter,
+};
+use crate::{AppError, dbs::error::DatabaseError, sys::config::state::AppState};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Most deliveries returned by one request.
+const MAX_LIMIT: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    #[serde(flatten)]
+    pub filter: DeliveryFilter,
+    #[serde(default)]
+    pub start: u64,
+    pub limit: Option<u64>,
+}
+
+/// Subscription as returned by the API, without its secret.
+fn subscription_json(subscription: &Subscription) -> Value {
+    json!({
+        "id": subscription.id,
+        "url": subscription.url,
+        "events": subscription.events,
+        "description": subscription.description,
+        "active": subscription.active,
+        "failure_count": subscription.failure_count,
+        "circuit_open_until": subscription.circuit_open_until,
+        "created_at": subscription.created_at,
+    })
+}
+
+/// Creates a subscription. The secret is only returned here.
+pub async fn create_subscription(
+    State(state): State<Arc<AppState>>,
+    Path(tenant): Path<String>,
+    Json(input): Json<NewSubscription>,
+) -> Result<impl IntoResponse, AppError> {
+    let subscription = state.webhooks.subscribe(&tenant, input).await?;
+    let mut body = subscription_json(&subscription);
+    body["secret"] = Value::String(subscription.secret);
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+/// Lists the subscriptions of a tenant.
+pub async fn list_subscriptions(
+    State(state): State<Arc<AppState>>,
+    Path(tenant): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let subscriptions = state.webhooks.subscriptions(&tenant).await?;
+    let subscriptions: Vec<_> = subscriptions.iter().map(subscription_json).collect();
+    Ok(Json(json!({
+        "tenant": tenant,
+        "subscriptions": subscriptions,
+    })))
+}
+
+/// Deletes a subscription.
+pub async fn delete_subscription(
+    State(state): State<Arc<AppState>>,
+    Path((tenant, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    if state.webhooks.unsubscribe(&tenant, &id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(DatabaseError::NotFound(format!("Subscription '{id}' not found")).into())
+    }
+}
+
+/// Lists the deliveries of a tenant, newest first.
+pub async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    Path(tenant): Path<String>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query.limit.unwrap_or(100).min(MAX_LIMIT);
+    let deliveries = state
+        .webhooks
+        .deliveries(&tenant, &query.filter, query.start, limit)
+        .await?;
+    Ok(Json(json!({
+        "tenant": tenant,
+        "deliveries": deliveries,
+    })))
+}
+
+/// Returns a delivery with every attempt made for it.
+pub async fn get_delivery(
+    State(state): State<Arc<AppState>>,
+    Path((tenant, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let delivery: Delivery = state
+        .webhooks
+        .delivery(&tenant, &id)
+        .await?
+        .ok_or_else(|| DatabaseError::NotFound(format!("Delivery '{id}' not found")))?;
+    let attempts = state.webhooks.attempts(&id).await?;
+    Ok(Json(json!({
+        "delivery": delivery,
+        "attempts": attempts,
+    })))
+}
+
+/// Sends a delivery again as a new delivery.
+pub async fn replay_delivery(
+    State(state): State<Arc<AppState>>,
+    Path((tenant, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let replay = state
+        .webhooks
+        .replay(&tenant, &id)
+        .await?
+        .ok_or_else(|| DatabaseError::NotFound(format!("Delivery '{id}' not found")))?;
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "id": replay,
+            "replay_of": id,
+        })),
+    ))
+}
@@ -0,0 +1,233 @@
+use super::models::{Delivery, DeliveryAttempt, DeliveryStatus, Subscription};
+use crate::dbs::{error::DatabaseError, models::DbConnection};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Fields selected for every record: its own fields plus the key as `id`.
+const FIELDS: &str = "*, meta::id(id) AS id";
+
+/// Filters for listing deliveries.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeliveryFilter {
+    pub subscription: Option<String>,
+    pub status: Option<DeliveryStatus>,
+    pub event: Option<String>,
+}
+
+/// Subscriptions, deliveries and attempts in SurrealDB.
+#[derive(Clone)]
+pub(super) struct WebhookStore {
+    db: DbConnection,
+}
+
+impl WebhookStore {
+    pub(super) fn new(db: DbConnection) -> Self {
+        Self { db }
+    }
+
+    pub(super) async fn create_subscription(
+        &self,
+        content: Value,
+    ) -> Result<Subscription, DatabaseError> {
+        let created: Option<Subscription> = self
+            .db
+            .query(format!(
+                "CREATE webhook_subscription CONTENT $content RETURN {FIELDS}"
+            ))
+            .bind(("content", content))
+            .await?
+            .take(0)?;
+        created
+            .ok_or_else(|| DatabaseError::QueryError("Failed to create subscription".to_string()))
+    }
+
+    pub(super) async fn subscription(
+        &self,
+        id: &str,
+    ) -> Result<Option<Subscription>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM type::thing('webhook_subscription', $id)"
+            ))
+            .bind(("id", id.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    pub(super) async fn subscriptions(
+        &self,
+        tenant: &str,
+    ) -> Result<Vec<Subscription>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM webhook_subscription WHERE tenant = $tenant ORDER BY created_at"
+            ))
+            .bind(("tenant", tenant.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Deletes a subscription of `tenant`. Returns false if there is none.
+    pub(super) async fn delete_subscription(
+        &self,
+        tenant: &str,
+        id: &str,
+    ) -> Result<bool, DatabaseError> {
+        let deleted: Option<Value> = self
+            .db
+            .query(
+                "SELECT meta::id(id) AS id FROM \
+                 (DELETE type::thing('webhook_subscription', $id) WHERE tenant = $tenant RETURN BEFORE)",
+            )
+            .bind(("id", id.to_string()))
+            .bind(("tenant", tenant.to_string()))
+            .await?
+            .take(0)?;
+        Ok(deleted.is_some())
+    }
+
+    /// Records a failed attempt, opening the circuit once failures reach
+    /// `threshold`. Returns whether the circuit is open.
+    pub(super) async fn record_failure(
+        &self,
+        id: &str,
+        threshold: u32,
+        open_until: i64,
+    ) -> Result<bool, DatabaseError> {
+        let opened: Option<Value> = self
+            .db
+            .query(
+                "UPDATE type::thing('webhook_subscription', $id) SET failure_count += 1;
+                 UPDATE type::thing('webhook_subscription', $id) SET circuit_open_until = $until
+                     WHERE failure_count >= $threshold RETURN meta::id(id) AS id;",
+            )
+            .bind(("id", id.to_string()))
+            .bind(("threshold", threshold))
+            .bind(("until", open_until))
+            .await?
+            .take(1)?;
+        Ok(opened.is_some())
+    }
+
+    /// Closes the circuit after a successful attempt.
+    pub(super) async fn record_success(&self, id: &str) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "UPDATE type::thing('webhook_subscription', $id) \
+                 SET failure_count = 0, circuit_open_until = NONE",
+            )
+            .bind(("id", id.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub(super) async fn create_delivery(&self, content: Value) -> Result<Delivery, DatabaseError> {
+        let created: Option<Delivery> = self
+            .db
+            .query(format!(
+                "CREATE webhook_delivery CONTENT $content RETURN {FIELDS}"
+            ))
+            .bind(("content", content))
+            .await?
+            .take(0)?;
+        created.ok_or_else(|| DatabaseError::QueryError("Failed to create delivery".to_string()))
+    }
+
+    pub(super) async fn delivery(&self, id: &str) -> Result<Option<Delivery>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM type::thing('webhook_delivery', $id)"
+            ))
+            .bind(("id", id.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    pub(super) async fn deliveries(
+        &self,
+        tenant: &str,
+        filter: &DeliveryFilter,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<Delivery>, DatabaseError> {
+        let mut conditions = vec!["tenant = $tenant"];
+        if filter.subscription.is_some() {
+            conditions.push("subscription = $subscription");
+        }
+        if filter.status.is_some() {
+            conditions.push("status = $status");
+        }
+        if filter.event.is_some() {
+            conditions.push("event = $event");
+        }
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM webhook_delivery WHERE {} \
+                 ORDER BY created_at DESC LIMIT $limit START $start",
+                conditions.join(" AND ")
+            ))
+            .bind(("tenant", tenant.to_string()))
+            .bind(("subscription", filter.subscription.clone()))
+            .bind(("status", filter.status))
+            .bind(("event", filter.event.clone()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await?
+            .take(0)?)
+    }
+
+    /// Stores the outcome of an attempt on its delivery.
+    pub(super) async fn update_delivery(
+        &self,
+        id: &str,
+        status: DeliveryStatus,
+        attempts: u32,
+        status_code: Option<u16>,
+        error: Option<String>,
+        now: i64,
+    ) -> Result<(), DatabaseError> {
+        self.db
+            .query(
+                "UPDATE type::thing('webhook_delivery', $id) SET status = $status, \
+                 attempts = $attempts, last_status_code = $status_code, \
+                 last_error = $error, updated_at = $now",
+            )
+            .bind(("id", id.to_string()))
+            .bind(("status", status))
+            .bind(("attempts", attempts))
+            .bind(("status_code", status_code))
+            .bind(("error", error))
+            .bind(("now", now))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub(super) async fn record_attempt(&self, content: Value) -> Result<(), DatabaseError> {
+        self.db
+            .query("CREATE webhook_attempt CONTENT $content RETURN NONE")
+            .bind(("content", content))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub(super) async fn attempts(
+        &self,
+        delivery: &str,
+    ) -> Result<Vec<DeliveryAttempt>, DatabaseError> {
+        Ok(self
+            .db
+            .query(format!(
+                "SELECT {FIELDS} FROM webhook_attempt WHERE delivery = $delivery ORDER BY attempt"
+            ))
+            .bind(("delivery", delivery.to_string()))
+            .await?
+            .take(0)?)
+    }
+}
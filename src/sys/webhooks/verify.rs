@@ -0,0 +1,254 @@
+use super::models::{InboundWebhookConfig, SignatureEncoding};
+use crate::{AppError, sys::config::state::AppState};
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tracing::warn;
+
+/// How long nonces are kept for senders that do not sign a timestamp, whose
+/// requests never become too old to replay.
+const UNTIMED_NONCE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// A sender of inbound webhooks, configured as `INBOUND_WEBHOOK__<NAME>__*`.
+///
+/// Each sender gets a marker type naming its configuration group, which
+/// handlers then take as `VerifiedWebhook<Sender>`.
+pub trait WebhookSource: Send + Sync + 'static {
+    /// Lowercase name of the configuration group.
+    const NAME: &'static str;
+}
+
+/// The raw body of a webhook request whose signature, age and uniqueness
+/// have been checked. Rejects the request with 401 otherwise.
+///
+/// The request is remembered as received only if the handler responds with
+/// 2xx, so the sender can retry after a failure; this needs the
+/// [`release_webhook_nonces`] middleware, which `load_middleware` installs.
+#[derive(Debug, Clone)]
+pub struct VerifiedWebhook<S> {
+    pub body: Bytes,
+    /// The request id from the configured id header, if sent.
+    pub id: Option<String>,
+    /// The signed Unix time, if the sender signs one.
+    pub timestamp: Option<i64>,
+    source: PhantomData<S>,
+}
+
+impl<S> VerifiedWebhook<S> {
+    /// Parses the body as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::BadRequest` if the body is not valid JSON for `T`.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, AppError> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| AppError::BadRequest(format!("Invalid webhook payload: {e}")))
+    }
+}
+
+impl<S: WebhookSource> FromRequest<Arc<AppState>> for VerifiedWebhook<S> {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let Some(config) = state.config.load().inbound_webhooks.get(S::NAME).cloned() else {
+            return Err(AppError::ServerError(format!(
+                "Webhook source '{}' is not configured",
+                S::NAME
+            )));
+        };
+        let headers = req.headers().clone();
+        let claim = req.extensions().get::<NonceClaim>().cloned();
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        let now = Utc::now().timestamp();
+        let result = verify(&config, &headers, &body, now);
+        let outcome = match &result {
+            Ok(_) => "verified",
+            Err(_) => "rejected",
+        };
+        let verified = match result {
+            Ok(verified) => verified,
+            Err(reason) => {
+                warn!(source = S::NAME, reason, "Rejected inbound webhook");
+                count(state, outcome);
+                return Err(AppError::Unauthorized(format!(
+                    "Webhook verification failed: {reason}"
+                )));
+            }
+        };
+
+        let ttl = if verified.timestamp.is_some() {
+            // Timestamps may be off by the tolerance in either direction
+            config.tolerance * 2
+        } else {
+            UNTIMED_NONCE_TTL
+        };
+        if !state
+            .webhooks
+            .nonces()
+            .insert(S::NAME, &verified.nonce, ttl)
+            .await?
+        {
+            warn!(source = S::NAME, nonce = %verified.nonce, "Rejected replayed inbound webhook");
+            count(state, "replayed");
+            return Err(AppError::Unauthorized(
+                "Webhook verification failed: already received".to_string(),
+            ));
+        }
+        count(state, outcome);
+        if let Some(claim) = claim {
+            *claim.0.lock().unwrap_or_else(PoisonError::into_inner) =
+                Some((S::NAME, verified.nonce));
+        }
+
+        Ok(Self {
+            body,
+            id: verified.id,
+            timestamp: verified.timestamp,
+            source: PhantomData,
+        })
+    }
+}
+
+/// The nonce a [`VerifiedWebhook`] recorded while handling a request.
+#[derive(Clone, Default)]
+struct NonceClaim(Arc<Mutex<Option<(&'static str, String)>>>);
+
+/// Middleware that forgets the nonce of a verified webhook when the handler
+/// does not respond with 2xx, so the sender's retry is not taken for a
+/// replay.
+pub async fn release_webhook_nonces(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let claim = NonceClaim::default();
+    request.extensions_mut().insert(claim.clone());
+    let response = next.run(request).await;
+
+    let claimed = claim
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    if let Some((source, nonce)) = claimed
+        && !response.status().is_success()
+        && let Err(e) = state.webhooks.nonces().remove(source, &nonce).await
+    {
+        warn!(source, error = %e, "Failed to release the nonce of a failed inbound webhook");
+    }
+    response
+}
+
+fn count(state: &AppState, result: &str) {
+    state
+        .metrics
+        .counter(
+            "inbound_webhooks_total",
+            "Inbound webhooks by verification result",
+            &[("result", result)],
+        )
+        .inc();
+}
+
+/// What a valid signature vouches for.
+struct Verified {
+    id: Option<String>,
+    timestamp: Option<i64>,
+    /// Identifies the request for replay detection.
+    nonce: String,
+}
+
+/// Checks the signature and age of a request at `now`, in seconds since the
+/// Unix epoch. Errors describe why the request was rejected.
+fn verify(
+    config: &InboundWebhookConfig,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> Result<Verified, &'static str> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+
+    let signatures = header(&config.signature_header).ok_or("missing signature")?;
+    let timestamp = match &config.timestamp_header {
+        Some(name) => {
+            let timestamp: i64 = header(name)
+                .ok_or("missing timestamp")?
+                .parse()
+                .map_err(|_| "invalid timestamp")?;
+            let tolerance = i64::try_from(config.tolerance.as_secs()).unwrap_or(i64::MAX);
+            if now.abs_diff(timestamp) > tolerance.unsigned_abs() {
+                return Err("timestamp outside the tolerance");
+            }
+            Some(timestamp)
+        }
+        None => None,
+    };
+
+    let id = config.id_header.as_deref().and_then(header);
+    let signed_id = match id {
+        Some(id) if config.signed_id => Some(id),
+        None if config.signed_id && config.id_header.is_some() => return Err("missing id"),
+        _ => None,
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(config.secret.expose().as_bytes())
+        .unwrap_or_else(|_| unreachable!("HMAC accepts keys of any length"));
+    if let Some(id) = signed_id {
+        mac.update(id.as_bytes());
+        mac.update(b".");
+    }
+    if let Some(timestamp) = timestamp {
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+    }
+    mac.update(body);
+
+    let signature = signatures
+        .split([',', ' '])
+        .filter_map(|signature| {
+            signature
+                .trim()
+                .strip_prefix(config.signature_prefix.as_str())
+        })
+        .filter_map(|encoded| match config.encoding {
+            SignatureEncoding::Hex => hex::decode(encoded).ok(),
+            SignatureEncoding::Base64 => STANDARD.decode(encoded).ok(),
+        })
+        .find(|signature| mac.clone().verify_slice(signature).is_ok())
+        .ok_or("invalid signature")?;
+
+    // Only signed content identifies a request: an unsigned id, or extra
+    // signatures in the header, could be changed to replay it
+    let nonce = match signed_id {
+        Some(id) => format!("id:{id}"),
+        None => format!("sig:{}", hex::encode(signature)),
+    };
+    Ok(Verified {
+        nonce,
+        id: id.map(str::to_string),
+        timestamp,
+    })
+}
@@ -0,0 +1,87 @@
+use axum_backend::sys::{
+    config::{AppConfig, ConfigHandle, cli::CliArgs, state::AppState},
+    events::EventBus,
+    health::models::HealthTracker,
+    init::{
+        load_cache, load_idempotency_store, load_job_queue, load_outbox, load_rate_limiter,
+        load_scheduler, load_tenants, load_webhooks,
+    },
+    metrics::MetricsRegistry,
+    middleware::load_shed::LoadShedder,
+    server::Shutdown,
+};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Builds the application state over a fresh in-memory database, with
+/// `flags` overriding configuration as on the command line.
+pub async fn state(flags: &[&str]) -> Arc<AppState> {
+    let args = [
+        "--db-endpoint=mem://",
+        "--db-namespace=test",
+        "--db-name=test",
+        "--db-username=test",
+        "--db-password=test",
+    ]
+    .iter()
+    .chain(flags)
+    .map(ToString::to_string);
+    let config = AppConfig::load_from(CliArgs::parse(args)).unwrap();
+
+    let db = surrealdb::engine::any::connect("mem://").await.unwrap();
+    db.use_ns("test").use_db("test").await.unwrap();
+    let db = Arc::new(db);
+    let databases = BTreeMap::new();
+
+    let metrics = Arc::new(MetricsRegistry::new());
+    let shutdown = Shutdown::new();
+    let events = EventBus::new(config.events.clone(), shutdown.clone(), metrics.clone());
+    let jobs = load_job_queue(&config, &db, &databases, &shutdown, &metrics).unwrap();
+    let webhooks = load_webhooks(&config, &db, &databases, &jobs, &shutdown, &metrics).unwrap();
+    let outbox = load_outbox(&config, &db, &webhooks, &events, &shutdown, &metrics);
+
+    Arc::new(AppState {
+        rate_limiter: load_rate_limiter(&config, &db, &databases, &shutdown).unwrap(),
+        load_shedder: LoadShedder::new(config.load_shed.clone(), metrics.clone()),
+        idempotency: load_idempotency_store(&config, &db, &databases, &shutdown).unwrap(),
+        cache: load_cache(&config, &db, &databases, &shutdown, &metrics).unwrap(),
+        scheduler: load_scheduler(&config, &db, &databases, &shutdown, &metrics).unwrap(),
+        tenants: load_tenants(&config, &db, &databases, &events, &metrics).unwrap(),
+        health_checkers: Arc::new(Vec::new()),
+        health_tracker: Arc::new(HealthTracker::new()),
+        config: Arc::new(ConfigHandle::new(config)),
+        db_connection: db,
+        databases,
+        jobs,
+        webhooks,
+        outbox,
+        events,
+        shutdown,
+        metrics,
+    })
+}
+
+/// Polls `check` until it returns a value or `timeout` passes.
+// Not every test crate waits on background work
+#[allow(dead_code)]
+pub async fn eventually<T, F, Fut>(timeout: Duration, mut check: F) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = check().await {
+            return value;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "condition not met within {timeout:?}"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    extract::ConnectInfo,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::from_fn_with_state,
+    routing::get,
+};
+use axum_backend::sys::middleware::rate_limit::rate_limit;
+use std::{net::SocketAddr, time::Duration};
+use tower::ServiceExt;
+
+/// Starts the rate limiter over the SurrealDB store with `quota`.
+async fn app(quota: &str) -> Router {
+    let quota = format!("--rate-limit-default={quota}");
+    let state = common::state(&[
+        "--rate-limit-enabled=true",
+        "--rate-limit-store=surrealdb",
+        &quota,
+    ])
+    .await;
+    Router::new()
+        .route("/", get(|| async { "ok" }))
+        .layer(from_fn_with_state(state.clone(), rate_limit))
+        .with_state(state)
+}
+
+/// Sends a request from `ip` and returns its status and headers.
+async fn send(app: &Router, ip: [u8; 4]) -> (StatusCode, HeaderMap) {
+    let request = Request::builder()
+        .uri("/")
+        .extension(ConnectInfo(SocketAddr::from((ip, 40_000))))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    (response.status(), response.headers().clone())
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers.get(name).unwrap().to_str().unwrap()
+}
+
+#[tokio::test]
+async fn allows_the_quota_then_rejects_with_retry_after() {
+    let app = app("2/1m").await;
+
+    let (status, headers) = send(&app, [10, 0, 0, 1]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header(&headers, "ratelimit-limit"), "2");
+    assert_eq!(header(&headers, "ratelimit-remaining"), "1");
+    assert_eq!(header(&headers, "ratelimit-reset"), "30");
+    assert_eq!(header(&headers, "ratelimit-policy"), "2;w=60");
+    assert!(headers.get("retry-after").is_none());
+
+    let (status, headers) = send(&app, [10, 0, 0, 1]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header(&headers, "ratelimit-remaining"), "0");
+    assert_eq!(header(&headers, "ratelimit-reset"), "60");
+
+    let (status, headers) = send(&app, [10, 0, 0, 1]).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header(&headers, "ratelimit-remaining"), "0");
+    assert_eq!(header(&headers, "ratelimit-reset"), "60");
+    assert_eq!(header(&headers, "retry-after"), "30");
+}
+
+#[tokio::test]
+async fn counts_each_client_separately() {
+    let app = app("1/1m").await;
+
+    assert_eq!(send(&app, [10, 0, 0, 1]).await.0, StatusCode::OK);
+    assert_eq!(
+        send(&app, [10, 0, 0, 1]).await.0,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(send(&app, [10, 0, 0, 2]).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn allows_requests_again_once_the_reset_passes() {
+    let app = app("1/1s").await;
+
+    let (status, headers) = send(&app, [10, 0, 0, 1]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header(&headers, "ratelimit-reset"), "1");
+    let (status, headers) = send(&app, [10, 0, 0, 1]).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header(&headers, "retry-after"), "1");
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let (status, headers) = send(&app, [10, 0, 0, 1]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header(&headers, "ratelimit-remaining"), "0");
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_backend::{
+    AppError,
+    dbs::{error::DatabaseError, repository::Repository},
+};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Order {
+    status: String,
+}
+
+fn order(status: &str) -> Order {
+    Order {
+        status: status.to_string(),
+    }
+}
+
+async fn orders() -> Repository<Order> {
+    let state = common::state(&[]).await;
+    Repository::new(state.db_connection.clone(), "order")
+}
+
+#[tokio::test]
+async fn stale_updates_fail_with_a_version_mismatch() {
+    let orders = orders().await;
+    let created = orders.create_with_id("1", &order("new")).await.unwrap();
+    assert_eq!(created.version, 1);
+
+    let updated = orders.update("1", 1, &order("paid")).await.unwrap();
+    assert_eq!(updated.version, 2);
+
+    let stale = orders
+        .update("1", 1, &order("cancelled"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&stale, DatabaseError::VersionMismatch(msg) if msg.contains("version 2, not 1")),
+        "{stale:?}"
+    );
+    let current = orders.get("1").await.unwrap().unwrap();
+    assert_eq!((current.version, current.data), (2, order("paid")));
+}
+
+#[tokio::test]
+async fn stale_deletes_fail_with_a_version_mismatch() {
+    let orders = orders().await;
+    orders.create_with_id("1", &order("new")).await.unwrap();
+    orders.update("1", 1, &order("paid")).await.unwrap();
+
+    let stale = orders.delete("1", 1).await.unwrap_err();
+    assert!(
+        matches!(stale, DatabaseError::VersionMismatch(_)),
+        "{stale:?}"
+    );
+    assert!(orders.get("1").await.unwrap().is_some());
+
+    orders.delete("1", 2).await.unwrap();
+    assert!(orders.get("1").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn version_mismatches_are_answered_with_409() {
+    let orders = orders().await;
+    orders.create_with_id("1", &order("new")).await.unwrap();
+    orders.update("1", 1, &order("paid")).await.unwrap();
+    let stale = orders
+        .update("1", 1, &order("cancelled"))
+        .await
+        .unwrap_err();
+
+    let response = AppError::from(stale).into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"], "order '1' is at version 2, not 1");
+}
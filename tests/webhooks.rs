@@ -0,0 +1,309 @@
+mod common;
+
+use axum::{
+    Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    middleware::from_fn_with_state,
+    routing::post,
+};
+use axum_backend::sys::{
+    config::state::AppState,
+    webhooks::{
+        Delivery, DeliveryStatus, ID_HEADER, NewSubscription, SIGNATURE_HEADER, Subscription,
+        TIMESTAMP_HEADER, VerifiedWebhook, WebhookSource, release_webhook_nonces, sign,
+    },
+};
+use common::eventually;
+use serde_json::json;
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+
+const SECRET: &str = "0123456789abcdef0123";
+
+const TENANT: &str = "acme";
+const WAIT: Duration = Duration::from_secs(10);
+
+/// Requests received by a test endpoint, as headers and body.
+type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+#[derive(Clone)]
+struct Receiver {
+    status: StatusCode,
+    received: Received,
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: String) -> StatusCode {
+    receiver.received.lock().unwrap().push((headers, body));
+    receiver.status
+}
+
+/// Starts an endpoint answering every webhook with `status`. Returns its URL
+/// and the requests it receives.
+async fn receiver(status: StatusCode) -> (String, Received) {
+    let received = Received::default();
+    let app = Router::new()
+        .route("/hook", post(receive))
+        .with_state(Receiver {
+            status,
+            received: received.clone(),
+        });
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (url, received)
+}
+
+/// Starts job workers with quick retries, with `flags` overriding the rest.
+async fn start(flags: &[&str]) -> Arc<AppState> {
+    let flags: Vec<&str> = [
+        "--jobs-poll-interval=20ms",
+        "--jobs-backoff-base=10ms",
+        "--jobs-backoff-max=20ms",
+    ]
+    .iter()
+    .chain(flags)
+    .copied()
+    .collect();
+    let state = common::state(&flags).await;
+    state.jobs.start(&state);
+    state
+}
+
+async fn subscribe(state: &AppState, url: String) -> Subscription {
+    state
+        .webhooks
+        .subscribe(
+            TENANT,
+            NewSubscription {
+                url,
+                events: vec!["order.*".to_string()],
+                secret: Some(SECRET.to_string()),
+                description: None,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+async fn publish(state: &AppState) -> String {
+    let deliveries = state
+        .webhooks
+        .publish(TENANT, "order.created", &json!({ "order": 1 }))
+        .await
+        .unwrap();
+    assert_eq!(deliveries.len(), 1);
+    deliveries[0].clone()
+}
+
+async fn delivery(state: &AppState, id: &str) -> Delivery {
+    state.webhooks.delivery(TENANT, id).await.unwrap().unwrap()
+}
+
+/// Waits until a delivery has `status`.
+async fn settled(state: &AppState, id: &str, status: DeliveryStatus) -> Delivery {
+    eventually(WAIT, || async {
+        Some(delivery(state, id).await).filter(|delivery| delivery.status == status)
+    })
+    .await
+}
+
+#[tokio::test]
+async fn signs_deliveries_with_the_subscription_secret() {
+    let state = start(&[]).await;
+    let (url, received) = receiver(StatusCode::OK).await;
+    let subscription = subscribe(&state, url).await;
+
+    let id = publish(&state).await;
+    let delivery = settled(&state, &id, DeliveryStatus::Succeeded).await;
+    assert_eq!(delivery.attempts, 1);
+
+    let (headers, body) = received.lock().unwrap()[0].clone();
+    let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+    let signature = sign(
+        subscription.secret.as_bytes(),
+        &id,
+        timestamp,
+        body.as_bytes(),
+    );
+    assert_eq!(headers[SIGNATURE_HEADER], signature.as_str());
+    assert_eq!(headers[ID_HEADER], id.as_str());
+}
+
+#[tokio::test]
+async fn fails_after_max_attempts_and_logs_every_attempt() {
+    let state = start(&[
+        "--webhooks-max-attempts=3",
+        "--webhooks-failure-threshold=100",
+    ])
+    .await;
+    let (url, received) = receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+    subscribe(&state, url).await;
+
+    let id = publish(&state).await;
+    let delivery = settled(&state, &id, DeliveryStatus::Failed).await;
+    assert_eq!(delivery.attempts, 3);
+    assert_eq!(delivery.last_status_code, Some(500));
+
+    let attempts = state.webhooks.attempts(&id).await.unwrap();
+    let numbers: Vec<u32> = attempts.iter().map(|attempt| attempt.attempt).collect();
+    assert_eq!(numbers, [1, 2, 3]);
+    assert!(attempts.iter().all(|attempt| !attempt.success));
+    assert!(
+        attempts
+            .iter()
+            .all(|attempt| attempt.status_code == Some(500))
+    );
+    assert_eq!(received.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn open_circuit_holds_deliveries_without_spending_attempts() {
+    let state = start(&[
+        "--webhooks-max-attempts=5",
+        "--webhooks-failure-threshold=2",
+        "--webhooks-circuit-cooldown=1m",
+    ])
+    .await;
+    let (url, received) = receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+    let subscription = subscribe(&state, url).await;
+
+    let first = publish(&state).await;
+    eventually(WAIT, || async {
+        let subscriptions = state.webhooks.subscriptions(TENANT).await.unwrap();
+        subscriptions
+            .into_iter()
+            .find(|s| s.id == subscription.id && s.circuit_open_until.is_some())
+    })
+    .await;
+    let second = publish(&state).await;
+
+    // Give held deliveries time to be retried if the circuit let them
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let first = delivery(&state, &first).await;
+    assert_eq!(first.status, DeliveryStatus::Pending);
+    assert_eq!(first.attempts, 2);
+    let second = delivery(&state, &second).await;
+    assert_eq!(second.status, DeliveryStatus::Pending);
+    assert_eq!(second.attempts, 0);
+    assert_eq!(state.webhooks.attempts(&first.id).await.unwrap().len(), 2);
+    assert_eq!(received.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn replay_sends_a_new_delivery() {
+    let state = start(&[]).await;
+    let (url, received) = receiver(StatusCode::OK).await;
+    subscribe(&state, url).await;
+
+    let original = publish(&state).await;
+    let original = settled(&state, &original, DeliveryStatus::Succeeded).await;
+
+    let replayed = state
+        .webhooks
+        .replay(TENANT, &original.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(replayed, original.id);
+    let replayed = settled(&state, &replayed, DeliveryStatus::Succeeded).await;
+    assert_eq!(replayed.replay_of.as_deref(), Some(original.id.as_str()));
+    assert_eq!(replayed.event_id, original.event_id);
+    assert_eq!(replayed.attempts, 1);
+    assert_eq!(received.lock().unwrap().len(), 2);
+
+    assert!(
+        state
+            .webhooks
+            .replay(TENANT, "missing")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+struct Partner;
+
+impl WebhookSource for Partner {
+    const NAME: &'static str = "partner";
+}
+
+async fn verified(webhook: VerifiedWebhook<Partner>) -> String {
+    webhook.id.unwrap_or_default()
+}
+
+#[tokio::test]
+async fn inbound_replays_are_rejected_even_with_a_new_id() {
+    let secret = format!("--inbound-webhook--partner--secret={SECRET}");
+    let state = common::state(&[&secret]).await;
+    let app = Router::new()
+        .route("/inbound", post(verified))
+        .with_state(state);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/inbound", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let body = r#"{"event":"order.created"}"#;
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign(SECRET.as_bytes(), "evt_1", timestamp, body.as_bytes());
+    let send = |id: &'static str| {
+        reqwest::Client::new()
+            .post(&url)
+            .header(ID_HEADER, id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body)
+            .send()
+    };
+
+    assert_eq!(send("evt_1").await.unwrap().status(), 200);
+    assert_eq!(send("evt_1").await.unwrap().status(), 401);
+    assert_eq!(send("evt_2").await.unwrap().status(), 401);
+}
+
+#[tokio::test]
+async fn inbound_retries_are_accepted_after_a_failed_handler() {
+    let secret = format!("--inbound-webhook--partner--secret={SECRET}");
+    let state = common::state(&[&secret]).await;
+    let failed = Arc::new(AtomicBool::new(false));
+    let handler = {
+        let failed = failed.clone();
+        move |webhook: VerifiedWebhook<Partner>| async move {
+            if failed.swap(true, Ordering::SeqCst) {
+                (StatusCode::OK, webhook.id.unwrap_or_default())
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+            }
+        }
+    };
+    let app = Router::new()
+        .route("/inbound", post(handler))
+        .layer(from_fn_with_state(state.clone(), release_webhook_nonces))
+        .with_state(state);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/inbound", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let body = r#"{"event":"order.created"}"#;
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign(SECRET.as_bytes(), "evt_1", timestamp, body.as_bytes());
+    let send = || {
+        reqwest::Client::new()
+            .post(&url)
+            .header(ID_HEADER, "evt_1")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body)
+            .send()
+    };
+
+    assert_eq!(send().await.unwrap().status(), 500);
+    assert_eq!(send().await.unwrap().status(), 200);
+    assert_eq!(send().await.unwrap().status(), 401);
+}